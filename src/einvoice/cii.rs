@@ -0,0 +1,1209 @@
+use super::parse::{
+    LineTax, Node, ParseReport, check_allowance_charge, check_amount, electronic_address,
+    out_of_range, parse_address, parse_base_quantity, parse_cii_date, parse_country,
+    parse_currency, parse_decimal, parse_document_type, parse_percent, parse_unit, payment_info,
+    summary_line, tax_fields,
+};
+use super::{
+    BUYER_REFERENCE, EInvoiceProfile, PAYMENT_MEANS_CREDIT_TRANSFER, TAX_REGISTRATION_SCHEME,
+    additional_lines, to_cii_date, to_percent, write_element, write_element_with_attr,
+};
+use crate::error::InvoiceError;
+use crate::models::{
+    Address, AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind, BillingReference, Buyer,
+    Decimal, DocumentType, ElectronicAddress, Invoice, InvoiceSummary, ItemAttribute,
+    ItemClassification, ItemIdentifier, Money, Product, Seller, TaxCategory,
+};
+use locale_rs::Locale;
+use locale_rs::datetime_formats::DateTime;
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
+use std::io::Cursor;
+
+impl Invoice {
+    /// Serializes the invoice as UN/CEFACT Cross Industry Invoice (D16B), the syntax
+    /// embedded in ZUGFeRD / Factur-X PDFs.
+    pub fn to_cii_xml(&self, profile: EInvoiceProfile) -> Result<String, InvoiceError> {
+        profile.validate(self)?;
+        let summary = self.try_calculate_summary()?;
+
+        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 4);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+        let mut root = BytesStart::new("rsm:CrossIndustryInvoice");
+        root.push_attribute((
+            "xmlns:rsm",
+            "urn:un:unece:uncefact:data:standard:CrossIndustryInvoice:100",
+        ));
+        root.push_attribute((
+            "xmlns:ram",
+            "urn:un:unece:uncefact:data:standard:ReusableAggregateBusinessInformationEntity:100",
+        ));
+        root.push_attribute((
+            "xmlns:udt",
+            "urn:un:unece:uncefact:data:standard:UnqualifiedDataType:100",
+        ));
+        root.push_attribute((
+            "xmlns:qdt",
+            "urn:un:unece:uncefact:data:standard:QualifiedDataType:100",
+        ));
+        writer.write_event(Event::Start(root))?;
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "rsm:ExchangedDocumentContext",
+        )))?;
+        if let Some(process) = profile.business_process() {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:BusinessProcessSpecifiedDocumentContextParameter",
+            )))?;
+            write_element(&mut writer, "ram:ID", process)?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:BusinessProcessSpecifiedDocumentContextParameter",
+            )))?;
+        }
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:GuidelineSpecifiedDocumentContextParameter",
+        )))?;
+        write_element(&mut writer, "ram:ID", profile.guideline_id())?;
+        writer.write_event(Event::End(BytesEnd::new(
+            "ram:GuidelineSpecifiedDocumentContextParameter",
+        )))?;
+        writer.write_event(Event::End(BytesEnd::new("rsm:ExchangedDocumentContext")))?;
+
+        writer.write_event(Event::Start(BytesStart::new("rsm:ExchangedDocument")))?;
+        write_element(&mut writer, "ram:ID", &self.number)?;
+        write_element(&mut writer, "ram:TypeCode", self.document_type.code())?;
+        write_date(&mut writer, "ram:IssueDateTime", &self.date)?;
+        writer.write_event(Event::End(BytesEnd::new("rsm:ExchangedDocument")))?;
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "rsm:SupplyChainTradeTransaction",
+        )))?;
+
+        if profile.includes_lines() {
+            self.write_cii_lines(&mut writer, profile)?;
+        }
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:ApplicableHeaderTradeAgreement",
+        )))?;
+        write_element(&mut writer, "ram:BuyerReference", BUYER_REFERENCE)?;
+        self.write_cii_seller(&mut writer, profile)?;
+        self.write_cii_buyer(&mut writer, profile)?;
+        writer.write_event(Event::End(BytesEnd::new(
+            "ram:ApplicableHeaderTradeAgreement",
+        )))?;
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:ApplicableHeaderTradeDelivery",
+        )))?;
+        if let Some(country) = self.delivery_country.filter(|_| profile.includes_details()) {
+            writer.write_event(Event::Start(BytesStart::new("ram:ShipToTradeParty")))?;
+            writer.write_event(Event::Start(BytesStart::new("ram:PostalTradeAddress")))?;
+            write_element(&mut writer, "ram:CountryID", country.code())?;
+            writer.write_event(Event::End(BytesEnd::new("ram:PostalTradeAddress")))?;
+            writer.write_event(Event::End(BytesEnd::new("ram:ShipToTradeParty")))?;
+        }
+        if let Some(delivery_date) = self
+            .delivery_date
+            .as_ref()
+            .filter(|_| profile.includes_details())
+        {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:ActualDeliverySupplyChainEvent",
+            )))?;
+            write_date(&mut writer, "ram:OccurrenceDateTime", delivery_date)?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:ActualDeliverySupplyChainEvent",
+            )))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new(
+            "ram:ApplicableHeaderTradeDelivery",
+        )))?;
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:ApplicableHeaderTradeSettlement",
+        )))?;
+        write_element(&mut writer, "ram:InvoiceCurrencyCode", self.currency.code())?;
+
+        if profile.includes_details() {
+            self.write_cii_settlement_details(&mut writer, profile, &summary)?;
+        }
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:SpecifiedTradeSettlementHeaderMonetarySummation",
+        )))?;
+        if profile.includes_details() {
+            write_amount(&mut writer, "ram:LineTotalAmount", &summary.subtotal)?;
+            let has = |kind| self.allowances_charges.iter().any(|e| e.kind == kind);
+            if has(AllowanceChargeKind::Charge) {
+                write_amount(&mut writer, "ram:ChargeTotalAmount", &summary.charge_total)?;
+            }
+            if has(AllowanceChargeKind::Allowance) {
+                write_amount(
+                    &mut writer,
+                    "ram:AllowanceTotalAmount",
+                    &summary.allowance_total,
+                )?;
+            }
+        }
+        write_amount(&mut writer, "ram:TaxBasisTotalAmount", &summary.net_total)?;
+        let mut tax_total = BytesStart::new("ram:TaxTotalAmount");
+        tax_total.push_attribute(("currencyID", self.currency.code()));
+        write_element_with_attr(
+            &mut writer,
+            tax_total,
+            &summary.tax_total.round().to_string(),
+        )?;
+        // MINIMUM has no prepaid or rounding amount, only the resulting amount due.
+        if profile.includes_details() && self.rounding_amount.is_some() {
+            write_amount(&mut writer, "ram:RoundingAmount", &summary.rounding)?;
+        }
+        write_amount(&mut writer, "ram:GrandTotalAmount", &summary.total)?;
+        if profile.includes_details() && self.has_prepayment() {
+            write_amount(&mut writer, "ram:TotalPrepaidAmount", &summary.prepaid)?;
+        }
+        write_amount(&mut writer, "ram:DuePayableAmount", &summary.amount_due)?;
+        writer.write_event(Event::End(BytesEnd::new(
+            "ram:SpecifiedTradeSettlementHeaderMonetarySummation",
+        )))?;
+
+        if profile.includes_details()
+            && let Some(reference) = &self.billing_reference
+        {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:InvoiceReferencedDocument",
+            )))?;
+            write_element(&mut writer, "ram:IssuerAssignedID", &reference.number)?;
+            if let Some(date) = &reference.date {
+                writer.write_event(Event::Start(BytesStart::new("ram:FormattedIssueDateTime")))?;
+                let mut value = BytesStart::new("qdt:DateTimeString");
+                value.push_attribute(("format", "102"));
+                write_element_with_attr(&mut writer, value, &to_cii_date(date))?;
+                writer.write_event(Event::End(BytesEnd::new("ram:FormattedIssueDateTime")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("ram:InvoiceReferencedDocument")))?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new(
+            "ram:ApplicableHeaderTradeSettlement",
+        )))?;
+
+        writer.write_event(Event::End(BytesEnd::new("rsm:SupplyChainTradeTransaction")))?;
+        writer.write_event(Event::End(BytesEnd::new("rsm:CrossIndustryInvoice")))?;
+        Ok(String::from_utf8(writer.into_inner().into_inner())?)
+    }
+
+    fn write_cii_lines(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        profile: EInvoiceProfile,
+    ) -> Result<(), InvoiceError> {
+        for (i, prod) in self.products.iter().enumerate() {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:IncludedSupplyChainTradeLineItem",
+            )))?;
+
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:AssociatedDocumentLineDocument",
+            )))?;
+            write_element(writer, "ram:LineID", &(i + 1).to_string())?;
+            if let Some(note) = &prod.note {
+                writer.write_event(Event::Start(BytesStart::new("ram:IncludedNote")))?;
+                write_element(writer, "ram:Content", note)?;
+                writer.write_event(Event::End(BytesEnd::new("ram:IncludedNote")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:AssociatedDocumentLineDocument",
+            )))?;
+
+            write_trade_product(writer, prod, profile)?;
+
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:SpecifiedLineTradeAgreement",
+            )))?;
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:NetPriceProductTradePrice",
+            )))?;
+            write_element(writer, "ram:ChargeAmount", &prod.cost_per_unit.to_string())?;
+            if let Some(base) = prod.price_base_quantity {
+                let mut base_qty = BytesStart::new("ram:BasisQuantity");
+                base_qty.push_attribute(("unitCode", prod.unit.code()));
+                write_element_with_attr(writer, base_qty, &base.normalize().to_string())?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("ram:NetPriceProductTradePrice")))?;
+            writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedLineTradeAgreement")))?;
+
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:SpecifiedLineTradeDelivery",
+            )))?;
+            let mut qty = BytesStart::new("ram:BilledQuantity");
+            qty.push_attribute(("unitCode", prod.unit.code()));
+            write_element_with_attr(writer, qty, &prod.quantity.normalize().to_string())?;
+            writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedLineTradeDelivery")))?;
+
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:SpecifiedLineTradeSettlement",
+            )))?;
+            writer.write_event(Event::Start(BytesStart::new("ram:ApplicableTradeTax")))?;
+            write_element(writer, "ram:TypeCode", "VAT")?;
+            write_element(writer, "ram:CategoryCode", prod.tax_category.code())?;
+            if prod.tax_category.has_rate() {
+                write_element(
+                    writer,
+                    "ram:RateApplicablePercent",
+                    &to_percent(prod.effective_tax_rate()),
+                )?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("ram:ApplicableTradeTax")))?;
+            for entry in &prod.allowances_charges {
+                write_allowance_charge(writer, entry, prod.base_amount(self.currency), false)?;
+            }
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:SpecifiedTradeSettlementLineMonetarySummation",
+            )))?;
+            write_amount(
+                writer,
+                "ram:LineTotalAmount",
+                &prod.net_amount(self.currency),
+            )?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:SpecifiedTradeSettlementLineMonetarySummation",
+            )))?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:SpecifiedLineTradeSettlement",
+            )))?;
+
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:IncludedSupplyChainTradeLineItem",
+            )))?;
+        }
+        Ok(())
+    }
+
+    /// Payment means, VAT breakdown and payment terms, omitted by the MINIMUM profile.
+    fn write_cii_settlement_details(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        profile: EInvoiceProfile,
+        summary: &InvoiceSummary,
+    ) -> Result<(), InvoiceError> {
+        // Credit transfer is the only payment means written, and it needs the account.
+        if let Some((iban, bic)) = self.bank_account() {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:SpecifiedTradeSettlementPaymentMeans",
+            )))?;
+            write_element(writer, "ram:TypeCode", PAYMENT_MEANS_CREDIT_TRANSFER)?;
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:PayeePartyCreditorFinancialAccount",
+            )))?;
+            write_element(writer, "ram:IBANID", iban)?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:PayeePartyCreditorFinancialAccount",
+            )))?;
+            if let Some(bic) = bic.filter(|_| profile.includes_contacts()) {
+                writer.write_event(Event::Start(BytesStart::new(
+                    "ram:PayeeSpecifiedCreditorFinancialInstitution",
+                )))?;
+                write_element(writer, "ram:BICID", bic)?;
+                writer.write_event(Event::End(BytesEnd::new(
+                    "ram:PayeeSpecifiedCreditorFinancialInstitution",
+                )))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:SpecifiedTradeSettlementPaymentMeans",
+            )))?;
+        }
+
+        for subtotal in &summary.tax_subtotals {
+            writer.write_event(Event::Start(BytesStart::new("ram:ApplicableTradeTax")))?;
+            write_amount(writer, "ram:CalculatedAmount", &subtotal.tax_amount)?;
+            write_element(writer, "ram:TypeCode", "VAT")?;
+            if !subtotal.exemption_reasons.is_empty() {
+                write_element(
+                    writer,
+                    "ram:ExemptionReason",
+                    &subtotal.exemption_reasons.join(", "),
+                )?;
+            }
+            write_amount(writer, "ram:BasisAmount", &subtotal.taxable_amount)?;
+            write_element(writer, "ram:CategoryCode", subtotal.category.code())?;
+            if let Some(code) = &subtotal.exemption_code {
+                write_element(writer, "ram:ExemptionReasonCode", code)?;
+            }
+            write_element(
+                writer,
+                "ram:RateApplicablePercent",
+                &to_percent(subtotal.rate),
+            )?;
+            writer.write_event(Event::End(BytesEnd::new("ram:ApplicableTradeTax")))?;
+        }
+
+        for entry in &self.allowances_charges {
+            write_allowance_charge(writer, entry, self.allowance_charge_base(entry), true)?;
+        }
+
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:SpecifiedTradePaymentTerms",
+        )))?;
+        write_date(writer, "ram:DueDateDateTime", &self.payment_due)?;
+        writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedTradePaymentTerms")))?;
+        Ok(())
+    }
+
+    fn write_cii_seller(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        profile: EInvoiceProfile,
+    ) -> Result<(), InvoiceError> {
+        writer.write_event(Event::Start(BytesStart::new("ram:SellerTradeParty")))?;
+        write_element(writer, "ram:Name", &self.seller.name)?;
+        if profile.includes_contacts()
+            && let Some(form) = &self.seller.legal_form
+        {
+            write_element(writer, "ram:Description", form)?;
+        }
+        write_legal_organization(writer, self.seller.legal_registration_id.as_deref())?;
+
+        if profile.includes_contacts() {
+            writer.write_event(Event::Start(BytesStart::new("ram:DefinedTradeContact")))?;
+            write_element(writer, "ram:PersonName", &self.seller.name)?;
+            if let Some(phone) = &self.seller.phone {
+                writer.write_event(Event::Start(BytesStart::new(
+                    "ram:TelephoneUniversalCommunication",
+                )))?;
+                write_element(writer, "ram:CompleteNumber", phone)?;
+                writer.write_event(Event::End(BytesEnd::new(
+                    "ram:TelephoneUniversalCommunication",
+                )))?;
+            }
+            if let Some(email) = &self.seller.email {
+                write_email(writer, email)?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("ram:DefinedTradeContact")))?;
+        }
+
+        write_address(writer, &self.seller.address, profile)?;
+        if profile.includes_details() {
+            write_endpoint(writer, self.seller.endpoint())?;
+        }
+
+        write_tax_registration(writer, &self.seller.vat_id, "VA")?;
+        if let Some(number) = &self.seller.tax_registration_number {
+            write_tax_registration(writer, number, TAX_REGISTRATION_SCHEME)?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("ram:SellerTradeParty")))?;
+        Ok(())
+    }
+
+    fn write_cii_buyer(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+        profile: EInvoiceProfile,
+    ) -> Result<(), InvoiceError> {
+        writer.write_event(Event::Start(BytesStart::new("ram:BuyerTradeParty")))?;
+        write_element(writer, "ram:Name", &self.buyer.name)?;
+        write_legal_organization(writer, self.buyer.legal_registration_id.as_deref())?;
+
+        if profile.includes_contacts() && !self.buyer.email.is_empty() {
+            writer.write_event(Event::Start(BytesStart::new("ram:DefinedTradeContact")))?;
+            write_element(writer, "ram:PersonName", &self.buyer.name)?;
+            write_email(writer, &self.buyer.email)?;
+            writer.write_event(Event::End(BytesEnd::new("ram:DefinedTradeContact")))?;
+        }
+
+        // MINIMUM carries the buyer's name and registration only.
+        if profile.includes_details() {
+            write_address(writer, &self.buyer.address, profile)?;
+            write_endpoint(writer, self.buyer.endpoint())?;
+            if let Some(vat_id) = &self.buyer.vat_id {
+                write_tax_registration(writer, vat_id, "VA")?;
+            }
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("ram:BuyerTradeParty")))?;
+        Ok(())
+    }
+}
+
+/// Amounts carry no currency in CII; the document currency applies.
+fn write_amount(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    tag: &str,
+    amount: &Money,
+) -> Result<(), InvoiceError> {
+    write_element(writer, tag, &amount.round().to_string())
+}
+
+/// `ram:SpecifiedTradeAllowanceCharge`; only header entries state their VAT category.
+fn write_allowance_charge(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    entry: &AllowanceCharge,
+    base: Money,
+    with_tax: bool,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new(
+        "ram:SpecifiedTradeAllowanceCharge",
+    )))?;
+    writer.write_event(Event::Start(BytesStart::new("ram:ChargeIndicator")))?;
+    write_element(
+        writer,
+        "udt:Indicator",
+        if entry.is_charge() { "true" } else { "false" },
+    )?;
+    writer.write_event(Event::End(BytesEnd::new("ram:ChargeIndicator")))?;
+    if let Some(rate) = entry.percentage() {
+        write_element(writer, "ram:CalculationPercent", &to_percent(rate))?;
+        write_amount(writer, "ram:BasisAmount", &base)?;
+    }
+    write_amount(writer, "ram:ActualAmount", &entry.amount_on(base))?;
+    if let Some(code) = &entry.reason_code {
+        write_element(writer, "ram:ReasonCode", code)?;
+    }
+    if let Some(reason) = &entry.reason {
+        write_element(writer, "ram:Reason", reason)?;
+    }
+    if with_tax {
+        writer.write_event(Event::Start(BytesStart::new("ram:CategoryTradeTax")))?;
+        write_element(writer, "ram:TypeCode", "VAT")?;
+        write_element(writer, "ram:CategoryCode", entry.tax_category.code())?;
+        if entry.tax_category.has_rate() {
+            write_element(
+                writer,
+                "ram:RateApplicablePercent",
+                &to_percent(entry.effective_tax_rate()),
+            )?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("ram:CategoryTradeTax")))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new(
+        "ram:SpecifiedTradeAllowanceCharge",
+    )))?;
+    Ok(())
+}
+
+/// `ram:SpecifiedTradeProduct`; BASIC only carries the name and the standard identifier.
+fn write_trade_product(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    prod: &Product,
+    profile: EInvoiceProfile,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("ram:SpecifiedTradeProduct")))?;
+    if let Some(standard) = &prod.standard_item_id {
+        let mut el = BytesStart::new("ram:GlobalID");
+        el.push_attribute(("schemeID", standard.scheme.as_str()));
+        write_element_with_attr(writer, el, &standard.id)?;
+    }
+    if profile.includes_item_details() {
+        if let Some(id) = &prod.seller_item_id {
+            write_element(writer, "ram:SellerAssignedID", id)?;
+        }
+        if let Some(id) = &prod.buyer_item_id {
+            write_element(writer, "ram:BuyerAssignedID", id)?;
+        }
+    }
+    write_element(writer, "ram:Name", &prod.description)?;
+    if profile.includes_item_details() {
+        for attribute in &prod.attributes {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:ApplicableProductCharacteristic",
+            )))?;
+            write_element(writer, "ram:Description", &attribute.name)?;
+            write_element(writer, "ram:Value", &attribute.value)?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:ApplicableProductCharacteristic",
+            )))?;
+        }
+        for class in &prod.classifications {
+            writer.write_event(Event::Start(BytesStart::new(
+                "ram:DesignatedProductClassification",
+            )))?;
+            let mut el = BytesStart::new("ram:ClassCode");
+            el.push_attribute(("listID", class.list_id.as_str()));
+            if let Some(version) = &class.list_version {
+                el.push_attribute(("listVersionID", version.as_str()));
+            }
+            write_element_with_attr(writer, el, &class.code)?;
+            writer.write_event(Event::End(BytesEnd::new(
+                "ram:DesignatedProductClassification",
+            )))?;
+        }
+        if let Some(country) = prod.origin_country {
+            writer.write_event(Event::Start(BytesStart::new("ram:OriginTradeCountry")))?;
+            write_element(writer, "ram:ID", country.code())?;
+            writer.write_event(Event::End(BytesEnd::new("ram:OriginTradeCountry")))?;
+        }
+    }
+    writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedTradeProduct")))?;
+    Ok(())
+}
+
+fn write_date(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    tag: &str,
+    date: &DateTime,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new(tag)))?;
+    let mut value = BytesStart::new("udt:DateTimeString");
+    value.push_attribute(("format", "102"));
+    write_element_with_attr(writer, value, &to_cii_date(date))?;
+    writer.write_event(Event::End(BytesEnd::new(tag)))?;
+    Ok(())
+}
+
+fn write_email(writer: &mut Writer<Cursor<Vec<u8>>>, email: &str) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new(
+        "ram:EmailURIUniversalCommunication",
+    )))?;
+    write_element(writer, "ram:URIID", email)?;
+    writer.write_event(Event::End(BytesEnd::new(
+        "ram:EmailURIUniversalCommunication",
+    )))?;
+    Ok(())
+}
+
+fn write_legal_organization(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    registration_id: Option<&str>,
+) -> Result<(), InvoiceError> {
+    if let Some(id) = registration_id {
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:SpecifiedLegalOrganization",
+        )))?;
+        write_element(writer, "ram:ID", id)?;
+        writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedLegalOrganization")))?;
+    }
+    Ok(())
+}
+
+fn write_endpoint(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    endpoint: Option<ElectronicAddress>,
+) -> Result<(), InvoiceError> {
+    if let Some(endpoint) = endpoint {
+        writer.write_event(Event::Start(BytesStart::new(
+            "ram:URIUniversalCommunication",
+        )))?;
+        let mut id = BytesStart::new("ram:URIID");
+        id.push_attribute(("schemeID", endpoint.scheme.as_str()));
+        write_element_with_attr(writer, id, &endpoint.value)?;
+        writer.write_event(Event::End(BytesEnd::new("ram:URIUniversalCommunication")))?;
+    }
+    Ok(())
+}
+
+/// `schemeID` is `VA` for VAT identifiers and `FC` for local tax numbers.
+fn write_tax_registration(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    value: &str,
+    scheme: &str,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new(
+        "ram:SpecifiedTaxRegistration",
+    )))?;
+    let mut id = BytesStart::new("ram:ID");
+    id.push_attribute(("schemeID", scheme));
+    write_element_with_attr(writer, id, value)?;
+    writer.write_event(Event::End(BytesEnd::new("ram:SpecifiedTaxRegistration")))?;
+    Ok(())
+}
+
+/// MINIMUM only carries the country of the seller's address.
+fn write_address(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    addr: &Address,
+    profile: EInvoiceProfile,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("ram:PostalTradeAddress")))?;
+    if profile.includes_details() {
+        if !addr.code.is_empty() {
+            write_element(writer, "ram:PostcodeCode", &addr.code)?;
+        }
+        write_element(writer, "ram:LineOne", &addr.street_line())?;
+        let (line_two, line_three) = additional_lines(addr);
+        if let Some(line) = line_two {
+            write_element(writer, "ram:LineTwo", line)?;
+        }
+        if let Some(line) = line_three {
+            write_element(writer, "ram:LineThree", &line)?;
+        }
+        if !addr.town.is_empty() {
+            write_element(writer, "ram:CityName", &addr.town)?;
+        }
+    }
+    write_element(writer, "ram:CountryID", addr.country.code())?;
+    if profile.includes_details()
+        && let Some(subdivision) = &addr.subdivision
+    {
+        write_element(writer, "ram:CountrySubDivisionName", subdivision)?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("ram:PostalTradeAddress")))?;
+    Ok(())
+}
+
+impl Invoice {
+    /// Reads a UN/CEFACT Cross Industry Invoice of any Factur-X / ZUGFeRD profile.
+    ///
+    /// Works like [`Invoice::from_ubl_xml`]. Documents without invoice lines (MINIMUM,
+    /// BASIC WL) get one line per VAT breakdown entry so the totals are preserved.
+    pub fn from_cii_xml(xml: &str) -> Result<(Invoice, ParseReport), InvoiceError> {
+        let root = Node::parse(xml)?;
+        if root.local_name() != "CrossIndustryInvoice" {
+            return Err(InvoiceError::parse(format!(
+                "Expected a CrossIndustryInvoice, found {}",
+                root.local_name()
+            )));
+        }
+        let mut report = ParseReport::default();
+
+        if let Some(context) = root.child("ExchangedDocumentContext") {
+            for tag in [
+                "BusinessProcessSpecifiedDocumentContextParameter",
+                "GuidelineSpecifiedDocumentContextParameter",
+            ] {
+                context.find(&[tag, "ID"]).map(Node::consume);
+            }
+        }
+
+        let document = root
+            .child("ExchangedDocument")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no ExchangedDocument"))?;
+        let number = document
+            .text_at(&["ID"])
+            .ok_or_else(|| InvoiceError::parse("Invoice has no ID"))?
+            .to_string();
+        let document_type = match document.text_at(&["TypeCode"]) {
+            Some(code) => parse_document_type(code, DocumentType::CommercialInvoice, &mut report),
+            None => DocumentType::CommercialInvoice,
+        };
+        let date = parse_cii_date(
+            document
+                .child("IssueDateTime")
+                .ok_or_else(|| InvoiceError::parse("Invoice has no IssueDateTime"))?,
+        )?;
+
+        let transaction = root
+            .child("SupplyChainTradeTransaction")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no SupplyChainTradeTransaction"))?;
+        let agreement = transaction
+            .child("ApplicableHeaderTradeAgreement")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no ApplicableHeaderTradeAgreement"))?;
+        let settlement = transaction
+            .child("ApplicableHeaderTradeSettlement")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no ApplicableHeaderTradeSettlement"))?;
+
+        if let Some(reference) = agreement.child("BuyerReference")
+            && reference.is(BUYER_REFERENCE)
+        {
+            reference.consume();
+        }
+        let currency = parse_currency(
+            settlement
+                .text_at(&["InvoiceCurrencyCode"])
+                .ok_or_else(|| InvoiceError::parse("Invoice has no InvoiceCurrencyCode"))?,
+        )?;
+
+        let seller_party = agreement
+            .child("SellerTradeParty")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no SellerTradeParty"))?;
+        let seller_name = seller_party
+            .text_at(&["Name"])
+            .ok_or_else(|| InvoiceError::parse("Seller without a name"))?
+            .to_string();
+        let seller_contact = seller_party.child("DefinedTradeContact");
+        if let Some(person) = seller_contact.and_then(|c| c.child("PersonName"))
+            && person.is(&seller_name)
+        {
+            person.consume();
+        }
+        let seller_email = seller_contact
+            .and_then(|c| c.text_at(&["EmailURIUniversalCommunication", "URIID"]))
+            .map(str::to_string);
+        let seller = Seller {
+            address: read_cii_address(seller_party, &mut report),
+            vat_id: read_tax_registration(seller_party, "VA")
+                .unwrap_or_default()
+                .to_string(),
+            tax_registration_number: read_tax_registration(seller_party, TAX_REGISTRATION_SCHEME)
+                .map(str::to_string),
+            legal_registration_id: seller_party
+                .text_at(&["SpecifiedLegalOrganization", "ID"])
+                .map(str::to_string),
+            legal_form: seller_party.text_at(&["Description"]).map(str::to_string),
+            electronic_address: read_cii_endpoint(seller_party, seller_email.as_deref()),
+            website: String::new(),
+            phone: seller_contact
+                .and_then(|c| c.text_at(&["TelephoneUniversalCommunication", "CompleteNumber"]))
+                .map(str::to_string),
+            email: seller_email,
+            name: seller_name,
+        };
+
+        let buyer_party = agreement
+            .child("BuyerTradeParty")
+            .ok_or_else(|| InvoiceError::parse("Invoice has no BuyerTradeParty"))?;
+        let buyer_name = buyer_party
+            .text_at(&["Name"])
+            .ok_or_else(|| InvoiceError::parse("Buyer without a name"))?
+            .to_string();
+        let buyer_contact = buyer_party.child("DefinedTradeContact");
+        if let Some(person) = buyer_contact.and_then(|c| c.child("PersonName"))
+            && person.is(&buyer_name)
+        {
+            person.consume();
+        }
+        let buyer_email = buyer_contact
+            .and_then(|c| c.text_at(&["EmailURIUniversalCommunication", "URIID"]))
+            .unwrap_or_default()
+            .to_string();
+        let buyer = Buyer {
+            address: read_cii_address(buyer_party, &mut report),
+            vat_id: read_tax_registration(buyer_party, "VA").map(str::to_string),
+            tax_registration_number: None,
+            legal_registration_id: buyer_party
+                .text_at(&["SpecifiedLegalOrganization", "ID"])
+                .map(str::to_string),
+            legal_form: None,
+            electronic_address: read_cii_endpoint(buyer_party, Some(&buyer_email)),
+            email: buyer_email,
+            name: buyer_name,
+        };
+
+        let delivery_date = transaction
+            .find(&[
+                "ApplicableHeaderTradeDelivery",
+                "ActualDeliverySupplyChainEvent",
+                "OccurrenceDateTime",
+            ])
+            .map(parse_cii_date)
+            .transpose()?;
+        let delivery_country = transaction
+            .text_at(&[
+                "ApplicableHeaderTradeDelivery",
+                "ShipToTradeParty",
+                "PostalTradeAddress",
+                "CountryID",
+            ])
+            .and_then(|code| parse_country(code, "Delivery country", &mut report));
+
+        let mut iban = None;
+        let mut bic = None;
+        if let Some(means) = settlement.child("SpecifiedTradeSettlementPaymentMeans") {
+            if let Some(code) = means.text_at(&["TypeCode"])
+                && code != PAYMENT_MEANS_CREDIT_TRANSFER
+                && code != "58"
+            {
+                report.lossy(format!(
+                    "Payment means code {} read as credit transfer",
+                    code
+                ));
+            }
+            iban = means.text_at(&["PayeePartyCreditorFinancialAccount", "IBANID"]);
+            bic = means.text_at(&["PayeeSpecifiedCreditorFinancialInstitution", "BICID"]);
+        }
+
+        let payment_due = match settlement.find(&["SpecifiedTradePaymentTerms", "DueDateDateTime"])
+        {
+            Some(due) => parse_cii_date(due)?,
+            None => {
+                report.lossy("No due date, using the issue date");
+                date
+            }
+        };
+
+        // (category, rate, basis, exemption reason, exemption code) of the VAT breakdown.
+        let mut breakdown = Vec::new();
+        for tax in settlement.children("ApplicableTradeTax") {
+            for tag in ["CalculatedAmount", "TypeCode"] {
+                tax.children(tag).for_each(Node::consume);
+            }
+            let category = tax
+                .text_at(&["CategoryCode"])
+                .ok_or_else(|| InvoiceError::parse("VAT breakdown without category"))?;
+            let rate = tax
+                .text_at(&["RateApplicablePercent"])
+                .map(parse_percent)
+                .transpose()?
+                .unwrap_or(Decimal::ZERO);
+            let basis = parse_decimal(
+                tax.text_at(&["BasisAmount"])
+                    .ok_or_else(|| InvoiceError::parse("VAT breakdown without basis"))?,
+                "VAT basis",
+            )?;
+            breakdown.push((
+                category,
+                rate,
+                basis,
+                tax.text_at(&["ExemptionReason"]),
+                tax.text_at(&["ExemptionReasonCode"]),
+            ));
+        }
+
+        let mut products = Vec::new();
+        for line in transaction.children("IncludedSupplyChainTradeLineItem") {
+            line.find(&["AssociatedDocumentLineDocument", "LineID"])
+                .map(Node::consume);
+            let product_node = line.child("SpecifiedTradeProduct");
+            let item = |path: &[&str]| {
+                product_node
+                    .and_then(|p| p.text_at(path))
+                    .map(str::to_string)
+            };
+            let quantity = line
+                .find(&["SpecifiedLineTradeDelivery", "BilledQuantity"])
+                .ok_or_else(|| InvoiceError::parse("Line item without BilledQuantity"))?;
+            let unit = parse_unit(quantity.attr("unitCode"), &mut report);
+            let quantity = parse_decimal(quantity.text(), "quantity")?;
+
+            let line_settlement = line.child("SpecifiedLineTradeSettlement").ok_or_else(|| {
+                InvoiceError::parse("Line item without SpecifiedLineTradeSettlement")
+            })?;
+            let tax = line_settlement
+                .child("ApplicableTradeTax")
+                .ok_or_else(|| InvoiceError::parse("Line item without ApplicableTradeTax"))?;
+            tax.children("TypeCode").for_each(Node::consume);
+            let category = tax
+                .text_at(&["CategoryCode"])
+                .ok_or_else(|| InvoiceError::parse("Line tax without category"))?;
+            let rate = tax
+                .text_at(&["RateApplicablePercent"])
+                .map(parse_percent)
+                .transpose()?
+                .unwrap_or(Decimal::ZERO);
+            let entry = breakdown.iter().find(|(c, ..)| *c == category);
+            let reason = tax
+                .text_at(&["ExemptionReason"])
+                .or_else(|| entry.and_then(|(.., reason, _)| *reason));
+            let code = tax
+                .text_at(&["ExemptionReasonCode"])
+                .or_else(|| entry.and_then(|(.., code)| *code));
+            let tax = tax_fields(category, rate, reason, code, &mut report);
+
+            let price = line
+                .find(&["SpecifiedLineTradeAgreement", "NetPriceProductTradePrice"])
+                .ok_or_else(|| {
+                    InvoiceError::parse("Line item without NetPriceProductTradePrice")
+                })?;
+            let cost_per_unit = parse_decimal(
+                price
+                    .text_at(&["ChargeAmount"])
+                    .ok_or_else(|| InvoiceError::parse("Net price without amount"))?,
+                "price",
+            )?;
+            let price_base_quantity =
+                parse_base_quantity(price.child("BasisQuantity"), unit, &mut report)?;
+
+            let allowances_charges = line_settlement
+                .children("SpecifiedTradeAllowanceCharge")
+                .map(|node| read_allowance_charge(node, &mut report))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let product = Product {
+                description: line
+                    .text_at(&["SpecifiedTradeProduct", "Name"])
+                    .unwrap_or_default()
+                    .to_string(),
+                quantity,
+                unit,
+                cost_per_unit,
+                price_base_quantity,
+                tax_rate: tax.rate,
+                tax_category: tax.category,
+                tax_exempt_reason: tax.reason,
+                tax_exemption_code: tax.code,
+                allowances_charges,
+                note: line
+                    .text_at(&["AssociatedDocumentLineDocument", "IncludedNote", "Content"])
+                    .map(str::to_string),
+                seller_item_id: item(&["SellerAssignedID"]),
+                buyer_item_id: item(&["BuyerAssignedID"]),
+                standard_item_id: product_node.and_then(|p| p.child("GlobalID")).map(|id| {
+                    ItemIdentifier::new(id.attr("schemeID").unwrap_or_default(), id.text())
+                }),
+                classifications: product_node
+                    .into_iter()
+                    .flat_map(|p| p.children("DesignatedProductClassification"))
+                    .filter_map(|class| class.child("ClassCode"))
+                    .map(|code| ItemClassification {
+                        list_id: code.attr("listID").unwrap_or_default().to_string(),
+                        list_version: code.attr("listVersionID").map(str::to_string),
+                        code: code.text().to_string(),
+                    })
+                    .collect(),
+                origin_country: item(&["OriginTradeCountry", "ID"])
+                    .and_then(|code| parse_country(&code, "Origin country", &mut report)),
+                attributes: product_node
+                    .into_iter()
+                    .flat_map(|p| p.children("ApplicableProductCharacteristic"))
+                    .map(|property| {
+                        ItemAttribute::new(
+                            property.text_at(&["Description"]).unwrap_or_default(),
+                            property.text_at(&["Value"]).unwrap_or_default(),
+                        )
+                    })
+                    .collect(),
+            };
+            for (node, entry) in line_settlement
+                .children("SpecifiedTradeAllowanceCharge")
+                .zip(&product.allowances_charges)
+            {
+                check_allowance_charge(
+                    node.child("ActualAmount"),
+                    node.child("BasisAmount"),
+                    entry,
+                    product
+                        .checked_base_amount(currency)
+                        .ok_or_else(out_of_range)?,
+                    &mut report,
+                )?;
+            }
+            check_amount(
+                line_settlement.find(&[
+                    "SpecifiedTradeSettlementLineMonetarySummation",
+                    "LineTotalAmount",
+                ]),
+                "Line net amount",
+                product
+                    .checked_net_amount(currency)
+                    .ok_or_else(out_of_range)?,
+                &mut report,
+            )?;
+            products.push(product);
+        }
+
+        let billing_reference = settlement
+            .child("InvoiceReferencedDocument")
+            .map(|reference| -> Result<BillingReference, InvoiceError> {
+                Ok(BillingReference {
+                    number: reference
+                        .text_at(&["IssuerAssignedID"])
+                        .unwrap_or_default()
+                        .to_string(),
+                    date: reference
+                        .child("FormattedIssueDateTime")
+                        .map(parse_cii_date)
+                        .transpose()?,
+                })
+            })
+            .transpose()?;
+
+        let totals = settlement.child("SpecifiedTradeSettlementHeaderMonetarySummation");
+        let total = |tag| totals.and_then(|t| t.child(tag));
+        let stated = |tag, what| {
+            total(tag)
+                .map(|node| parse_decimal(node.text(), what))
+                .transpose()
+        };
+        // A second TaxTotalAmount may state the VAT in the accounting currency.
+        let tax_total = totals.and_then(|t| {
+            t.children("TaxTotalAmount")
+                .find(|a| a.attr("currencyID").is_none_or(|c| c == currency.code()))
+        });
+
+        // Without lines the VAT basis already includes the allowances and charges, which
+        // are then left unread.
+        let allowances_charges = if products.is_empty() {
+            Vec::new()
+        } else {
+            settlement
+                .children("SpecifiedTradeAllowanceCharge")
+                .map(|node| read_allowance_charge(node, &mut report))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        if products.is_empty() {
+            if breakdown.is_empty() {
+                let basis = total("TaxBasisTotalAmount").ok_or_else(|| {
+                    InvoiceError::parse("Invoice has neither lines nor a tax basis total")
+                })?;
+                let basis = parse_decimal(basis.text(), "tax basis total")?;
+                let tax = match tax_total {
+                    Some(tax) => parse_decimal(tax.text(), "tax total")?,
+                    None => Decimal::ZERO,
+                };
+                let rate = tax.div_round(basis, 4).unwrap_or(Decimal::ZERO).normalize();
+                report.lossy(format!(
+                    "Invoice has no lines or VAT breakdown, created one line at {}%",
+                    to_percent(rate)
+                ));
+                let tax = LineTax {
+                    category: TaxCategory::Standard,
+                    rate,
+                    reason: None,
+                    code: None,
+                };
+                let description = format!("{} {}%", tax.category.code(), to_percent(rate));
+                products.push(summary_line(description, basis, tax));
+            } else {
+                report.lossy("Invoice has no lines, created one line per VAT breakdown entry");
+                for (category, rate, basis, reason, code) in &breakdown {
+                    let tax = tax_fields(category, *rate, *reason, *code, &mut report);
+                    let description = format!("{} {}%", tax.category.code(), to_percent(tax.rate));
+                    products.push(summary_line(description, *basis, tax));
+                }
+            }
+        }
+
+        let invoice = Invoice {
+            number,
+            document_type,
+            billing_reference,
+            date,
+            seller,
+            buyer,
+            payment_due,
+            delivery_date,
+            delivery_type: None,
+            delivery_country,
+            extra_info: None,
+            payment_type: None,
+            payment_info: payment_info(iban, bic),
+            products,
+            allowances_charges,
+            prepaid_amount: stated("TotalPrepaidAmount", "prepaid amount")?,
+            payments: Vec::new(),
+            rounding_amount: stated("RoundingAmount", "rounding amount")?,
+            currency,
+            locale: Locale::en,
+        };
+
+        let summary = invoice
+            .try_calculate_summary()
+            .map_err(|_| out_of_range())?;
+        for (node, entry) in settlement
+            .children("SpecifiedTradeAllowanceCharge")
+            .zip(&invoice.allowances_charges)
+        {
+            check_allowance_charge(
+                node.child("ActualAmount"),
+                node.child("BasisAmount"),
+                entry,
+                invoice.allowance_charge_base(entry),
+                &mut report,
+            )?;
+        }
+        for (node, what, computed) in [
+            (
+                total("LineTotalAmount"),
+                "Sum of line net amounts",
+                summary.subtotal,
+            ),
+            (
+                total("TaxBasisTotalAmount"),
+                "Total without VAT",
+                summary.net_total,
+            ),
+            (
+                total("AllowanceTotalAmount"),
+                "Sum of allowances",
+                summary.allowance_total,
+            ),
+            (
+                total("ChargeTotalAmount"),
+                "Sum of charges",
+                summary.charge_total,
+            ),
+            (tax_total, "Tax total", summary.tax_total),
+            (total("GrandTotalAmount"), "Total with VAT", summary.total),
+            (total("DuePayableAmount"), "Amount due", summary.amount_due),
+        ] {
+            check_amount(node, what, computed, &mut report)?;
+        }
+
+        root.collect_unused(&mut report);
+        Ok((invoice, report))
+    }
+}
+
+/// A `ram:SpecifiedTradeAllowanceCharge` of the header or a line. Its amounts are
+/// checked with [`check_allowance_charge`] once the base is known.
+fn read_allowance_charge(
+    node: &Node,
+    report: &mut ParseReport,
+) -> Result<AllowanceCharge, InvoiceError> {
+    let kind = match node.text_at(&["ChargeIndicator", "Indicator"]) {
+        Some("true") => AllowanceChargeKind::Charge,
+        Some("false") => AllowanceChargeKind::Allowance,
+        _ => {
+            return Err(InvoiceError::parse(
+                "SpecifiedTradeAllowanceCharge without ChargeIndicator",
+            ));
+        }
+    };
+    let amount = match node.text_at(&["CalculationPercent"]) {
+        Some(percent) => AllowanceChargeAmount::Percentage(parse_percent(percent)?),
+        None => AllowanceChargeAmount::Fixed(parse_decimal(
+            node.text_at(&["ActualAmount"]).ok_or_else(|| {
+                InvoiceError::parse("SpecifiedTradeAllowanceCharge without ActualAmount")
+            })?,
+            "allowance or charge amount",
+        )?),
+    };
+    let tax = match node.child("CategoryTradeTax") {
+        Some(tax) => {
+            tax.children("TypeCode").for_each(Node::consume);
+            let category = tax
+                .text_at(&["CategoryCode"])
+                .ok_or_else(|| InvoiceError::parse("Allowance tax without category"))?;
+            let rate = tax
+                .text_at(&["RateApplicablePercent"])
+                .map(parse_percent)
+                .transpose()?;
+            tax_fields(category, rate.unwrap_or(Decimal::ZERO), None, None, report)
+        }
+        None => LineTax::default(),
+    };
+    Ok(AllowanceCharge {
+        kind,
+        amount,
+        reason: node.text_at(&["Reason"]).map(str::to_string),
+        reason_code: node.text_at(&["ReasonCode"]).map(str::to_string),
+        tax_category: tax.category,
+        tax_rate: tax.rate,
+    })
+}
+
+fn read_tax_registration<'a>(party: &'a Node, scheme: &str) -> Option<&'a str> {
+    party
+        .children("SpecifiedTaxRegistration")
+        .filter_map(|registration| registration.child("ID"))
+        .find(|id| id.attr("schemeID") == Some(scheme))
+        .map(Node::text)
+}
+
+fn read_cii_endpoint(party: &Node, email: Option<&str>) -> Option<ElectronicAddress> {
+    let endpoint = party.find(&["URIUniversalCommunication", "URIID"])?;
+    electronic_address(endpoint.attr("schemeID"), endpoint.text(), email)
+}
+
+fn read_cii_address(party: &Node, report: &mut ParseReport) -> Address {
+    let Some(address) = party.child("PostalTradeAddress") else {
+        return parse_address("", None, None, report);
+    };
+    let additional_lines = ["LineTwo", "LineThree"]
+        .into_iter()
+        .filter_map(|tag| address.text_at(&[tag]))
+        .map(str::to_string)
+        .collect();
+    Address {
+        additional_lines,
+        code: address
+            .text_at(&["PostcodeCode"])
+            .unwrap_or_default()
+            .to_string(),
+        town: address
+            .text_at(&["CityName"])
+            .unwrap_or_default()
+            .to_string(),
+        subdivision: address
+            .text_at(&["CountrySubDivisionName"])
+            .map(str::to_string),
+        ..parse_address(
+            address.text_at(&["LineOne"]).unwrap_or_default(),
+            None,
+            address.text_at(&["CountryID"]),
+            report,
+        )
+    }
+}
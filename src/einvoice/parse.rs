@@ -0,0 +1,492 @@
+//! Shared building blocks for reading UBL and CII documents back into the model.
+
+use crate::error::InvoiceError;
+use crate::models::{
+    Address, AllowanceCharge, Country, Currency, Decimal, DocumentType, ElectronicAddress, Money,
+    Product, TaxCategory, Unit,
+};
+use locale_rs::datetime_formats::DateTime;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::cell::Cell;
+
+/// What could not be carried over from an inbound e-invoice.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Paths of elements the model has no place for, e.g. `Invoice/cbc:Note`.
+    pub unsupported: Vec<String>,
+    /// Values that were converted with a loss of information.
+    pub lossy: Vec<String>,
+}
+
+impl ParseReport {
+    /// True if every element was mapped without loss.
+    pub fn is_lossless(&self) -> bool {
+        self.unsupported.is_empty() && self.lossy.is_empty()
+    }
+
+    pub(crate) fn lossy(&mut self, message: impl Into<String>) {
+        self.lossy.push(message.into());
+    }
+}
+
+/// Elements nested deeper than this are rejected; UBL and CII need far fewer levels,
+/// and the tree is walked and dropped recursively.
+const MAX_DEPTH: usize = 100;
+
+/// A parsed XML element. Lookups go by local name so any namespace prefix works;
+/// reading an element's text marks it as consumed for the [`ParseReport`].
+pub(crate) struct Node {
+    name: String,
+    local: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<Node>,
+    used: Cell<bool>,
+}
+
+impl Node {
+    pub(crate) fn parse(xml: &str) -> Result<Node, InvoiceError> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut stack: Vec<Node> = Vec::new();
+        loop {
+            match reader.read_event()? {
+                Event::Start(start) => {
+                    if stack.len() == MAX_DEPTH {
+                        return Err(InvoiceError::parse(format!(
+                            "XML is nested deeper than {} elements",
+                            MAX_DEPTH
+                        )));
+                    }
+                    stack.push(Node::from_start(&start)?)
+                }
+                Event::Empty(start) => {
+                    let node = Node::from_start(&start)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => return Ok(node),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&text.unescape()?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(std::str::from_utf8(&data)?);
+                    }
+                }
+                Event::End(_) => {
+                    let node = stack
+                        .pop()
+                        .ok_or_else(|| InvoiceError::parse("Unbalanced XML"))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => return Ok(node),
+                    }
+                }
+                Event::Eof => return Err(InvoiceError::parse("XML document has no root element")),
+                _ => {}
+            }
+        }
+    }
+
+    fn from_start(start: &BytesStart) -> Result<Node, InvoiceError> {
+        let mut attributes = Vec::new();
+        for attr in start.attributes() {
+            let attr = attr?;
+            let key = std::str::from_utf8(attr.key.as_ref())?;
+            if key == "xmlns" || key.starts_with("xmlns:") {
+                continue;
+            }
+            let local = std::str::from_utf8(attr.key.local_name().as_ref())?.to_string();
+            attributes.push((local, attr.unescape_value()?.into_owned()));
+        }
+        Ok(Node {
+            name: std::str::from_utf8(start.name().as_ref())?.to_string(),
+            local: std::str::from_utf8(start.local_name().as_ref())?.to_string(),
+            attributes,
+            text: String::new(),
+            children: Vec::new(),
+            used: Cell::new(false),
+        })
+    }
+
+    pub(crate) fn local_name(&self) -> &str {
+        &self.local
+    }
+
+    pub(crate) fn child(&self, local: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.local == local)
+    }
+
+    pub(crate) fn children<'a>(&'a self, local: &'a str) -> impl Iterator<Item = &'a Node> {
+        self.children.iter().filter(move |c| c.local == local)
+    }
+
+    /// Follows a chain of child names, e.g. `["Party", "PartyName", "Name"]`.
+    pub(crate) fn find(&self, path: &[&str]) -> Option<&Node> {
+        path.iter().try_fold(self, |node, name| node.child(name))
+    }
+
+    /// Text of the element at `path`, marking it as consumed.
+    pub(crate) fn text_at(&self, path: &[&str]) -> Option<&str> {
+        self.find(path).map(Node::text)
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        self.used.set(true);
+        self.text.trim()
+    }
+
+    /// Compares the text without consuming the element.
+    pub(crate) fn is(&self, value: &str) -> bool {
+        self.text.trim() == value
+    }
+
+    pub(crate) fn attr(&self, local: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == local)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Marks an element as consumed without reading it, e.g. recomputed totals.
+    pub(crate) fn consume(&self) {
+        self.used.set(true);
+    }
+
+    /// Collects elements that were never read. Subtrees that were skipped entirely are
+    /// reported once by their root instead of leaf by leaf.
+    pub(crate) fn collect_unused(&self, report: &mut ParseReport) {
+        self.collect_unused_at(&self.name, report);
+    }
+
+    fn collect_unused_at(&self, path: &str, report: &mut ParseReport) {
+        for child in &self.children {
+            let child_path = format!("{}/{}", path, child.name);
+            if !child.any_used() {
+                report.unsupported.push(child_path);
+            } else {
+                child.collect_unused_at(&child_path, report);
+            }
+        }
+    }
+
+    fn any_used(&self) -> bool {
+        self.used.get() || self.children.iter().any(Node::any_used)
+    }
+}
+
+pub(crate) fn parse_iso_date(value: &str) -> Result<DateTime, InvoiceError> {
+    let mut parts = value.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(InvoiceError::parse(format!("Invalid date '{}'", value)));
+    };
+    // The day is cut off from a time zone by byte offset.
+    if !value.is_ascii() {
+        return Err(InvoiceError::parse(format!("Invalid date '{}'", value)));
+    }
+    make_date(year, month, &day[..day.len().min(2)], value)
+}
+
+/// Reads a CII `udt:DateTimeString` element; only format `102` (`YYYYMMDD`) is defined
+/// for invoices.
+pub(crate) fn parse_cii_date(node: &Node) -> Result<DateTime, InvoiceError> {
+    let value = node
+        .child("DateTimeString")
+        .ok_or_else(|| InvoiceError::parse("Date without DateTimeString"))?;
+    if value.attr("format").is_some_and(|f| f != "102") {
+        return Err(InvoiceError::parse(format!(
+            "Unsupported date format {:?}",
+            value.attr("format")
+        )));
+    }
+    let text = value.text();
+    if text.len() != 8 || !text.is_ascii() {
+        return Err(InvoiceError::parse(format!("Invalid date '{}'", text)));
+    }
+    make_date(&text[..4], &text[4..6], &text[6..], text)
+}
+
+fn make_date(year: &str, month: &str, day: &str, original: &str) -> Result<DateTime, InvoiceError> {
+    let invalid = || InvoiceError::parse(format!("Invalid date '{}'", original));
+    let date = DateTime {
+        year: year.parse().map_err(|_| invalid())?,
+        month: month.parse().map_err(|_| invalid())?,
+        day: day.parse().map_err(|_| invalid())?,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+    if !(1..=12).contains(&date.month) || !(1..=31).contains(&date.day) {
+        return Err(invalid());
+    }
+    Ok(date)
+}
+
+pub(crate) fn parse_decimal(value: &str, what: &str) -> Result<Decimal, InvoiceError> {
+    value
+        .parse()
+        .map_err(|_| InvoiceError::parse(format!("Invalid {} '{}'", what, value)))
+}
+
+pub(crate) fn parse_currency(code: &str) -> Result<Currency, InvoiceError> {
+    Currency::from_code(code)
+        .ok_or_else(|| InvoiceError::parse(format!("Unsupported currency '{}'", code)))
+}
+
+pub(crate) fn parse_document_type(
+    code: &str,
+    default: DocumentType,
+    report: &mut ParseReport,
+) -> DocumentType {
+    DocumentType::from_code(code).unwrap_or_else(|| {
+        report.lossy(format!("Document type {} read as {}", code, default));
+        default
+    })
+}
+
+/// Converts an EN 16931 percentage such as `19` into the fractional rate `0.19`.
+pub(crate) fn parse_percent(value: &str) -> Result<Decimal, InvoiceError> {
+    let percent = parse_decimal(value, "VAT rate")?;
+    Ok(percent
+        .div_round(Decimal::ONE_HUNDRED, percent.scale() + 2)
+        .ok_or_else(|| InvoiceError::parse("Invalid VAT rate"))?
+        .normalize())
+}
+
+/// Street and country of an address; the remaining fields are left empty for the caller.
+/// A single address line like `Main Street 12` or `12 rue de la Paix` is split into
+/// street and house number where the country's order gives back the same line.
+pub(crate) fn parse_address(
+    street_line: &str,
+    building_number: Option<&str>,
+    country: Option<&str>,
+    report: &mut ParseReport,
+) -> Address {
+    let country = country.map_or_else(Country::default, |code| {
+        Country::from_code(code).unwrap_or_else(|| {
+            report.lossy(format!(
+                "Country '{}' is not an ISO 3166 code, using {}",
+                code,
+                Country::default()
+            ));
+            Country::default()
+        })
+    });
+    let mut address = Address {
+        street: street_line.to_string(),
+        house_number: building_number.unwrap_or_default().to_string(),
+        additional_lines: Vec::new(),
+        code: String::new(),
+        town: String::new(),
+        subdivision: None,
+        country,
+    };
+    if building_number.is_some() {
+        return address;
+    }
+    let is_number = |part: &str| part.starts_with(|c: char| c.is_ascii_digit());
+    let candidates = [
+        street_line
+            .rsplit_once(' ')
+            .filter(|(_, number)| is_number(number)),
+        street_line
+            .split_once(' ')
+            .filter(|(number, _)| is_number(number))
+            .map(|(number, street)| (street, number)),
+    ];
+    for (street, number) in candidates.into_iter().flatten() {
+        address.street = street.to_string();
+        address.house_number = number.to_string();
+        if address.street_line() == street_line {
+            return address;
+        }
+    }
+    address.street = street_line.to_string();
+    address.house_number.clear();
+    address
+}
+
+/// An optional country such as the origin of an item; unknown codes are dropped.
+pub(crate) fn parse_country(code: &str, what: &str, report: &mut ParseReport) -> Option<Country> {
+    let country = Country::from_code(code);
+    if country.is_none() {
+        report.lossy(format!("{} '{}' is not an ISO 3166 code", what, code));
+    }
+    country
+}
+
+/// The unit of a quantity; missing or malformed codes are read as pieces.
+pub(crate) fn parse_unit(unit_code: Option<&str>, report: &mut ParseReport) -> Unit {
+    match unit_code {
+        Some(code) => Unit::from_code(code).unwrap_or_else(|| {
+            report.lossy(format!("Unit code '{}' read as pieces", code));
+            Unit::PIECE
+        }),
+        None => Unit::PIECE,
+    }
+}
+
+/// The price base quantity (BT-149), whose unit (BT-150) must match the line's.
+pub(crate) fn parse_base_quantity(
+    node: Option<&Node>,
+    unit: Unit,
+    report: &mut ParseReport,
+) -> Result<Option<Decimal>, InvoiceError> {
+    let Some(node) = node else {
+        return Ok(None);
+    };
+    if let Some(code) = node.attr("unitCode")
+        && Unit::from_code(code) != Some(unit)
+    {
+        report.lossy(format!("Base quantity unit '{}' read as {}", code, unit));
+    }
+    let base = parse_decimal(node.text(), "base quantity")?;
+    if base.is_zero() || base.is_sign_negative() {
+        return Err(InvoiceError::parse("Invalid base quantity"));
+    }
+    Ok(Some(base))
+}
+
+/// The VAT fields of a line as the model stores them.
+#[derive(Default)]
+pub(crate) struct LineTax {
+    pub(crate) category: TaxCategory,
+    pub(crate) rate: Decimal,
+    pub(crate) reason: Option<String>,
+    pub(crate) code: Option<String>,
+}
+
+/// Maps a UNCL5305 category with its rate and exemption reason to the model. Reasons and
+/// codes equal to the category's defaults are dropped, they are written again on export.
+pub(crate) fn tax_fields(
+    category: &str,
+    rate: Decimal,
+    reason: Option<&str>,
+    code: Option<&str>,
+    report: &mut ParseReport,
+) -> LineTax {
+    let parsed = TaxCategory::from_code(category).unwrap_or_else(|| {
+        report.lossy(format!("VAT category '{}' read as exempt (E)", category));
+        TaxCategory::Exempt
+    });
+    if !parsed.is_taxed() && !rate.is_zero() {
+        report.lossy(format!(
+            "VAT rate {} of category {} read as 0%",
+            rate, parsed
+        ));
+    }
+    let keep = parsed.requires_exemption_reason();
+    LineTax {
+        category: parsed,
+        rate: if parsed.is_taxed() {
+            rate
+        } else {
+            Decimal::ZERO
+        },
+        reason: reason
+            .filter(|r| keep && Some(*r) != parsed.default_exemption_reason())
+            .map(str::to_string),
+        code: code
+            .filter(|c| keep && Some(*c) != parsed.default_exemption_code())
+            .map(str::to_string),
+    }
+}
+
+/// An inbound endpoint, unless it only repeats the contact email it is derived from on
+/// export.
+pub(crate) fn electronic_address(
+    scheme: Option<&str>,
+    value: &str,
+    email: Option<&str>,
+) -> Option<ElectronicAddress> {
+    let address = ElectronicAddress::new(scheme.unwrap_or_default(), value);
+    (Some(&address) != email.map(ElectronicAddress::email).as_ref()).then_some(address)
+}
+
+pub(crate) fn payment_info(iban: Option<&str>, bic: Option<&str>) -> Option<Vec<(String, String)>> {
+    let mut info = Vec::new();
+    if let Some(iban) = iban {
+        info.push(("IBAN".to_string(), iban.to_string()));
+    }
+    if let Some(bic) = bic {
+        info.push(("BIC".to_string(), bic.to_string()));
+    }
+    (!info.is_empty()).then_some(info)
+}
+
+/// A stand-in line for documents without invoice lines (Factur-X MINIMUM / BASIC WL).
+/// Callers describe it by UNCL5305 category code and rate, e.g. `S 19%`, rather than in
+/// any one language.
+pub(crate) fn summary_line(description: String, net: Decimal, tax: LineTax) -> Product {
+    Product {
+        description,
+        quantity: Decimal::ONE,
+        unit: Unit::PIECE,
+        cost_per_unit: net,
+        price_base_quantity: None,
+        tax_rate: tax.rate,
+        tax_category: tax.category,
+        tax_exempt_reason: tax.reason,
+        tax_exemption_code: tax.code,
+        allowances_charges: Vec::new(),
+        note: None,
+        seller_item_id: None,
+        buyer_item_id: None,
+        standard_item_id: None,
+        classifications: Vec::new(),
+        origin_country: None,
+        attributes: Vec::new(),
+    }
+}
+
+/// Compares a stated amount with what the model recomputes from the lines.
+pub(crate) fn check_amount(
+    node: Option<&Node>,
+    what: &str,
+    computed: Money,
+    report: &mut ParseReport,
+) -> Result<(), InvoiceError> {
+    if let Some(node) = node {
+        let stated = parse_decimal(node.text(), what)?;
+        if stated != computed.round().amount {
+            report.lossy(format!(
+                "{} {} differs from the recomputed {}",
+                what, stated, computed
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Error for amounts whose recomputation does not fit a [`Decimal`].
+pub(crate) fn out_of_range() -> InvoiceError {
+    InvoiceError::parse("Amounts exceed the supported range")
+}
+
+/// Compares the stated amount and base of an allowance or charge with the recomputed ones.
+pub(crate) fn check_allowance_charge(
+    amount: Option<&Node>,
+    base_amount: Option<&Node>,
+    entry: &AllowanceCharge,
+    base: Money,
+    report: &mut ParseReport,
+) -> Result<(), InvoiceError> {
+    let what = if entry.is_charge() {
+        "Charge"
+    } else {
+        "Allowance"
+    };
+    let computed = entry.checked_amount_on(base).ok_or_else(out_of_range)?;
+    check_amount(amount, &format!("{} amount", what), computed, report)?;
+    match base_amount {
+        Some(node) if entry.percentage().is_some() => {
+            check_amount(Some(node), &format!("{} base amount", what), base, report)
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,228 @@
+use crate::models::Invoice;
+use std::fmt;
+
+/// The specification an e-invoice claims conformance to (BT-24).
+///
+/// The profile decides which optional parts of the invoice are written and which fields
+/// must be present; [`EInvoiceProfile::validate`] runs before any XML is produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EInvoiceProfile {
+    /// Factur-X MINIMUM: document totals only, no lines and no tax breakdown.
+    Minimum,
+    /// Factur-X BASIC WL: header data and tax breakdown without invoice lines.
+    BasicWl,
+    /// Factur-X BASIC: like BASIC WL plus simple invoice lines.
+    Basic,
+    /// Plain EN 16931 core invoice, the Factur-X / ZUGFeRD "EN 16931" (COMFORT) profile.
+    #[default]
+    En16931,
+    /// Factur-X EXTENDED, a superset of EN 16931.
+    Extended,
+    /// German CIUS required by public authorities (XRechnung 3.0).
+    XRechnung,
+}
+
+impl EInvoiceProfile {
+    pub const ALL: [EInvoiceProfile; 6] = [
+        EInvoiceProfile::Minimum,
+        EInvoiceProfile::BasicWl,
+        EInvoiceProfile::Basic,
+        EInvoiceProfile::En16931,
+        EInvoiceProfile::Extended,
+        EInvoiceProfile::XRechnung,
+    ];
+
+    /// Specification identifier written to `CustomizationID` / `GuidelineSpecifiedDocumentContextParameter`.
+    pub fn guideline_id(&self) -> &'static str {
+        match self {
+            EInvoiceProfile::Minimum => "urn:factur-x.eu:1p0:minimum",
+            EInvoiceProfile::BasicWl => "urn:factur-x.eu:1p0:basicwl",
+            EInvoiceProfile::Basic => "urn:cen.eu:en16931:2017#compliant#urn:factur-x.eu:1p0:basic",
+            EInvoiceProfile::En16931 => "urn:cen.eu:en16931:2017",
+            EInvoiceProfile::Extended => {
+                "urn:cen.eu:en16931:2017#conformant#urn:factur-x.eu:1p0:extended"
+            }
+            EInvoiceProfile::XRechnung => {
+                "urn:cen.eu:en16931:2017#compliant#urn:xeinkauf.de:kosit:xrechnung_3.0"
+            }
+        }
+    }
+
+    /// Business process type (BT-23), if the profile asks for one.
+    pub fn business_process(&self) -> Option<&'static str> {
+        match self {
+            EInvoiceProfile::XRechnung => Some("urn:fdc:peppol.eu:2017:poacc:billing:01:1.0"),
+            _ => None,
+        }
+    }
+
+    /// Value of `fx:ConformanceLevel` in the XMP metadata of a hybrid PDF.
+    pub fn conformance_level(&self) -> &'static str {
+        match self {
+            EInvoiceProfile::Minimum => "MINIMUM",
+            EInvoiceProfile::BasicWl => "BASIC WL",
+            EInvoiceProfile::Basic => "BASIC",
+            EInvoiceProfile::En16931 => "EN 16931",
+            EInvoiceProfile::Extended => "EXTENDED",
+            EInvoiceProfile::XRechnung => "XRECHNUNG",
+        }
+    }
+
+    /// Whether the profile is a full EN 16931 invoice and can therefore be written as UBL.
+    pub fn is_en16931(&self) -> bool {
+        matches!(self, EInvoiceProfile::En16931 | EInvoiceProfile::XRechnung)
+    }
+
+    /// Invoice lines (BG-25).
+    pub fn includes_lines(&self) -> bool {
+        self.level() >= EInvoiceProfile::Basic.level()
+    }
+
+    /// Full postal addresses, payment means and terms, delivery and the VAT breakdown.
+    pub fn includes_details(&self) -> bool {
+        self.level() >= EInvoiceProfile::BasicWl.level()
+    }
+
+    /// Seller and buyer contacts (BG-6, BG-9) and the payment service provider BIC.
+    pub fn includes_contacts(&self) -> bool {
+        self.level() >= EInvoiceProfile::En16931.level()
+    }
+
+    /// Item identifiers, attributes, classifications and origin (BT-155 to BT-159).
+    pub fn includes_item_details(&self) -> bool {
+        self.level() >= EInvoiceProfile::En16931.level()
+    }
+
+    fn level(&self) -> u8 {
+        match self {
+            EInvoiceProfile::Minimum => 0,
+            EInvoiceProfile::BasicWl => 1,
+            EInvoiceProfile::Basic => 2,
+            EInvoiceProfile::En16931 | EInvoiceProfile::XRechnung => 3,
+            EInvoiceProfile::Extended => 4,
+        }
+    }
+
+    /// Checks the fields this profile makes mandatory.
+    pub fn validate(&self, invoice: &Invoice) -> Result<(), ProfileError> {
+        let mut missing = Vec::new();
+        if invoice.number.trim().is_empty() {
+            missing.push("invoice number (BT-1)");
+        }
+        if invoice.seller.name.trim().is_empty() {
+            missing.push("seller name (BT-27)");
+        }
+        if invoice.buyer.name.trim().is_empty() {
+            missing.push("buyer name (BT-44)");
+        }
+        if invoice.document_type.requires_billing_reference()
+            && invoice
+                .billing_reference
+                .as_ref()
+                .is_none_or(|r| r.number.trim().is_empty())
+        {
+            missing.push("preceding invoice reference (BG-3)");
+        }
+        if self.includes_lines() && invoice.products.is_empty() {
+            missing.push("at least one invoice line (BR-16)");
+        }
+        let line_entries = invoice
+            .products
+            .iter()
+            .filter(|_| self.includes_lines())
+            .flat_map(|p| &p.allowances_charges);
+        if invoice
+            .allowances_charges
+            .iter()
+            .filter(|_| self.includes_details())
+            .chain(line_entries)
+            .any(|e| e.reason.is_none() && e.reason_code.is_none())
+        {
+            missing.push("allowance or charge reason (BR-33, BR-38)");
+        }
+        let lines = || invoice.products.iter().filter(|_| self.includes_lines());
+        // Attributes and classifications are only written from EN 16931 upwards.
+        if self.includes_item_details()
+            && lines().any(|p| {
+                p.attributes
+                    .iter()
+                    .any(|a| a.name.trim().is_empty() || a.value.trim().is_empty())
+            })
+        {
+            missing.push("item attribute name and value (BR-54)");
+        }
+        if lines().any(|p| {
+            p.standard_item_id
+                .as_ref()
+                .is_some_and(|id| id.scheme.trim().is_empty())
+        }) {
+            missing.push("standard item identifier scheme (BR-64)");
+        }
+        if self.includes_item_details()
+            && lines().any(|p| {
+                p.classifications
+                    .iter()
+                    .any(|c| c.list_id.trim().is_empty())
+            })
+        {
+            missing.push("item classification scheme (BR-65)");
+        }
+        if *self == EInvoiceProfile::XRechnung {
+            if invoice
+                .seller
+                .phone
+                .as_deref()
+                .is_none_or(|p| p.trim().is_empty())
+            {
+                missing.push("seller contact telephone (BR-DE-6)");
+            }
+            if invoice
+                .seller
+                .email
+                .as_deref()
+                .is_none_or(|e| e.trim().is_empty())
+            {
+                missing.push("seller contact email (BR-DE-7)");
+            }
+            // Without an account no payment instructions are written.
+            if invoice.bank_account().is_none() {
+                missing.push("payment account IBAN (BR-DE-1)");
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ProfileError {
+                profile: *self,
+                missing,
+            })
+        }
+    }
+}
+
+impl fmt::Display for EInvoiceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.conformance_level())
+    }
+}
+
+/// Fields an invoice lacks for the requested profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileError {
+    pub profile: EInvoiceProfile,
+    pub missing: Vec<&'static str>,
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invoice does not meet the {} profile, missing: {}",
+            self.profile,
+            self.missing.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ProfileError {}
@@ -0,0 +1,1040 @@
+use super::parse::{
+    LineTax, Node, ParseReport, check_allowance_charge, check_amount, electronic_address,
+    out_of_range, parse_address, parse_base_quantity, parse_country, parse_currency, parse_decimal,
+    parse_document_type, parse_iso_date, parse_percent, parse_unit, payment_info, tax_fields,
+};
+use super::{
+    BUYER_REFERENCE, EInvoiceProfile, PAYMENT_MEANS_CREDIT_TRANSFER, TAX_REGISTRATION_SCHEME,
+    additional_lines, to_percent, to_ubl_date, write_element, write_element_with_attr,
+};
+use crate::error::InvoiceError;
+use crate::models::{
+    Address, AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind, BillingReference, Buyer,
+    Decimal, DocumentType, ElectronicAddress, Invoice, ItemAttribute, ItemClassification,
+    ItemIdentifier, Money, Product, Seller,
+};
+use locale_rs::Locale;
+use quick_xml::Writer;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
+use std::io::Cursor;
+
+impl Invoice {
+    /// Serializes the invoice as an XRechnung 3.0 UBL 2.1 document.
+    ///
+    /// Fails with [`InvoiceError::Profile`] when data XRechnung requires is missing, such
+    /// as the seller's telephone (BR-DE-6) and email (BR-DE-7) or an IBAN in
+    /// `payment_info` (BR-DE-1); use [`Invoice::to_ubl_xml`] with
+    /// [`EInvoiceProfile::En16931`] for plain EN 16931 output.
+    pub fn to_xml(&self) -> Result<String, InvoiceError> {
+        self.to_ubl_xml(EInvoiceProfile::XRechnung)
+    }
+
+    /// Serializes the invoice as UBL 2.1. Only the EN 16931 based profiles exist in UBL;
+    /// the Factur-X subsets are CII only.
+    pub fn to_ubl_xml(&self, profile: EInvoiceProfile) -> Result<String, InvoiceError> {
+        if !profile.is_en16931() {
+            return Err(InvoiceError::UnsupportedProfile(profile));
+        }
+        profile.validate(self)?;
+        let summary = self.try_calculate_summary()?;
+
+        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 4);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+        // Credit notes use their own UBL document with renamed line and type elements.
+        let credit_note = self.document_type.is_credit_note();
+        let (root_name, namespace, line_tag, quantity_tag) = if credit_note {
+            (
+                "CreditNote",
+                "urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2",
+                "cac:CreditNoteLine",
+                "cbc:CreditedQuantity",
+            )
+        } else {
+            (
+                "Invoice",
+                "urn:oasis:names:specification:ubl:schema:xsd:Invoice-2",
+                "cac:InvoiceLine",
+                "cbc:InvoicedQuantity",
+            )
+        };
+
+        let mut root = BytesStart::new(root_name);
+        root.push_attribute(("xmlns", namespace));
+        root.push_attribute((
+            "xmlns:cac",
+            "urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2",
+        ));
+        root.push_attribute((
+            "xmlns:cbc",
+            "urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2",
+        ));
+        writer.write_event(Event::Start(root))?;
+
+        write_element(&mut writer, "cbc:CustomizationID", profile.guideline_id())?;
+        if let Some(process) = profile.business_process() {
+            write_element(&mut writer, "cbc:ProfileID", process)?;
+        }
+        write_element(&mut writer, "cbc:ID", &self.number)?;
+        write_element(&mut writer, "cbc:IssueDate", &to_ubl_date(&self.date))?;
+        if credit_note {
+            write_element(
+                &mut writer,
+                "cbc:CreditNoteTypeCode",
+                self.document_type.code(),
+            )?;
+        } else {
+            write_element(&mut writer, "cbc:DueDate", &to_ubl_date(&self.payment_due))?;
+            write_element(
+                &mut writer,
+                "cbc:InvoiceTypeCode",
+                self.document_type.code(),
+            )?;
+        }
+        write_element(
+            &mut writer,
+            "cbc:DocumentCurrencyCode",
+            self.currency.code(),
+        )?;
+        write_element(&mut writer, "cbc:BuyerReference", BUYER_REFERENCE)?;
+
+        if let Some(reference) = &self.billing_reference {
+            writer.write_event(Event::Start(BytesStart::new("cac:BillingReference")))?;
+            writer.write_event(Event::Start(BytesStart::new(
+                "cac:InvoiceDocumentReference",
+            )))?;
+            write_element(&mut writer, "cbc:ID", &reference.number)?;
+            if let Some(date) = &reference.date {
+                write_element(&mut writer, "cbc:IssueDate", &to_ubl_date(date))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("cac:InvoiceDocumentReference")))?;
+            writer.write_event(Event::End(BytesEnd::new("cac:BillingReference")))?;
+        }
+
+        self.write_supplier_party(&mut writer)?;
+
+        self.write_customer_party(&mut writer)?;
+
+        if self.delivery_date.is_some() || self.delivery_country.is_some() {
+            writer.write_event(Event::Start(BytesStart::new("cac:Delivery")))?;
+            if let Some(delivery_date) = &self.delivery_date {
+                write_element(
+                    &mut writer,
+                    "cbc:ActualDeliveryDate",
+                    &to_ubl_date(delivery_date),
+                )?;
+            }
+            if let Some(country) = self.delivery_country {
+                writer.write_event(Event::Start(BytesStart::new("cac:DeliveryLocation")))?;
+                writer.write_event(Event::Start(BytesStart::new("cac:Address")))?;
+                writer.write_event(Event::Start(BytesStart::new("cac:Country")))?;
+                write_element(&mut writer, "cbc:IdentificationCode", country.code())?;
+                writer.write_event(Event::End(BytesEnd::new("cac:Country")))?;
+                writer.write_event(Event::End(BytesEnd::new("cac:Address")))?;
+                writer.write_event(Event::End(BytesEnd::new("cac:DeliveryLocation")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("cac:Delivery")))?;
+        }
+
+        // Credit transfer is the only payment means written, and it needs the account.
+        // CreditNote has no DueDate element, so without an account it has no due date.
+        if let Some((iban, bic)) = self.bank_account() {
+            writer.write_event(Event::Start(BytesStart::new("cac:PaymentMeans")))?;
+            write_element(
+                &mut writer,
+                "cbc:PaymentMeansCode",
+                PAYMENT_MEANS_CREDIT_TRANSFER,
+            )?;
+            if credit_note {
+                write_element(
+                    &mut writer,
+                    "cbc:PaymentDueDate",
+                    &to_ubl_date(&self.payment_due),
+                )?;
+            }
+            writer.write_event(Event::Start(BytesStart::new("cac:PayeeFinancialAccount")))?;
+            write_element(&mut writer, "cbc:ID", iban)?;
+            if let Some(bic) = bic {
+                writer.write_event(Event::Start(BytesStart::new(
+                    "cac:FinancialInstitutionBranch",
+                )))?;
+                write_element(&mut writer, "cbc:ID", bic)?;
+                writer.write_event(Event::End(BytesEnd::new("cac:FinancialInstitutionBranch")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("cac:PayeeFinancialAccount")))?;
+            writer.write_event(Event::End(BytesEnd::new("cac:PaymentMeans")))?;
+        }
+
+        for entry in &self.allowances_charges {
+            write_allowance_charge(&mut writer, entry, self.allowance_charge_base(entry), true)?;
+        }
+
+        writer.write_event(Event::Start(BytesStart::new("cac:TaxTotal")))?;
+        write_amount(&mut writer, "cbc:TaxAmount", &summary.tax_total)?;
+
+        for subtotal in &summary.tax_subtotals {
+            writer.write_event(Event::Start(BytesStart::new("cac:TaxSubtotal")))?;
+            write_amount(&mut writer, "cbc:TaxableAmount", &subtotal.taxable_amount)?;
+            write_amount(&mut writer, "cbc:TaxAmount", &subtotal.tax_amount)?;
+
+            writer.write_event(Event::Start(BytesStart::new("cac:TaxCategory")))?;
+            write_element(&mut writer, "cbc:ID", subtotal.category.code())?;
+            write_element(&mut writer, "cbc:Percent", &to_percent(subtotal.rate))?;
+            if let Some(code) = &subtotal.exemption_code {
+                write_element(&mut writer, "cbc:TaxExemptionReasonCode", code)?;
+            }
+            if !subtotal.exemption_reasons.is_empty() {
+                write_element(
+                    &mut writer,
+                    "cbc:TaxExemptionReason",
+                    &subtotal.exemption_reasons.join(", "),
+                )?;
+            }
+            writer.write_event(Event::Start(BytesStart::new("cac:TaxScheme")))?;
+            write_element(&mut writer, "cbc:ID", "VAT")?;
+            writer.write_event(Event::End(BytesEnd::new("cac:TaxScheme")))?;
+            writer.write_event(Event::End(BytesEnd::new("cac:TaxCategory")))?;
+            writer.write_event(Event::End(BytesEnd::new("cac:TaxSubtotal")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("cac:TaxTotal")))?;
+
+        writer.write_event(Event::Start(BytesStart::new("cac:LegalMonetaryTotal")))?;
+        let has = |kind| self.allowances_charges.iter().any(|e| e.kind == kind);
+        let totals = [
+            ("cbc:LineExtensionAmount", Some(summary.subtotal)),
+            ("cbc:TaxExclusiveAmount", Some(summary.net_total)),
+            ("cbc:TaxInclusiveAmount", Some(summary.total)),
+            (
+                "cbc:AllowanceTotalAmount",
+                has(AllowanceChargeKind::Allowance).then_some(summary.allowance_total),
+            ),
+            (
+                "cbc:ChargeTotalAmount",
+                has(AllowanceChargeKind::Charge).then_some(summary.charge_total),
+            ),
+            (
+                "cbc:PrepaidAmount",
+                self.has_prepayment().then_some(summary.prepaid),
+            ),
+            (
+                "cbc:PayableRoundingAmount",
+                self.rounding_amount.map(|_| summary.rounding),
+            ),
+            ("cbc:PayableAmount", Some(summary.amount_due)),
+        ];
+        for (tag, val) in totals {
+            if let Some(val) = val {
+                write_amount(&mut writer, tag, &val)?;
+            }
+        }
+        writer.write_event(Event::End(BytesEnd::new("cac:LegalMonetaryTotal")))?;
+
+        for (i, prod) in self.products.iter().enumerate() {
+            writer.write_event(Event::Start(BytesStart::new(line_tag)))?;
+            write_element(&mut writer, "cbc:ID", &(i + 1).to_string())?;
+            if let Some(note) = &prod.note {
+                write_element(&mut writer, "cbc:Note", note)?;
+            }
+            let mut qty = BytesStart::new(quantity_tag);
+            qty.push_attribute(("unitCode", prod.unit.code()));
+            write_element_with_attr(&mut writer, qty, &prod.quantity.normalize().to_string())?;
+            write_amount(
+                &mut writer,
+                "cbc:LineExtensionAmount",
+                &prod.net_amount(self.currency),
+            )?;
+            for entry in &prod.allowances_charges {
+                write_allowance_charge(&mut writer, entry, prod.base_amount(self.currency), false)?;
+            }
+            write_item(&mut writer, prod)?;
+            writer.write_event(Event::Start(BytesStart::new("cac:Price")))?;
+            let mut p_amt = BytesStart::new("cbc:PriceAmount");
+            p_amt.push_attribute(("currencyID", self.currency.code()));
+            write_element_with_attr(&mut writer, p_amt, &prod.cost_per_unit.to_string())?;
+            if let Some(base) = prod.price_base_quantity {
+                let mut base_qty = BytesStart::new("cbc:BaseQuantity");
+                base_qty.push_attribute(("unitCode", prod.unit.code()));
+                write_element_with_attr(&mut writer, base_qty, &base.normalize().to_string())?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("cac:Price")))?;
+            writer.write_event(Event::End(BytesEnd::new(line_tag)))?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new(root_name)))?;
+        Ok(String::from_utf8(writer.into_inner().into_inner())?)
+    }
+
+    fn write_supplier_party(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+    ) -> Result<(), InvoiceError> {
+        writer.write_event(Event::Start(BytesStart::new("cac:AccountingSupplierParty")))?;
+        writer.write_event(Event::Start(BytesStart::new("cac:Party")))?;
+        write_endpoint(writer, self.seller.endpoint())?;
+
+        writer.write_event(Event::Start(BytesStart::new("cac:PartyName")))?;
+        write_element(writer, "cbc:Name", &self.seller.name)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:PartyName")))?;
+
+        write_address_block(writer, &self.seller.address)?;
+
+        write_tax_scheme(writer, &self.seller.vat_id, "VAT")?;
+        if let Some(number) = &self.seller.tax_registration_number {
+            write_tax_scheme(writer, number, TAX_REGISTRATION_SCHEME)?;
+        }
+
+        write_legal_entity(
+            writer,
+            &self.seller.name,
+            self.seller.legal_registration_id.as_deref(),
+            self.seller.legal_form.as_deref(),
+        )?;
+
+        writer.write_event(Event::Start(BytesStart::new("cac:Contact")))?;
+        write_element(writer, "cbc:Name", &self.seller.name)?;
+        if let Some(phone) = &self.seller.phone {
+            write_element(writer, "cbc:Telephone", phone)?;
+        }
+        if let Some(email) = &self.seller.email {
+            write_element(writer, "cbc:ElectronicMail", email)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("cac:Contact")))?;
+
+        writer.write_event(Event::End(BytesEnd::new("cac:Party")))?;
+        writer.write_event(Event::End(BytesEnd::new("cac:AccountingSupplierParty")))?;
+        Ok(())
+    }
+
+    fn write_customer_party(
+        &self,
+        writer: &mut Writer<Cursor<Vec<u8>>>,
+    ) -> Result<(), InvoiceError> {
+        writer.write_event(Event::Start(BytesStart::new("cac:AccountingCustomerParty")))?;
+        writer.write_event(Event::Start(BytesStart::new("cac:Party")))?;
+        write_endpoint(writer, self.buyer.endpoint())?;
+        writer.write_event(Event::Start(BytesStart::new("cac:PartyName")))?;
+        write_element(writer, "cbc:Name", &self.buyer.name)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:PartyName")))?;
+
+        write_address_block(writer, &self.buyer.address)?;
+
+        if let Some(vat_id) = &self.buyer.vat_id {
+            write_tax_scheme(writer, vat_id, "VAT")?;
+        }
+
+        write_legal_entity(
+            writer,
+            &self.buyer.name,
+            self.buyer.legal_registration_id.as_deref(),
+            None,
+        )?;
+
+        writer.write_event(Event::Start(BytesStart::new("cac:Contact")))?;
+        write_element(writer, "cbc:Name", &self.buyer.name)?;
+        if !self.buyer.email.is_empty() {
+            write_element(writer, "cbc:ElectronicMail", &self.buyer.email)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("cac:Contact")))?;
+
+        writer.write_event(Event::End(BytesEnd::new("cac:Party")))?;
+        writer.write_event(Event::End(BytesEnd::new("cac:AccountingCustomerParty")))?;
+        Ok(())
+    }
+}
+
+fn write_amount(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    tag: &str,
+    amount: &Money,
+) -> Result<(), InvoiceError> {
+    let mut el = BytesStart::new(tag);
+    el.push_attribute(("currencyID", amount.currency.code()));
+    write_element_with_attr(writer, el, &amount.round().to_string())
+}
+
+/// `cac:AllowanceCharge`; only invoice level entries state their VAT category.
+fn write_allowance_charge(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    entry: &AllowanceCharge,
+    base: Money,
+    with_tax: bool,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("cac:AllowanceCharge")))?;
+    write_element(
+        writer,
+        "cbc:ChargeIndicator",
+        if entry.is_charge() { "true" } else { "false" },
+    )?;
+    if let Some(code) = &entry.reason_code {
+        write_element(writer, "cbc:AllowanceChargeReasonCode", code)?;
+    }
+    if let Some(reason) = &entry.reason {
+        write_element(writer, "cbc:AllowanceChargeReason", reason)?;
+    }
+    if let Some(rate) = entry.percentage() {
+        write_element(writer, "cbc:MultiplierFactorNumeric", &to_percent(rate))?;
+    }
+    write_amount(writer, "cbc:Amount", &entry.amount_on(base))?;
+    if entry.percentage().is_some() {
+        write_amount(writer, "cbc:BaseAmount", &base)?;
+    }
+    if with_tax {
+        writer.write_event(Event::Start(BytesStart::new("cac:TaxCategory")))?;
+        write_element(writer, "cbc:ID", entry.tax_category.code())?;
+        if entry.tax_category.has_rate() {
+            write_element(
+                writer,
+                "cbc:Percent",
+                &to_percent(entry.effective_tax_rate()),
+            )?;
+        }
+        writer.write_event(Event::Start(BytesStart::new("cac:TaxScheme")))?;
+        write_element(writer, "cbc:ID", "VAT")?;
+        writer.write_event(Event::End(BytesEnd::new("cac:TaxScheme")))?;
+        writer.write_event(Event::End(BytesEnd::new("cac:TaxCategory")))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("cac:AllowanceCharge")))?;
+    Ok(())
+}
+
+/// `cac:Item` with the identifiers, classifications and attributes of a line.
+fn write_item(writer: &mut Writer<Cursor<Vec<u8>>>, prod: &Product) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("cac:Item")))?;
+    write_element(writer, "cbc:Name", &prod.description)?;
+    let item_ids = [
+        ("cac:BuyersItemIdentification", &prod.buyer_item_id),
+        ("cac:SellersItemIdentification", &prod.seller_item_id),
+    ];
+    for (tag, id) in item_ids {
+        if let Some(id) = id {
+            writer.write_event(Event::Start(BytesStart::new(tag)))?;
+            write_element(writer, "cbc:ID", id)?;
+            writer.write_event(Event::End(BytesEnd::new(tag)))?;
+        }
+    }
+    if let Some(standard) = &prod.standard_item_id {
+        writer.write_event(Event::Start(BytesStart::new(
+            "cac:StandardItemIdentification",
+        )))?;
+        let mut el = BytesStart::new("cbc:ID");
+        el.push_attribute(("schemeID", standard.scheme.as_str()));
+        write_element_with_attr(writer, el, &standard.id)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:StandardItemIdentification")))?;
+    }
+    if let Some(country) = prod.origin_country {
+        writer.write_event(Event::Start(BytesStart::new("cac:OriginCountry")))?;
+        write_element(writer, "cbc:IdentificationCode", country.code())?;
+        writer.write_event(Event::End(BytesEnd::new("cac:OriginCountry")))?;
+    }
+    for class in &prod.classifications {
+        writer.write_event(Event::Start(BytesStart::new("cac:CommodityClassification")))?;
+        let mut el = BytesStart::new("cbc:ItemClassificationCode");
+        el.push_attribute(("listID", class.list_id.as_str()));
+        if let Some(version) = &class.list_version {
+            el.push_attribute(("listVersionID", version.as_str()));
+        }
+        write_element_with_attr(writer, el, &class.code)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:CommodityClassification")))?;
+    }
+    writer.write_event(Event::Start(BytesStart::new("cac:ClassifiedTaxCategory")))?;
+    write_element(writer, "cbc:ID", prod.tax_category.code())?;
+    if prod.tax_category.has_rate() {
+        write_element(
+            writer,
+            "cbc:Percent",
+            &to_percent(prod.effective_tax_rate()),
+        )?;
+    }
+    writer.write_event(Event::Start(BytesStart::new("cac:TaxScheme")))?;
+    write_element(writer, "cbc:ID", "VAT")?;
+    writer.write_event(Event::End(BytesEnd::new("cac:TaxScheme")))?;
+    writer.write_event(Event::End(BytesEnd::new("cac:ClassifiedTaxCategory")))?;
+    for attribute in &prod.attributes {
+        writer.write_event(Event::Start(BytesStart::new("cac:AdditionalItemProperty")))?;
+        write_element(writer, "cbc:Name", &attribute.name)?;
+        write_element(writer, "cbc:Value", &attribute.value)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:AdditionalItemProperty")))?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("cac:Item")))?;
+    Ok(())
+}
+
+fn write_endpoint(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    endpoint: Option<ElectronicAddress>,
+) -> Result<(), InvoiceError> {
+    if let Some(endpoint) = endpoint {
+        let mut el = BytesStart::new("cbc:EndpointID");
+        el.push_attribute(("schemeID", endpoint.scheme.as_str()));
+        write_element_with_attr(writer, el, &endpoint.value)?;
+    }
+    Ok(())
+}
+
+fn write_tax_scheme(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    company_id: &str,
+    scheme: &str,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("cac:PartyTaxScheme")))?;
+    write_element(writer, "cbc:CompanyID", company_id)?;
+    writer.write_event(Event::Start(BytesStart::new("cac:TaxScheme")))?;
+    write_element(writer, "cbc:ID", scheme)?;
+    writer.write_event(Event::End(BytesEnd::new("cac:TaxScheme")))?;
+    writer.write_event(Event::End(BytesEnd::new("cac:PartyTaxScheme")))?;
+    Ok(())
+}
+
+fn write_legal_entity(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    name: &str,
+    registration_id: Option<&str>,
+    legal_form: Option<&str>,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("cac:PartyLegalEntity")))?;
+    write_element(writer, "cbc:RegistrationName", name)?;
+    if let Some(id) = registration_id {
+        write_element(writer, "cbc:CompanyID", id)?;
+    }
+    if let Some(form) = legal_form {
+        write_element(writer, "cbc:CompanyLegalForm", form)?;
+    }
+    writer.write_event(Event::End(BytesEnd::new("cac:PartyLegalEntity")))?;
+    Ok(())
+}
+
+fn write_address_block(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    addr: &Address,
+) -> Result<(), InvoiceError> {
+    writer.write_event(Event::Start(BytesStart::new("cac:PostalAddress")))?;
+    write_element(writer, "cbc:StreetName", &addr.street_line())?;
+    let (line_two, line_three) = additional_lines(addr);
+    if let Some(line) = line_two {
+        write_element(writer, "cbc:AdditionalStreetName", line)?;
+    }
+    if !addr.town.is_empty() {
+        write_element(writer, "cbc:CityName", &addr.town)?;
+    }
+    if !addr.code.is_empty() {
+        write_element(writer, "cbc:PostalZone", &addr.code)?;
+    }
+    if let Some(subdivision) = &addr.subdivision {
+        write_element(writer, "cbc:CountrySubentity", subdivision)?;
+    }
+    if let Some(line) = line_three {
+        writer.write_event(Event::Start(BytesStart::new("cac:AddressLine")))?;
+        write_element(writer, "cbc:Line", &line)?;
+        writer.write_event(Event::End(BytesEnd::new("cac:AddressLine")))?;
+    }
+    writer.write_event(Event::Start(BytesStart::new("cac:Country")))?;
+    write_element(writer, "cbc:IdentificationCode", addr.country.code())?;
+    writer.write_event(Event::End(BytesEnd::new("cac:Country")))?;
+    writer.write_event(Event::End(BytesEnd::new("cac:PostalAddress")))?;
+    Ok(())
+}
+
+impl Invoice {
+    /// Reads a UBL 2.1 invoice or credit note.
+    ///
+    /// Elements the model has no place for and values that had to be converted are listed
+    /// in the returned report. Totals are recomputed from the lines; stated amounts that
+    /// differ are reported as lossy. The locale is set to English.
+    pub fn from_ubl_xml(xml: &str) -> Result<(Invoice, ParseReport), InvoiceError> {
+        let root = Node::parse(xml)?;
+        let credit_note = match root.local_name() {
+            "Invoice" => false,
+            "CreditNote" => true,
+            other => {
+                return Err(InvoiceError::parse(format!(
+                    "Expected a UBL Invoice or CreditNote, found {}",
+                    other
+                )));
+            }
+        };
+        let (line_tag, quantity_tag) = if credit_note {
+            ("CreditNoteLine", "CreditedQuantity")
+        } else {
+            ("InvoiceLine", "InvoicedQuantity")
+        };
+        let mut report = ParseReport::default();
+
+        for tag in ["UBLVersionID", "CustomizationID", "ProfileID"] {
+            root.children(tag).for_each(Node::consume);
+        }
+        if let Some(reference) = root.child("BuyerReference")
+            && reference.is(BUYER_REFERENCE)
+        {
+            reference.consume();
+        }
+
+        let number = root
+            .text_at(&["ID"])
+            .ok_or_else(|| InvoiceError::parse("Invoice has no ID"))?
+            .to_string();
+        let date = parse_iso_date(
+            root.text_at(&["IssueDate"])
+                .ok_or_else(|| InvoiceError::parse("Invoice has no IssueDate"))?,
+        )?;
+        let payment_due = match root
+            .text_at(&["DueDate"])
+            .or_else(|| root.text_at(&["PaymentMeans", "PaymentDueDate"]))
+        {
+            Some(due) => parse_iso_date(due)?,
+            None => {
+                report.lossy("No due date, using the issue date");
+                date
+            }
+        };
+        let default_type = if credit_note {
+            DocumentType::CreditNote
+        } else {
+            DocumentType::CommercialInvoice
+        };
+        let document_type = match root
+            .text_at(&["InvoiceTypeCode"])
+            .or_else(|| root.text_at(&["CreditNoteTypeCode"]))
+        {
+            Some(code) => parse_document_type(code, default_type, &mut report),
+            None => default_type,
+        };
+        let billing_reference = root
+            .find(&["BillingReference", "InvoiceDocumentReference"])
+            .map(|reference| -> Result<BillingReference, InvoiceError> {
+                Ok(BillingReference {
+                    number: reference.text_at(&["ID"]).unwrap_or_default().to_string(),
+                    date: reference
+                        .text_at(&["IssueDate"])
+                        .map(parse_iso_date)
+                        .transpose()?,
+                })
+            })
+            .transpose()?;
+        let currency = parse_currency(
+            root.text_at(&["DocumentCurrencyCode"])
+                .ok_or_else(|| InvoiceError::parse("Invoice has no DocumentCurrencyCode"))?,
+        )?;
+
+        let supplier = root
+            .find(&["AccountingSupplierParty", "Party"])
+            .ok_or_else(|| InvoiceError::parse("Invoice has no AccountingSupplierParty"))?;
+        let seller_name = read_party_name(supplier)?;
+        let seller_contact = supplier.child("Contact");
+        let seller_email = seller_contact
+            .and_then(|c| c.text_at(&["ElectronicMail"]))
+            .map(str::to_string);
+        let seller = Seller {
+            address: read_address(supplier, &mut report),
+            vat_id: read_tax_scheme(supplier, |id| id == "VAT")
+                .unwrap_or_default()
+                .to_string(),
+            tax_registration_number: read_tax_scheme(supplier, |id| id != "VAT")
+                .map(str::to_string),
+            legal_registration_id: supplier
+                .text_at(&["PartyLegalEntity", "CompanyID"])
+                .map(str::to_string),
+            legal_form: supplier
+                .text_at(&["PartyLegalEntity", "CompanyLegalForm"])
+                .map(str::to_string),
+            electronic_address: read_endpoint(supplier, seller_email.as_deref()),
+            website: String::new(),
+            phone: seller_contact
+                .and_then(|c| c.text_at(&["Telephone"]))
+                .map(str::to_string),
+            email: seller_email,
+            name: seller_name,
+        };
+        consume_if(seller_contact.and_then(|c| c.child("Name")), &seller.name);
+
+        let customer = root
+            .find(&["AccountingCustomerParty", "Party"])
+            .ok_or_else(|| InvoiceError::parse("Invoice has no AccountingCustomerParty"))?;
+        let buyer_contact = customer.child("Contact");
+        let buyer_email = buyer_contact
+            .and_then(|c| c.text_at(&["ElectronicMail"]))
+            .unwrap_or_default()
+            .to_string();
+        let buyer = Buyer {
+            name: read_party_name(customer)?,
+            address: read_address(customer, &mut report),
+            vat_id: read_tax_scheme(customer, |id| id == "VAT").map(str::to_string),
+            tax_registration_number: None,
+            legal_registration_id: customer
+                .text_at(&["PartyLegalEntity", "CompanyID"])
+                .map(str::to_string),
+            legal_form: None,
+            electronic_address: read_endpoint(customer, Some(&buyer_email)),
+            email: buyer_email,
+        };
+        consume_if(buyer_contact.and_then(|c| c.child("Name")), &buyer.name);
+
+        let delivery_date = root
+            .text_at(&["Delivery", "ActualDeliveryDate"])
+            .map(parse_iso_date)
+            .transpose()?;
+        let delivery_country = root
+            .text_at(&[
+                "Delivery",
+                "DeliveryLocation",
+                "Address",
+                "Country",
+                "IdentificationCode",
+            ])
+            .and_then(|code| parse_country(code, "Delivery country", &mut report));
+
+        let mut iban = None;
+        let mut bic = None;
+        if let Some(means) = root.child("PaymentMeans") {
+            if let Some(code) = means.text_at(&["PaymentMeansCode"])
+                && code != PAYMENT_MEANS_CREDIT_TRANSFER
+                && code != "58"
+            {
+                report.lossy(format!(
+                    "Payment means code {} read as credit transfer",
+                    code
+                ));
+            }
+            iban = means.text_at(&["PayeeFinancialAccount", "ID"]);
+            bic = means.text_at(&["PayeeFinancialAccount", "FinancialInstitutionBranch", "ID"]);
+        }
+
+        // Line categories carry no exemption reason in UBL, only the VAT breakdown does.
+        let mut exemptions: Vec<(&str, Option<&str>, Option<&str>)> = Vec::new();
+        let tax_total = root.child("TaxTotal");
+        for subtotal in tax_total.iter().flat_map(|t| t.children("TaxSubtotal")) {
+            for tag in ["TaxableAmount", "TaxAmount"] {
+                subtotal.children(tag).for_each(Node::consume);
+            }
+            let Some(category) = subtotal.child("TaxCategory") else {
+                continue;
+            };
+            category.children("Percent").for_each(Node::consume);
+            category.find(&["TaxScheme", "ID"]).map(Node::consume);
+            if let Some(id) = category.text_at(&["ID"]) {
+                exemptions.push((
+                    id,
+                    category.text_at(&["TaxExemptionReason"]),
+                    category.text_at(&["TaxExemptionReasonCode"]),
+                ));
+            }
+        }
+
+        let mut products = Vec::new();
+        for line in root.children(line_tag) {
+            line.children("ID").for_each(Node::consume);
+            let quantity = line
+                .child(quantity_tag)
+                .ok_or_else(|| InvoiceError::parse("InvoiceLine without quantity"))?;
+            let unit = parse_unit(quantity.attr("unitCode"), &mut report);
+            let quantity = parse_decimal(quantity.text(), "quantity")?;
+            let item = line
+                .child("Item")
+                .ok_or_else(|| InvoiceError::parse("InvoiceLine without Item"))?;
+            let tax = item
+                .child("ClassifiedTaxCategory")
+                .ok_or_else(|| InvoiceError::parse("Item without ClassifiedTaxCategory"))?;
+            tax.find(&["TaxScheme", "ID"]).map(Node::consume);
+            let category = tax
+                .text_at(&["ID"])
+                .ok_or_else(|| InvoiceError::parse("Tax category without ID"))?;
+            let rate = tax.text_at(&["Percent"]).map(parse_percent).transpose()?;
+            let (reason, code) = exemptions
+                .iter()
+                .find(|(id, _, _)| *id == category)
+                .map_or((None, None), |(_, reason, code)| (*reason, *code));
+            let tax = tax_fields(
+                category,
+                rate.unwrap_or(Decimal::ZERO),
+                reason,
+                code,
+                &mut report,
+            );
+
+            let price = line
+                .child("Price")
+                .ok_or_else(|| InvoiceError::parse("InvoiceLine without Price"))?;
+            let cost_per_unit = parse_decimal(
+                price
+                    .text_at(&["PriceAmount"])
+                    .ok_or_else(|| InvoiceError::parse("Price without amount"))?,
+                "price",
+            )?;
+            let price_base_quantity =
+                parse_base_quantity(price.child("BaseQuantity"), unit, &mut report)?;
+
+            let allowances_charges = line
+                .children("AllowanceCharge")
+                .map(|node| read_allowance_charge(node, &mut report))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let product = Product {
+                description: item.text_at(&["Name"]).unwrap_or_default().to_string(),
+                quantity,
+                unit,
+                cost_per_unit,
+                price_base_quantity,
+                tax_rate: tax.rate,
+                tax_category: tax.category,
+                tax_exempt_reason: tax.reason,
+                tax_exemption_code: tax.code,
+                allowances_charges,
+                note: line.text_at(&["Note"]).map(str::to_string),
+                seller_item_id: item
+                    .text_at(&["SellersItemIdentification", "ID"])
+                    .map(str::to_string),
+                buyer_item_id: item
+                    .text_at(&["BuyersItemIdentification", "ID"])
+                    .map(str::to_string),
+                standard_item_id: item.find(&["StandardItemIdentification", "ID"]).map(|id| {
+                    ItemIdentifier::new(id.attr("schemeID").unwrap_or_default(), id.text())
+                }),
+                classifications: item
+                    .children("CommodityClassification")
+                    .filter_map(|class| class.child("ItemClassificationCode"))
+                    .map(|code| ItemClassification {
+                        list_id: code.attr("listID").unwrap_or_default().to_string(),
+                        list_version: code.attr("listVersionID").map(str::to_string),
+                        code: code.text().to_string(),
+                    })
+                    .collect(),
+                origin_country: item
+                    .text_at(&["OriginCountry", "IdentificationCode"])
+                    .and_then(|code| parse_country(code, "Origin country", &mut report)),
+                attributes: item
+                    .children("AdditionalItemProperty")
+                    .map(|property| {
+                        ItemAttribute::new(
+                            property.text_at(&["Name"]).unwrap_or_default(),
+                            property.text_at(&["Value"]).unwrap_or_default(),
+                        )
+                    })
+                    .collect(),
+            };
+            for (node, entry) in line
+                .children("AllowanceCharge")
+                .zip(&product.allowances_charges)
+            {
+                check_allowance_charge(
+                    node.child("Amount"),
+                    node.child("BaseAmount"),
+                    entry,
+                    product
+                        .checked_base_amount(currency)
+                        .ok_or_else(out_of_range)?,
+                    &mut report,
+                )?;
+            }
+            check_amount(
+                line.child("LineExtensionAmount"),
+                "Line net amount",
+                product
+                    .checked_net_amount(currency)
+                    .ok_or_else(out_of_range)?,
+                &mut report,
+            )?;
+            products.push(product);
+        }
+
+        let allowances_charges = root
+            .children("AllowanceCharge")
+            .map(|node| read_allowance_charge(node, &mut report))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let totals = root.child("LegalMonetaryTotal");
+        let total = |tag| totals.and_then(|t| t.child(tag));
+        let stated = |tag, what| {
+            total(tag)
+                .map(|node| parse_decimal(node.text(), what))
+                .transpose()
+        };
+
+        let invoice = Invoice {
+            number,
+            document_type,
+            billing_reference,
+            date,
+            seller,
+            buyer,
+            payment_due,
+            delivery_date,
+            delivery_type: None,
+            delivery_country,
+            extra_info: None,
+            payment_type: None,
+            payment_info: payment_info(iban, bic),
+            products,
+            allowances_charges,
+            prepaid_amount: stated("PrepaidAmount", "prepaid amount")?,
+            payments: Vec::new(),
+            rounding_amount: stated("PayableRoundingAmount", "rounding amount")?,
+            currency,
+            locale: Locale::en,
+        };
+
+        let summary = invoice
+            .try_calculate_summary()
+            .map_err(|_| out_of_range())?;
+        for (node, entry) in root
+            .children("AllowanceCharge")
+            .zip(&invoice.allowances_charges)
+        {
+            check_allowance_charge(
+                node.child("Amount"),
+                node.child("BaseAmount"),
+                entry,
+                invoice.allowance_charge_base(entry),
+                &mut report,
+            )?;
+        }
+        for (node, what, computed) in [
+            (
+                tax_total.and_then(|t| t.child("TaxAmount")),
+                "Tax total",
+                summary.tax_total,
+            ),
+            (
+                total("LineExtensionAmount"),
+                "Sum of line net amounts",
+                summary.subtotal,
+            ),
+            (
+                total("TaxExclusiveAmount"),
+                "Total without VAT",
+                summary.net_total,
+            ),
+            (total("TaxInclusiveAmount"), "Total with VAT", summary.total),
+            (
+                total("AllowanceTotalAmount"),
+                "Sum of allowances",
+                summary.allowance_total,
+            ),
+            (
+                total("ChargeTotalAmount"),
+                "Sum of charges",
+                summary.charge_total,
+            ),
+            (total("PayableAmount"), "Amount due", summary.amount_due),
+        ] {
+            check_amount(node, what, computed, &mut report)?;
+        }
+
+        root.collect_unused(&mut report);
+        Ok((invoice, report))
+    }
+}
+
+/// A `cac:AllowanceCharge` of the invoice or a line. Its amounts are checked with
+/// [`check_allowance_charge`] once the base is known.
+fn read_allowance_charge(
+    node: &Node,
+    report: &mut ParseReport,
+) -> Result<AllowanceCharge, InvoiceError> {
+    let kind = match node.text_at(&["ChargeIndicator"]) {
+        Some("true") => AllowanceChargeKind::Charge,
+        Some("false") => AllowanceChargeKind::Allowance,
+        _ => {
+            return Err(InvoiceError::parse(
+                "AllowanceCharge without ChargeIndicator",
+            ));
+        }
+    };
+    let amount = match node.text_at(&["MultiplierFactorNumeric"]) {
+        Some(percent) => AllowanceChargeAmount::Percentage(parse_percent(percent)?),
+        None => AllowanceChargeAmount::Fixed(parse_decimal(
+            node.text_at(&["Amount"])
+                .ok_or_else(|| InvoiceError::parse("AllowanceCharge without Amount"))?,
+            "allowance or charge amount",
+        )?),
+    };
+    let tax = match node.child("TaxCategory") {
+        Some(tax) => {
+            tax.find(&["TaxScheme", "ID"]).map(Node::consume);
+            let category = tax
+                .text_at(&["ID"])
+                .ok_or_else(|| InvoiceError::parse("Tax category without ID"))?;
+            let rate = tax.text_at(&["Percent"]).map(parse_percent).transpose()?;
+            tax_fields(category, rate.unwrap_or(Decimal::ZERO), None, None, report)
+        }
+        None => LineTax::default(),
+    };
+    Ok(AllowanceCharge {
+        kind,
+        amount,
+        reason: node.text_at(&["AllowanceChargeReason"]).map(str::to_string),
+        reason_code: node
+            .text_at(&["AllowanceChargeReasonCode"])
+            .map(str::to_string),
+        tax_category: tax.category,
+        tax_rate: tax.rate,
+    })
+}
+
+/// `PartyName/Name`, falling back to the legal registration name.
+fn read_party_name(party: &Node) -> Result<String, InvoiceError> {
+    let registration = party.find(&["PartyLegalEntity", "RegistrationName"]);
+    let name = match party.text_at(&["PartyName", "Name"]) {
+        Some(name) => name,
+        None => registration
+            .ok_or_else(|| InvoiceError::parse("Party without a name"))?
+            .text(),
+    };
+    consume_if(registration, name);
+    Ok(name.to_string())
+}
+
+/// CompanyID of the first `PartyTaxScheme` whose scheme matches.
+fn read_tax_scheme(party: &Node, matches: impl Fn(&str) -> bool) -> Option<&str> {
+    let scheme = party.children("PartyTaxScheme").find(|scheme| {
+        scheme
+            .find(&["TaxScheme", "ID"])
+            .is_some_and(|id| matches(id.text()))
+    })?;
+    scheme.text_at(&["CompanyID"])
+}
+
+fn read_endpoint(party: &Node, email: Option<&str>) -> Option<ElectronicAddress> {
+    let endpoint = party.child("EndpointID")?;
+    electronic_address(endpoint.attr("schemeID"), endpoint.text(), email)
+}
+
+/// Consumes an element that repeats a value already read, so it is not reported.
+fn consume_if(node: Option<&Node>, value: &str) {
+    if let Some(node) = node
+        && node.is(value)
+    {
+        node.consume();
+    }
+}
+
+fn read_address(party: &Node, report: &mut ParseReport) -> Address {
+    let Some(address) = party.child("PostalAddress") else {
+        return parse_address("", None, None, report);
+    };
+    let additional_lines = address
+        .children("AdditionalStreetName")
+        .chain(
+            address
+                .children("AddressLine")
+                .filter_map(|l| l.child("Line")),
+        )
+        .map(|line| line.text().to_string())
+        .collect();
+    Address {
+        additional_lines,
+        code: address
+            .text_at(&["PostalZone"])
+            .unwrap_or_default()
+            .to_string(),
+        town: address
+            .text_at(&["CityName"])
+            .unwrap_or_default()
+            .to_string(),
+        subdivision: address.text_at(&["CountrySubentity"]).map(str::to_string),
+        ..parse_address(
+            address.text_at(&["StreetName"]).unwrap_or_default(),
+            address.text_at(&["BuildingNumber"]),
+            address.text_at(&["Country", "IdentificationCode"]),
+            report,
+        )
+    }
+}
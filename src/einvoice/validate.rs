@@ -0,0 +1,1439 @@
+//! Offline checks of the EN 16931 business rules and the XRechnung `BR-DE-*` rules.
+//!
+//! The rules run on a flat view of the document, built either from an [`Invoice`] as the
+//! writers would serialize it or from the elements of a UBL / CII file as stated there.
+//! That way stated totals are checked as written instead of being recomputed.
+
+use super::parse::Node;
+use super::{BUYER_REFERENCE, EInvoiceProfile, PAYMENT_MEANS_CREDIT_TRANSFER};
+use crate::error::InvoiceError;
+use crate::models::{Decimal, Invoice};
+use std::fmt;
+
+/// How the official validation artefacts flag a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Fatal,
+}
+
+/// One violated business rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Rule identifier, e.g. `BR-CO-15` or `BR-DE-7`.
+    pub rule: String,
+    pub severity: Severity,
+    /// Business term or group the rule is about, e.g. `BT-112`.
+    pub field: &'static str,
+    /// 1-based position of the invoice line, for line-level rules.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Fatal => "fatal",
+        };
+        write!(f, "[{}] {} {}", self.rule, severity, self.field)?;
+        if let Some(line) = self.line {
+            write!(f, " (line {})", line)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    /// True if no fatal rule is violated; warnings do not make an invoice invalid.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Fatal)
+    }
+
+    /// Whether the given rule was reported.
+    pub fn has(&self, rule: &str) -> bool {
+        self.findings.iter().any(|f| f.rule == rule)
+    }
+
+    fn fatal(&mut self, rule: impl Into<String>, field: &'static str, message: impl Into<String>) {
+        self.push(rule, Severity::Fatal, field, None, message);
+    }
+
+    fn push(
+        &mut self,
+        rule: impl Into<String>,
+        severity: Severity,
+        field: &'static str,
+        line: Option<usize>,
+        message: impl Into<String>,
+    ) {
+        self.findings.push(Finding {
+            rule: rule.into(),
+            severity,
+            field,
+            line,
+            message: message.into(),
+        });
+    }
+}
+
+/// Validates an invoice as it would be written for `profile`. Fails like the writers
+/// when its amounts exceed the supported range.
+pub fn validate_invoice(
+    invoice: &Invoice,
+    profile: EInvoiceProfile,
+) -> Result<ValidationReport, InvoiceError> {
+    Ok(check(&Document::from_invoice(invoice, profile)?, profile))
+}
+
+/// Validates a UBL or CII document. The profile is taken from its specification
+/// identifier (BT-24); unknown identifiers are checked as plain EN 16931.
+pub fn validate_xml(xml: &str) -> Result<ValidationReport, InvoiceError> {
+    let root = Node::parse(xml)?;
+    let document = match root.local_name() {
+        "Invoice" | "CreditNote" => Document::from_ubl(&root),
+        "CrossIndustryInvoice" => Document::from_cii(&root),
+        other => {
+            return Err(InvoiceError::parse(format!(
+                "Unsupported e-invoice root element {}",
+                other
+            )));
+        }
+    };
+    let profile = EInvoiceProfile::ALL
+        .into_iter()
+        .find(|p| document.specification.as_deref() == Some(p.guideline_id()))
+        .unwrap_or_default();
+    Ok(check(&document, profile))
+}
+
+/// The business terms the rules look at. Rates are percentages as written in the XML.
+#[derive(Default)]
+struct Document {
+    specification: Option<String>,
+    number: Option<String>,
+    issue_date: Option<String>,
+    type_code: Option<String>,
+    preceding_invoice: Option<String>,
+    currency: Option<String>,
+    buyer_reference: Option<String>,
+    seller: Party,
+    buyer: Party,
+    payment_means: Option<String>,
+    iban: Option<String>,
+    due_date: Option<String>,
+    payment_terms: Option<String>,
+    /// Actual delivery date (BT-72).
+    delivery_date: Option<String>,
+    /// Whether an invoicing period (BG-14) is given.
+    has_invoicing_period: bool,
+    /// Deliver to country code (BT-80).
+    delivery_country: Option<String>,
+    lines: Vec<Line>,
+    adjustments: Vec<Adjustment>,
+    breakdown: Vec<Breakdown>,
+    totals: Totals,
+}
+
+#[derive(Default)]
+struct Party {
+    name: Option<String>,
+    has_address: bool,
+    city: Option<String>,
+    postcode: Option<String>,
+    country: Option<String>,
+    vat_id: Option<String>,
+    /// Tax registration identifier (BT-32), scheme `FC`.
+    tax_registration: Option<String>,
+    /// Legal registration identifier (BT-30, BT-47).
+    legal_registration: Option<String>,
+    endpoint: Option<String>,
+    endpoint_scheme: Option<String>,
+    has_contact: bool,
+    contact_name: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Default)]
+struct Line {
+    id: Option<String>,
+    name: Option<String>,
+    quantity: Option<Decimal>,
+    net_amount: Option<Decimal>,
+    net_price: Option<Decimal>,
+    category: Option<String>,
+    rate: Option<Decimal>,
+    /// Scheme of the standard item identifier (BT-157-1), if one is given.
+    standard_id_scheme: Option<Option<String>>,
+    /// Scheme of each item classification (BT-158-1).
+    classification_lists: Vec<Option<String>>,
+    /// Name and value of each item attribute (BG-32).
+    attributes: Vec<(Option<String>, Option<String>)>,
+}
+
+/// A document level allowance (BG-20) or charge (BG-21).
+#[derive(Default)]
+struct Adjustment {
+    charge: bool,
+    amount: Option<Decimal>,
+    reason: Option<String>,
+    reason_code: Option<String>,
+    category: Option<String>,
+    rate: Option<Decimal>,
+}
+
+#[derive(Default)]
+struct Breakdown {
+    category: Option<String>,
+    rate: Option<Decimal>,
+    taxable: Option<Decimal>,
+    tax: Option<Decimal>,
+    reason: Option<String>,
+}
+
+#[derive(Default)]
+struct Totals {
+    line_total: Option<Decimal>,
+    allowance_total: Option<Decimal>,
+    charge_total: Option<Decimal>,
+    tax_exclusive: Option<Decimal>,
+    tax_total: Option<Decimal>,
+    tax_inclusive: Option<Decimal>,
+    prepaid: Option<Decimal>,
+    rounding: Option<Decimal>,
+    payable: Option<Decimal>,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn text(node: Option<&Node>) -> Option<String> {
+    node.and_then(|n| non_empty(n.text()))
+}
+
+fn amount(node: Option<&Node>) -> Option<Decimal> {
+    node.and_then(|n| n.text().parse().ok())
+}
+
+impl Document {
+    fn from_invoice(invoice: &Invoice, profile: EInvoiceProfile) -> Result<Document, InvoiceError> {
+        let summary = invoice.try_calculate_summary()?;
+        let percent = |rate: Decimal| (rate * Decimal::ONE_HUNDRED).normalize();
+        let contacts = profile.includes_contacts();
+        let bank_account = invoice.bank_account();
+        let has = |charge: bool| {
+            profile.includes_details()
+                && invoice
+                    .allowances_charges
+                    .iter()
+                    .any(|e| e.is_charge() == charge)
+        };
+        // Electronic addresses are not part of MINIMUM.
+        let seller_endpoint = invoice
+            .seller
+            .endpoint()
+            .filter(|_| profile.includes_details());
+        let buyer_endpoint = invoice
+            .buyer
+            .endpoint()
+            .filter(|_| profile.includes_details());
+
+        Ok(Document {
+            specification: Some(profile.guideline_id().to_string()),
+            number: non_empty(&invoice.number),
+            issue_date: Some(super::to_ubl_date(&invoice.date)),
+            type_code: Some(invoice.document_type.code().to_string()),
+            preceding_invoice: invoice
+                .billing_reference
+                .as_ref()
+                .filter(|_| profile.includes_details())
+                .and_then(|r| non_empty(&r.number)),
+            currency: Some(invoice.currency.code().to_string()),
+            buyer_reference: Some(BUYER_REFERENCE.to_string()),
+            seller: Party {
+                name: non_empty(&invoice.seller.name),
+                has_address: true,
+                city: non_empty(&invoice.seller.address.town),
+                postcode: non_empty(&invoice.seller.address.code),
+                country: Some(invoice.seller.address.country.code().to_string()),
+                vat_id: non_empty(&invoice.seller.vat_id),
+                tax_registration: invoice
+                    .seller
+                    .tax_registration_number
+                    .as_deref()
+                    .and_then(non_empty),
+                legal_registration: invoice
+                    .seller
+                    .legal_registration_id
+                    .as_deref()
+                    .and_then(non_empty),
+                endpoint: seller_endpoint.as_ref().map(|e| e.value.clone()),
+                endpoint_scheme: seller_endpoint.map(|e| e.scheme),
+                has_contact: contacts,
+                contact_name: contacts.then(|| non_empty(&invoice.seller.name)).flatten(),
+                phone: contacts
+                    .then(|| invoice.seller.phone.as_deref().and_then(non_empty))
+                    .flatten(),
+                email: contacts
+                    .then(|| invoice.seller.email.as_deref().and_then(non_empty))
+                    .flatten(),
+            },
+            buyer: Party {
+                name: non_empty(&invoice.buyer.name),
+                has_address: profile.includes_details(),
+                city: non_empty(&invoice.buyer.address.town),
+                postcode: non_empty(&invoice.buyer.address.code),
+                country: Some(invoice.buyer.address.country.code().to_string()),
+                vat_id: invoice
+                    .buyer
+                    .vat_id
+                    .as_deref()
+                    .filter(|_| profile.includes_details())
+                    .and_then(non_empty),
+                legal_registration: invoice
+                    .buyer
+                    .legal_registration_id
+                    .as_deref()
+                    .and_then(non_empty),
+                endpoint: buyer_endpoint.as_ref().map(|e| e.value.clone()),
+                endpoint_scheme: buyer_endpoint.map(|e| e.scheme),
+                email: non_empty(&invoice.buyer.email),
+                ..Party::default()
+            },
+            payment_means: bank_account.map(|_| PAYMENT_MEANS_CREDIT_TRANSFER.to_string()),
+            iban: bank_account.map(|(iban, _)| iban.to_string()),
+            due_date: Some(super::to_ubl_date(&invoice.payment_due)),
+            payment_terms: None,
+            delivery_date: invoice
+                .delivery_date
+                .as_ref()
+                .filter(|_| profile.includes_details())
+                .map(super::to_ubl_date),
+            has_invoicing_period: false,
+            delivery_country: invoice
+                .delivery_country
+                .filter(|_| profile.includes_details())
+                .map(|country| country.code().to_string()),
+            lines: if profile.includes_lines() {
+                invoice
+                    .products
+                    .iter()
+                    .enumerate()
+                    .map(|(i, product)| Line {
+                        id: Some((i + 1).to_string()),
+                        name: non_empty(&product.description),
+                        quantity: Some(product.quantity),
+                        net_amount: Some(product.net_amount(invoice.currency).amount),
+                        net_price: Some(product.cost_per_unit),
+                        category: Some(product.tax_category.code().to_string()),
+                        rate: product
+                            .tax_category
+                            .has_rate()
+                            .then(|| percent(product.effective_tax_rate())),
+                        standard_id_scheme: product
+                            .standard_item_id
+                            .as_ref()
+                            .map(|id| non_empty(&id.scheme)),
+                        classification_lists: product
+                            .classifications
+                            .iter()
+                            .filter(|_| profile.includes_item_details())
+                            .map(|c| non_empty(&c.list_id))
+                            .collect(),
+                        attributes: product
+                            .attributes
+                            .iter()
+                            .filter(|_| profile.includes_item_details())
+                            .map(|a| (non_empty(&a.name), non_empty(&a.value)))
+                            .collect(),
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            adjustments: invoice
+                .allowances_charges
+                .iter()
+                .filter(|_| profile.includes_details())
+                .map(|entry| Adjustment {
+                    charge: entry.is_charge(),
+                    amount: Some(entry.amount_on(invoice.allowance_charge_base(entry)).amount),
+                    reason: entry.reason.as_deref().and_then(non_empty),
+                    reason_code: entry.reason_code.as_deref().and_then(non_empty),
+                    category: Some(entry.tax_category.code().to_string()),
+                    rate: entry
+                        .tax_category
+                        .has_rate()
+                        .then(|| percent(entry.effective_tax_rate())),
+                })
+                .collect(),
+            breakdown: summary
+                .tax_subtotals
+                .iter()
+                .map(|subtotal| Breakdown {
+                    category: Some(subtotal.category.code().to_string()),
+                    rate: Some(percent(subtotal.rate)),
+                    taxable: Some(subtotal.taxable_amount.amount),
+                    tax: Some(subtotal.tax_amount.amount),
+                    reason: non_empty(&subtotal.exemption_reasons.join("; "))
+                        .or_else(|| subtotal.exemption_code.clone()),
+                })
+                .collect(),
+            totals: Totals {
+                line_total: Some(summary.subtotal.amount),
+                allowance_total: has(false).then_some(summary.allowance_total.amount),
+                charge_total: has(true).then_some(summary.charge_total.amount),
+                tax_exclusive: Some(summary.net_total.amount),
+                tax_total: Some(summary.tax_total.amount),
+                tax_inclusive: Some(summary.total.amount),
+                prepaid: (profile.includes_details() && invoice.has_prepayment())
+                    .then_some(summary.prepaid.amount),
+                rounding: invoice
+                    .rounding_amount
+                    .filter(|_| profile.includes_details())
+                    .map(|_| summary.rounding.amount),
+                payable: Some(summary.amount_due.amount),
+            },
+        })
+    }
+
+    fn from_ubl(root: &Node) -> Document {
+        let party = |path: &[&str]| {
+            let Some(party) = root.find(path) else {
+                return Party::default();
+            };
+            let address = party.child("PostalAddress");
+            let contact = party.child("Contact");
+            // The VAT identifier or, under any other scheme, the tax registration.
+            let tax_scheme = |vat: bool| {
+                party
+                    .children("PartyTaxScheme")
+                    .find(|scheme| {
+                        scheme
+                            .find(&["TaxScheme", "ID"])
+                            .is_some_and(|id| id.is("VAT") == vat)
+                    })
+                    .and_then(|scheme| text(scheme.child("CompanyID")))
+            };
+            Party {
+                name: text(party.find(&["PartyLegalEntity", "RegistrationName"]))
+                    .or_else(|| text(party.find(&["PartyName", "Name"]))),
+                has_address: address.is_some(),
+                city: text(address.and_then(|a| a.child("CityName"))),
+                postcode: text(address.and_then(|a| a.child("PostalZone"))),
+                country: text(address.and_then(|a| a.find(&["Country", "IdentificationCode"]))),
+                vat_id: tax_scheme(true),
+                tax_registration: tax_scheme(false),
+                legal_registration: text(party.find(&["PartyLegalEntity", "CompanyID"])),
+                endpoint: text(party.child("EndpointID")),
+                endpoint_scheme: party
+                    .child("EndpointID")
+                    .and_then(|e| e.attr("schemeID"))
+                    .and_then(non_empty),
+                has_contact: contact.is_some(),
+                contact_name: text(contact.and_then(|c| c.child("Name"))),
+                phone: text(contact.and_then(|c| c.child("Telephone"))),
+                email: text(contact.and_then(|c| c.child("ElectronicMail"))),
+            }
+        };
+        let means = root.child("PaymentMeans");
+        // The first TaxTotal carries the breakdown in the document currency.
+        let tax_total = root
+            .children("TaxTotal")
+            .find(|t| t.child("TaxSubtotal").is_some())
+            .or_else(|| root.child("TaxTotal"));
+        let totals = root.child("LegalMonetaryTotal");
+        let total = |tag| amount(totals.and_then(|t| t.child(tag)));
+
+        Document {
+            specification: text(root.child("CustomizationID")),
+            number: text(root.child("ID")),
+            issue_date: text(root.child("IssueDate")),
+            type_code: text(root.child("InvoiceTypeCode"))
+                .or_else(|| text(root.child("CreditNoteTypeCode"))),
+            preceding_invoice: text(root.find(&[
+                "BillingReference",
+                "InvoiceDocumentReference",
+                "ID",
+            ])),
+            currency: text(root.child("DocumentCurrencyCode")),
+            buyer_reference: text(root.child("BuyerReference")),
+            seller: party(&["AccountingSupplierParty", "Party"]),
+            buyer: party(&["AccountingCustomerParty", "Party"]),
+            payment_means: text(means.and_then(|m| m.child("PaymentMeansCode"))),
+            iban: text(means.and_then(|m| m.find(&["PayeeFinancialAccount", "ID"]))),
+            due_date: text(root.child("DueDate"))
+                .or_else(|| text(means.and_then(|m| m.child("PaymentDueDate")))),
+            payment_terms: text(root.find(&["PaymentTerms", "Note"])),
+            delivery_date: text(root.find(&["Delivery", "ActualDeliveryDate"])),
+            has_invoicing_period: root.child("InvoicePeriod").is_some(),
+            delivery_country: text(root.find(&[
+                "Delivery",
+                "DeliveryLocation",
+                "Address",
+                "Country",
+                "IdentificationCode",
+            ])),
+            lines: root
+                .children("InvoiceLine")
+                .chain(root.children("CreditNoteLine"))
+                .map(|line| {
+                    let tax = line.find(&["Item", "ClassifiedTaxCategory"]);
+                    Line {
+                        id: text(line.child("ID")),
+                        name: text(line.find(&["Item", "Name"])),
+                        quantity: amount(
+                            line.child("InvoicedQuantity")
+                                .or_else(|| line.child("CreditedQuantity")),
+                        ),
+                        net_amount: amount(line.child("LineExtensionAmount")),
+                        net_price: amount(line.find(&["Price", "PriceAmount"])),
+                        category: text(tax.and_then(|t| t.child("ID"))),
+                        rate: amount(tax.and_then(|t| t.child("Percent"))),
+                        standard_id_scheme: line
+                            .find(&["Item", "StandardItemIdentification", "ID"])
+                            .map(|id| id.attr("schemeID").and_then(non_empty)),
+                        classification_lists: line
+                            .child("Item")
+                            .into_iter()
+                            .flat_map(|item| item.children("CommodityClassification"))
+                            .map(|c| {
+                                c.child("ItemClassificationCode")
+                                    .and_then(|code| code.attr("listID"))
+                                    .and_then(non_empty)
+                            })
+                            .collect(),
+                        attributes: line
+                            .child("Item")
+                            .into_iter()
+                            .flat_map(|item| item.children("AdditionalItemProperty"))
+                            .map(|a| (text(a.child("Name")), text(a.child("Value"))))
+                            .collect(),
+                    }
+                })
+                .collect(),
+            breakdown: tax_total
+                .map(|t| {
+                    t.children("TaxSubtotal")
+                        .map(|subtotal| {
+                            let category = subtotal.child("TaxCategory");
+                            Breakdown {
+                                category: text(category.and_then(|c| c.child("ID"))),
+                                rate: amount(category.and_then(|c| c.child("Percent"))),
+                                taxable: amount(subtotal.child("TaxableAmount")),
+                                tax: amount(subtotal.child("TaxAmount")),
+                                reason: text(category.and_then(|c| c.child("TaxExemptionReason")))
+                                    .or_else(|| {
+                                        text(
+                                            category
+                                                .and_then(|c| c.child("TaxExemptionReasonCode")),
+                                        )
+                                    }),
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            adjustments: root
+                .children("AllowanceCharge")
+                .map(|entry| {
+                    let tax = entry.child("TaxCategory");
+                    Adjustment {
+                        charge: entry.child("ChargeIndicator").is_some_and(|c| c.is("true")),
+                        amount: amount(entry.child("Amount")),
+                        reason: text(entry.child("AllowanceChargeReason")),
+                        reason_code: text(entry.child("AllowanceChargeReasonCode")),
+                        category: text(tax.and_then(|t| t.child("ID"))),
+                        rate: amount(tax.and_then(|t| t.child("Percent"))),
+                    }
+                })
+                .collect(),
+            totals: Totals {
+                line_total: total("LineExtensionAmount"),
+                allowance_total: total("AllowanceTotalAmount"),
+                charge_total: total("ChargeTotalAmount"),
+                tax_exclusive: total("TaxExclusiveAmount"),
+                tax_total: amount(tax_total.and_then(|t| t.child("TaxAmount"))),
+                tax_inclusive: total("TaxInclusiveAmount"),
+                prepaid: total("PrepaidAmount"),
+                rounding: total("PayableRoundingAmount"),
+                payable: total("PayableAmount"),
+            },
+        }
+    }
+
+    fn from_cii(root: &Node) -> Document {
+        let transaction = root.child("SupplyChainTradeTransaction");
+        let agreement = transaction.and_then(|t| t.child("ApplicableHeaderTradeAgreement"));
+        let settlement = transaction.and_then(|t| t.child("ApplicableHeaderTradeSettlement"));
+        let delivery = transaction.and_then(|t| t.child("ApplicableHeaderTradeDelivery"));
+        let party =
+            |tag| {
+                let Some(party) = agreement.and_then(|a| a.child(tag)) else {
+                    return Party::default();
+                };
+                let address = party.child("PostalTradeAddress");
+                let contact = party.child("DefinedTradeContact");
+                let registration = |scheme| {
+                    party
+                        .children("SpecifiedTaxRegistration")
+                        .filter_map(|registration| registration.child("ID"))
+                        .find(|id| id.attr("schemeID") == Some(scheme))
+                        .and_then(|id| non_empty(id.text()))
+                };
+                Party {
+                    name: text(party.child("Name")),
+                    has_address: address.is_some(),
+                    city: text(address.and_then(|a| a.child("CityName"))),
+                    postcode: text(address.and_then(|a| a.child("PostcodeCode"))),
+                    country: text(address.and_then(|a| a.child("CountryID"))),
+                    vat_id: registration("VA"),
+                    tax_registration: registration(super::TAX_REGISTRATION_SCHEME),
+                    legal_registration: text(party.find(&["SpecifiedLegalOrganization", "ID"])),
+                    endpoint: text(party.find(&["URIUniversalCommunication", "URIID"])),
+                    endpoint_scheme: party
+                        .find(&["URIUniversalCommunication", "URIID"])
+                        .and_then(|e| e.attr("schemeID"))
+                        .and_then(non_empty),
+                    has_contact: contact.is_some(),
+                    contact_name: text(contact.and_then(|c| c.child("PersonName"))),
+                    phone: text(contact.and_then(|c| {
+                        c.find(&["TelephoneUniversalCommunication", "CompleteNumber"])
+                    })),
+                    email: text(
+                        contact.and_then(|c| c.find(&["EmailURIUniversalCommunication", "URIID"])),
+                    ),
+                }
+            };
+        let means = settlement.and_then(|s| s.child("SpecifiedTradeSettlementPaymentMeans"));
+        let terms = settlement.and_then(|s| s.child("SpecifiedTradePaymentTerms"));
+        let currency = text(settlement.and_then(|s| s.child("InvoiceCurrencyCode")));
+        let totals =
+            settlement.and_then(|s| s.child("SpecifiedTradeSettlementHeaderMonetarySummation"));
+        let total = |tag| amount(totals.and_then(|t| t.child(tag)));
+        let date = |node: Option<&Node>| text(node.and_then(|n| n.child("DateTimeString")));
+
+        Document {
+            specification: text(root.find(&[
+                "ExchangedDocumentContext",
+                "GuidelineSpecifiedDocumentContextParameter",
+                "ID",
+            ])),
+            number: text(root.find(&["ExchangedDocument", "ID"])),
+            issue_date: date(root.find(&["ExchangedDocument", "IssueDateTime"])),
+            type_code: text(root.find(&["ExchangedDocument", "TypeCode"])),
+            preceding_invoice: text(
+                settlement.and_then(|s| s.find(&["InvoiceReferencedDocument", "IssuerAssignedID"])),
+            ),
+            buyer_reference: text(agreement.and_then(|a| a.child("BuyerReference"))),
+            seller: party("SellerTradeParty"),
+            buyer: party("BuyerTradeParty"),
+            payment_means: text(means.and_then(|m| m.child("TypeCode"))),
+            iban: text(
+                means.and_then(|m| m.find(&["PayeePartyCreditorFinancialAccount", "IBANID"])),
+            ),
+            due_date: date(terms.and_then(|t| t.child("DueDateDateTime"))),
+            payment_terms: text(terms.and_then(|t| t.child("Description"))),
+            delivery_date: date(
+                delivery.and_then(|d| {
+                    d.find(&["ActualDeliverySupplyChainEvent", "OccurrenceDateTime"])
+                }),
+            ),
+            has_invoicing_period: settlement
+                .and_then(|s| s.child("BillingSpecifiedPeriod"))
+                .is_some(),
+            delivery_country: text(
+                delivery
+                    .and_then(|d| d.find(&["ShipToTradeParty", "PostalTradeAddress", "CountryID"])),
+            ),
+            lines: transaction
+                .map(|t| {
+                    t.children("IncludedSupplyChainTradeLineItem")
+                        .map(|line| {
+                            let settlement = line.child("SpecifiedLineTradeSettlement");
+                            let tax = settlement.and_then(|s| s.child("ApplicableTradeTax"));
+                            let product = line.child("SpecifiedTradeProduct");
+                            Line {
+                                id: text(line.find(&["AssociatedDocumentLineDocument", "LineID"])),
+                                name: text(line.find(&["SpecifiedTradeProduct", "Name"])),
+                                quantity: amount(
+                                    line.find(&["SpecifiedLineTradeDelivery", "BilledQuantity"]),
+                                ),
+                                net_amount: amount(settlement.and_then(|s| {
+                                    s.find(&[
+                                        "SpecifiedTradeSettlementLineMonetarySummation",
+                                        "LineTotalAmount",
+                                    ])
+                                })),
+                                net_price: amount(line.find(&[
+                                    "SpecifiedLineTradeAgreement",
+                                    "NetPriceProductTradePrice",
+                                    "ChargeAmount",
+                                ])),
+                                category: text(tax.and_then(|t| t.child("CategoryCode"))),
+                                rate: amount(tax.and_then(|t| t.child("RateApplicablePercent"))),
+                                standard_id_scheme: product
+                                    .and_then(|p| p.child("GlobalID"))
+                                    .map(|id| id.attr("schemeID").and_then(non_empty)),
+                                classification_lists: product
+                                    .into_iter()
+                                    .flat_map(|p| p.children("DesignatedProductClassification"))
+                                    .map(|c| {
+                                        c.child("ClassCode")
+                                            .and_then(|code| code.attr("listID"))
+                                            .and_then(non_empty)
+                                    })
+                                    .collect(),
+                                attributes: product
+                                    .into_iter()
+                                    .flat_map(|p| p.children("ApplicableProductCharacteristic"))
+                                    .map(|a| (text(a.child("Description")), text(a.child("Value"))))
+                                    .collect(),
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            breakdown: settlement
+                .map(|s| {
+                    s.children("ApplicableTradeTax")
+                        .map(|tax| Breakdown {
+                            category: text(tax.child("CategoryCode")),
+                            rate: amount(tax.child("RateApplicablePercent")),
+                            taxable: amount(tax.child("BasisAmount")),
+                            tax: amount(tax.child("CalculatedAmount")),
+                            reason: text(tax.child("ExemptionReason"))
+                                .or_else(|| text(tax.child("ExemptionReasonCode"))),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            adjustments: settlement
+                .map(|s| {
+                    s.children("SpecifiedTradeAllowanceCharge")
+                        .map(|entry| {
+                            let tax = entry.child("CategoryTradeTax");
+                            Adjustment {
+                                charge: entry
+                                    .find(&["ChargeIndicator", "Indicator"])
+                                    .is_some_and(|c| c.is("true")),
+                                amount: amount(entry.child("ActualAmount")),
+                                reason: text(entry.child("Reason")),
+                                reason_code: text(entry.child("ReasonCode")),
+                                category: text(tax.and_then(|t| t.child("CategoryCode"))),
+                                rate: amount(tax.and_then(|t| t.child("RateApplicablePercent"))),
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            totals: Totals {
+                line_total: total("LineTotalAmount"),
+                allowance_total: total("AllowanceTotalAmount"),
+                charge_total: total("ChargeTotalAmount"),
+                tax_exclusive: total("TaxBasisTotalAmount"),
+                // A second TaxTotalAmount may state the VAT in the accounting currency.
+                tax_total: amount(totals.and_then(|t| {
+                    t.children("TaxTotalAmount").find(|a| {
+                        a.attr("currencyID")
+                            .is_none_or(|c| Some(c) == currency.as_deref())
+                    })
+                })),
+                tax_inclusive: total("GrandTotalAmount"),
+                prepaid: total("TotalPrepaidAmount"),
+                rounding: total("RoundingAmount"),
+                payable: total("DuePayableAmount"),
+            },
+            currency,
+        }
+    }
+}
+
+/// UNCL5305 categories with their rule group name.
+const CATEGORIES: [(&str, &str); 7] = [
+    ("S", "Standard rated"),
+    ("Z", "Zero rated"),
+    ("E", "Exempt from VAT"),
+    ("AE", "Reverse charge"),
+    ("K", "Intra-community supply"),
+    ("G", "Export outside the EU"),
+    ("O", "Not subject to VAT"),
+];
+
+fn check(doc: &Document, profile: EInvoiceProfile) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    check_core(doc, profile, &mut report);
+    check_totals(doc, profile, &mut report);
+    if profile.includes_details() {
+        check_categories(doc, profile, &mut report);
+    }
+    if profile == EInvoiceProfile::XRechnung {
+        check_xrechnung(doc, &mut report);
+    }
+    report
+}
+
+fn check_core(doc: &Document, profile: EInvoiceProfile, report: &mut ValidationReport) {
+    let required = [
+        (
+            "BR-1",
+            "BT-24",
+            doc.specification.is_some(),
+            "Specification identifier",
+        ),
+        ("BR-2", "BT-1", doc.number.is_some(), "Invoice number"),
+        (
+            "BR-3",
+            "BT-2",
+            doc.issue_date.is_some(),
+            "Invoice issue date",
+        ),
+        ("BR-4", "BT-3", doc.type_code.is_some(), "Invoice type code"),
+        (
+            "BR-5",
+            "BT-5",
+            doc.currency.is_some(),
+            "Invoice currency code",
+        ),
+        ("BR-6", "BT-27", doc.seller.name.is_some(), "Seller name"),
+        ("BR-7", "BT-44", doc.buyer.name.is_some(), "Buyer name"),
+        (
+            "BR-8",
+            "BG-5",
+            doc.seller.has_address,
+            "Seller postal address",
+        ),
+        (
+            "BR-9",
+            "BT-40",
+            doc.seller.country.is_some(),
+            "Seller country code",
+        ),
+    ];
+    for (rule, field, present, what) in required {
+        if !present {
+            report.fatal(rule, field, format!("{} is missing", what));
+        }
+    }
+
+    if profile.includes_details() {
+        if !doc.buyer.has_address {
+            report.fatal("BR-10", "BG-8", "Buyer postal address is missing");
+        } else if doc.buyer.country.is_none() {
+            report.fatal("BR-11", "BT-55", "Buyer country code is missing");
+        }
+    }
+
+    let totals = [
+        (
+            "BR-12",
+            "BT-106",
+            doc.totals.line_total,
+            "Sum of invoice line net amounts",
+        ),
+        (
+            "BR-13",
+            "BT-109",
+            doc.totals.tax_exclusive,
+            "Invoice total without VAT",
+        ),
+        (
+            "BR-14",
+            "BT-112",
+            doc.totals.tax_inclusive,
+            "Invoice total with VAT",
+        ),
+        (
+            "BR-15",
+            "BT-115",
+            doc.totals.payable,
+            "Amount due for payment",
+        ),
+    ];
+    for (rule, field, value, what) in totals {
+        // MINIMUM and BASIC WL documents carry no line total.
+        if value.is_none() && (rule != "BR-12" || profile.includes_lines()) {
+            report.fatal(rule, field, format!("{} is missing", what));
+        }
+    }
+
+    if profile.includes_lines() && doc.lines.is_empty() {
+        report.fatal("BR-16", "BG-25", "Invoice has no lines");
+    }
+    for (i, line) in doc.lines.iter().enumerate() {
+        let mut line_rule = |rule: &str, field, message: &str| {
+            report.push(rule, Severity::Fatal, field, Some(i + 1), message);
+        };
+        if line.id.is_none() {
+            line_rule("BR-21", "BT-126", "Invoice line identifier is missing");
+        }
+        if line.quantity.is_none() {
+            line_rule("BR-22", "BT-129", "Invoiced quantity is missing");
+        }
+        if line.net_amount.is_none() {
+            line_rule("BR-24", "BT-131", "Invoice line net amount is missing");
+        }
+        if line.name.is_none() {
+            line_rule("BR-25", "BT-153", "Item name is missing");
+        }
+        match line.net_price {
+            None => line_rule("BR-26", "BT-146", "Item net price is missing"),
+            Some(price) if price.is_sign_negative() => {
+                line_rule("BR-27", "BT-146", "Item net price is negative")
+            }
+            Some(_) => {}
+        }
+        if line.category.is_none() {
+            line_rule(
+                "BR-CO-4",
+                "BT-151",
+                "Invoiced item VAT category code is missing",
+            );
+        }
+        if line
+            .attributes
+            .iter()
+            .any(|(name, value)| name.is_none() || value.is_none())
+        {
+            line_rule("BR-54", "BG-32", "Item attribute without name or value");
+        }
+        if line.standard_id_scheme == Some(None) {
+            line_rule(
+                "BR-64",
+                "BT-157-1",
+                "Item standard identifier without scheme identifier",
+            );
+        }
+        if line.classification_lists.iter().any(Option::is_none) {
+            line_rule(
+                "BR-65",
+                "BT-158-1",
+                "Item classification without scheme identifier",
+            );
+        }
+    }
+
+    for entry in &doc.adjustments {
+        let (rules, fields, what) = if entry.charge {
+            (
+                ["BR-36", "BR-37", "BR-38"],
+                ["BT-99", "BT-102", "BT-104"],
+                "charge",
+            )
+        } else {
+            (
+                ["BR-31", "BR-32", "BR-33"],
+                ["BT-92", "BT-95", "BT-97"],
+                "allowance",
+            )
+        };
+        if entry.amount.is_none() {
+            report.fatal(
+                rules[0],
+                fields[0],
+                format!("Document level {} amount is missing", what),
+            );
+        }
+        if entry.category.is_none() {
+            report.fatal(
+                rules[1],
+                fields[1],
+                format!("Document level {} VAT category code is missing", what),
+            );
+        }
+        if entry.reason.is_none() && entry.reason_code.is_none() {
+            report.fatal(
+                rules[2],
+                fields[2],
+                format!("Document level {} reason and reason code are missing", what),
+            );
+        }
+    }
+
+    for (party, field, who) in [
+        (&doc.seller, "BT-31", "Seller"),
+        (&doc.buyer, "BT-48", "Buyer"),
+    ] {
+        if let Some(vat_id) = &party.vat_id
+            && !vat_id
+                .get(..2)
+                .is_some_and(|prefix| prefix.chars().all(|c| c.is_ascii_uppercase()))
+        {
+            report.fatal(
+                "BR-CO-9",
+                field,
+                format!("{} VAT identifier '{}' has no country prefix", who, vat_id),
+            );
+        }
+    }
+    for (party, rule, field, who) in [
+        (&doc.seller, "BR-62", "BT-34-1", "Seller"),
+        (&doc.buyer, "BR-63", "BT-49-1", "Buyer"),
+    ] {
+        if party.endpoint.is_some() && party.endpoint_scheme.is_none() {
+            report.fatal(
+                rule,
+                field,
+                format!("{} electronic address has no scheme identifier", who),
+            );
+        }
+    }
+
+    if profile.includes_details() && doc.breakdown.is_empty() {
+        report.fatal("BR-CO-18", "BG-23", "Invoice has no VAT breakdown");
+    }
+    if doc.totals.payable.is_some_and(|p| p > Decimal::ZERO)
+        && doc.due_date.is_none()
+        && doc.payment_terms.is_none()
+        && profile.includes_details()
+    {
+        report.fatal(
+            "BR-CO-25",
+            "BT-9",
+            "A positive amount due needs a payment due date or payment terms",
+        );
+    }
+}
+
+/// Sum of the values; `None` when one is missing or the sum does not fit.
+fn sum(values: impl IntoIterator<Item = Option<Decimal>>) -> Option<Decimal> {
+    values
+        .into_iter()
+        .try_fold(Decimal::ZERO, |sum, value| sum.checked_add(value?))
+}
+
+/// Whether `stated` matches the exact `computed` value at the precision it was written in.
+fn matches(stated: Decimal, computed: Decimal) -> bool {
+    stated == computed.round_dp(stated.scale())
+}
+
+fn check_totals(doc: &Document, profile: EInvoiceProfile, report: &mut ValidationReport) {
+    let totals = &doc.totals;
+    let mut expect = |rule, field, stated: Option<Decimal>, computed: Option<Decimal>, what| {
+        if let (Some(stated), Some(computed)) = (stated, computed)
+            && !matches(stated, computed)
+        {
+            report.fatal(
+                rule,
+                field,
+                format!("{} is {}, expected {}", what, stated, computed),
+            );
+        }
+    };
+
+    let line_sum = (!doc.lines.is_empty())
+        .then(|| sum(doc.lines.iter().map(|l| l.net_amount)))
+        .flatten();
+    expect(
+        "BR-CO-10",
+        "BT-106",
+        totals.line_total,
+        line_sum,
+        "Sum of line net amounts",
+    );
+    let adjustment_sum = |charge: bool| {
+        sum(doc
+            .adjustments
+            .iter()
+            .filter(|a| a.charge == charge)
+            .map(|a| a.amount))
+    };
+    expect(
+        "BR-CO-11",
+        "BT-107",
+        totals.allowance_total,
+        adjustment_sum(false),
+        "Sum of allowances on document level",
+    );
+    expect(
+        "BR-CO-12",
+        "BT-108",
+        totals.charge_total,
+        adjustment_sum(true),
+        "Sum of charges on document level",
+    );
+    expect(
+        "BR-CO-13",
+        "BT-109",
+        totals.tax_exclusive,
+        totals.line_total.and_then(|lines| {
+            lines
+                .checked_sub(totals.allowance_total.unwrap_or(Decimal::ZERO))?
+                .checked_add(totals.charge_total.unwrap_or(Decimal::ZERO))
+        }),
+        "Total without VAT",
+    );
+    let breakdown_tax = (!doc.breakdown.is_empty())
+        .then(|| sum(doc.breakdown.iter().map(|b| b.tax)))
+        .flatten();
+    expect(
+        "BR-CO-14",
+        "BT-110",
+        totals.tax_total,
+        breakdown_tax,
+        "Total VAT amount",
+    );
+    expect(
+        "BR-CO-15",
+        "BT-112",
+        totals.tax_inclusive,
+        totals
+            .tax_exclusive
+            .and_then(|net| net.checked_add(totals.tax_total.unwrap_or(Decimal::ZERO))),
+        "Total with VAT",
+    );
+    // MINIMUM documents cannot state a prepaid amount, so the amount due stands alone.
+    expect(
+        "BR-CO-16",
+        "BT-115",
+        totals.payable.filter(|_| profile.includes_details()),
+        totals.tax_inclusive.and_then(|gross| {
+            gross
+                .checked_sub(totals.prepaid.unwrap_or(Decimal::ZERO))?
+                .checked_add(totals.rounding.unwrap_or(Decimal::ZERO))
+        }),
+        "Amount due for payment",
+    );
+    for entry in &doc.breakdown {
+        if let (Some(taxable), Some(rate)) = (entry.taxable, entry.rate) {
+            expect(
+                "BR-CO-17",
+                "BT-117",
+                entry.tax,
+                taxable
+                    .div_round(Decimal::ONE_HUNDRED, taxable.scale() + rate.scale() + 2)
+                    .and_then(|base| base.checked_mul(rate)),
+                "VAT category tax amount",
+            );
+        }
+    }
+}
+
+fn check_categories(doc: &Document, profile: EInvoiceProfile, report: &mut ValidationReport) {
+    for (code, name) in CATEGORIES {
+        let is_code = |category: &Option<String>| category.as_deref() == Some(code);
+        let lines: Vec<(usize, &Line)> = doc
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| is_code(&l.category))
+            .collect();
+        let adjustments: Vec<&Adjustment> = doc
+            .adjustments
+            .iter()
+            .filter(|a| is_code(&a.category))
+            .collect();
+        let entries: Vec<&Breakdown> = doc
+            .breakdown
+            .iter()
+            .filter(|b| is_code(&b.category))
+            .collect();
+        if lines.is_empty() && adjustments.is_empty() && entries.is_empty() {
+            continue;
+        }
+        // Intra-community supply rules are named after "IC", not the category code.
+        let prefix = if code == "K" { "IC" } else { code };
+        let rule = |n: u32| format!("BR-{}-{}", prefix, n);
+
+        if (!lines.is_empty() || !adjustments.is_empty())
+            && (entries.is_empty() || (code == "O" && entries.len() > 1))
+        {
+            report.fatal(
+                rule(1),
+                "BG-23",
+                format!(
+                    "Lines, allowances and charges with category {} need exactly one matching \
+                     VAT breakdown",
+                    code
+                ),
+            );
+        }
+
+        // The tax registration (BT-32) stands in for the VAT identifier except in the
+        // intra-community, export and not subject to VAT rules.
+        let seller_registered = doc.seller.vat_id.is_some()
+            || (matches!(code, "S" | "Z" | "E" | "AE") && doc.seller.tax_registration.is_some());
+        match code {
+            "O" if seller_registered || doc.buyer.vat_id.is_some() => report.fatal(
+                rule(2),
+                "BT-31",
+                format!(
+                    "{} invoices shall not contain seller or buyer VAT identifiers",
+                    name
+                ),
+            ),
+            "O" => {}
+            // Reverse charge also accepts the buyer's legal registration identifier.
+            "AE" if !seller_registered
+                || (doc.buyer.vat_id.is_none() && doc.buyer.legal_registration.is_none()) =>
+            {
+                report.fatal(
+                    rule(2),
+                    "BT-48",
+                    format!(
+                        "{} needs the seller VAT identifier and the buyer VAT identifier or \
+                         legal registration identifier",
+                        name
+                    ),
+                )
+            }
+            "K" if !seller_registered || doc.buyer.vat_id.is_none() => report.fatal(
+                rule(2),
+                "BT-48",
+                format!(
+                    "{} needs both the seller and the buyer VAT identifier",
+                    name
+                ),
+            ),
+            "G" if !seller_registered => report.fatal(
+                rule(2),
+                "BT-31",
+                format!("{} needs the seller VAT identifier", name),
+            ),
+            _ if !seller_registered => report.fatal(
+                rule(2),
+                "BT-31",
+                format!(
+                    "{} needs the seller VAT identifier or tax registration",
+                    name
+                ),
+            ),
+            _ => {}
+        }
+
+        let valid_rate = |rate: Option<Decimal>| match (code, rate) {
+            ("S", Some(rate)) => rate > Decimal::ZERO,
+            ("O", rate) => rate.is_none(),
+            (_, Some(rate)) => rate.is_zero(),
+            (_, None) => false,
+        };
+        let expected = match code {
+            "S" => "greater than zero",
+            "O" => "absent",
+            _ => "zero",
+        };
+        for (i, line) in &lines {
+            if !valid_rate(line.rate) {
+                report.push(
+                    rule(5),
+                    Severity::Fatal,
+                    "BT-152",
+                    Some(i + 1),
+                    format!("{} line VAT rate must be {}", name, expected),
+                );
+            }
+        }
+
+        for adjustment in &adjustments {
+            if !valid_rate(adjustment.rate) {
+                let (n, field, what) = if adjustment.charge {
+                    (7, "BT-103", "charge")
+                } else {
+                    (6, "BT-96", "allowance")
+                };
+                report.fatal(
+                    rule(n),
+                    field,
+                    format!(
+                        "{} document level {} VAT rate must be {}",
+                        name, what, expected
+                    ),
+                );
+            }
+        }
+
+        for entry in &entries {
+            // Standard rated breakdowns are per rate, the others per category.
+            let lines_sum = sum(lines
+                .iter()
+                .filter(|(_, l)| code != "S" || l.rate == entry.rate)
+                .map(|(_, l)| l.net_amount));
+            let taxable = adjustments
+                .iter()
+                .filter(|a| code != "S" || a.rate == entry.rate)
+                .try_fold(lines_sum, |sum, a| {
+                    let amount = a.amount?;
+                    Some(sum.and_then(|s| {
+                        if a.charge {
+                            s.checked_add(amount)
+                        } else {
+                            s.checked_sub(amount)
+                        }
+                    }))
+                })
+                .flatten();
+            if profile.includes_lines()
+                && let (Some(stated), Some(computed)) = (entry.taxable, taxable)
+                && (code == "S" || entries.len() == 1)
+                && stated != computed
+            {
+                report.fatal(
+                    rule(8),
+                    "BT-116",
+                    format!(
+                        "{} taxable amount is {}, lines, allowances and charges add up to {}",
+                        name, stated, computed
+                    ),
+                );
+            }
+            if code != "S" && entry.tax.is_some_and(|tax| !tax.is_zero()) {
+                report.fatal(
+                    rule(9),
+                    "BT-117",
+                    format!("{} tax amount must be zero", name),
+                );
+            }
+            match (code, &entry.reason) {
+                ("S" | "Z", Some(_)) => report.fatal(
+                    rule(10),
+                    "BT-120",
+                    format!("{} breakdown shall not have an exemption reason", name),
+                ),
+                ("S" | "Z", None) | (_, Some(_)) => {}
+                (_, None) => report.fatal(
+                    rule(10),
+                    "BT-120",
+                    format!("{} breakdown needs an exemption reason", name),
+                ),
+            }
+        }
+
+        if code == "K" && !entries.is_empty() {
+            if doc.delivery_date.is_none() && !doc.has_invoicing_period {
+                report.fatal(
+                    rule(11),
+                    "BT-72",
+                    format!("{} needs a delivery date or an invoicing period", name),
+                );
+            }
+            if doc.delivery_country.is_none() {
+                report.fatal(
+                    rule(12),
+                    "BT-80",
+                    format!("{} needs the deliver to country", name),
+                );
+            }
+        }
+
+        if code == "O" && doc.breakdown.len() > entries.len() {
+            report.fatal(
+                "BR-O-11",
+                "BG-23",
+                "Not subject to VAT breakdowns exclude all other VAT categories",
+            );
+        }
+    }
+}
+
+fn check_xrechnung(doc: &Document, report: &mut ValidationReport) {
+    if doc.payment_means.is_none() {
+        report.fatal("BR-DE-1", "BG-16", "Payment instructions are missing");
+    }
+    if !doc.seller.has_contact {
+        report.fatal("BR-DE-2", "BG-6", "Seller contact is missing");
+    }
+    let required = [
+        ("BR-DE-3", "BT-37", doc.seller.city.is_some(), "Seller city"),
+        (
+            "BR-DE-4",
+            "BT-38",
+            doc.seller.postcode.is_some(),
+            "Seller post code",
+        ),
+        (
+            "BR-DE-5",
+            "BT-41",
+            doc.seller.contact_name.is_some(),
+            "Seller contact point",
+        ),
+        (
+            "BR-DE-6",
+            "BT-42",
+            doc.seller.phone.is_some(),
+            "Seller contact telephone number",
+        ),
+        (
+            "BR-DE-7",
+            "BT-43",
+            doc.seller.email.is_some(),
+            "Seller contact email address",
+        ),
+        ("BR-DE-8", "BT-52", doc.buyer.city.is_some(), "Buyer city"),
+        (
+            "BR-DE-9",
+            "BT-53",
+            doc.buyer.postcode.is_some(),
+            "Buyer post code",
+        ),
+        (
+            "BR-DE-15",
+            "BT-10",
+            doc.buyer_reference.is_some(),
+            "Buyer reference",
+        ),
+    ];
+    for (rule, field, present, what) in required {
+        if !present {
+            report.fatal(rule, field, format!("{} is missing", what));
+        }
+    }
+    if doc.breakdown.iter().any(|b| b.rate.is_none()) {
+        report.fatal("BR-DE-14", "BT-119", "VAT category rate is missing");
+    }
+    if doc
+        .payment_means
+        .as_deref()
+        .is_some_and(|code| code == PAYMENT_MEANS_CREDIT_TRANSFER || code == "58")
+        && doc.iban.is_none()
+    {
+        report.fatal("BR-DE-23-a", "BT-84", "Credit transfer without an IBAN");
+    }
+
+    if let Some(code) = &doc.type_code
+        && !["326", "380", "384", "389", "381", "875", "876", "877"].contains(&code.as_str())
+    {
+        report.push(
+            "BR-DE-17",
+            Severity::Warning,
+            "BT-3",
+            None,
+            format!("Invoice type code {} is not accepted by XRechnung", code),
+        );
+    }
+    if doc.type_code.as_deref() == Some("384") && doc.preceding_invoice.is_none() {
+        report.push(
+            "BR-DE-26",
+            Severity::Warning,
+            "BG-3",
+            None,
+            "Corrected invoice without a preceding invoice reference",
+        );
+    }
+    if doc
+        .specification
+        .as_deref()
+        .is_some_and(|id| id != EInvoiceProfile::XRechnung.guideline_id())
+    {
+        report.push(
+            "BR-DE-21",
+            Severity::Warning,
+            "BT-24",
+            None,
+            "Specification identifier is not the XRechnung one",
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::einvoice::{EInvoiceProfile, ProfileError};
+use crate::models::decimal::ParseDecimalError;
+use krilla::error::KrillaError;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong while writing or reading an invoice.
+#[derive(Debug)]
+pub enum InvoiceError {
+    /// The font file could not be read.
+    FontNotFound {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The font was read but is not a usable TrueType / OpenType font; `path` is `None`
+    /// for fonts passed as bytes.
+    InvalidFont {
+        path: Option<PathBuf>,
+    },
+    /// The logo could not be read or decoded.
+    Logo(String),
+    /// The invoice data cannot be represented, e.g. an amount out of range.
+    InvalidInvoice(String),
+    /// Fields the chosen e-invoice profile requires are missing.
+    Profile(ProfileError),
+    /// The profile only exists in CII, e.g. MINIMUM requested as UBL.
+    UnsupportedProfile(EInvoiceProfile),
+    /// Writing or tokenizing XML failed.
+    Xml(quick_xml::Error),
+    /// An inbound UBL / CII document or hybrid PDF does not contain a readable invoice.
+    Parse(String),
+    /// Laying out the PDF pages failed.
+    Render(String),
+    /// krilla could not serialize the document.
+    PdfFinalization(KrillaError),
+    /// The Factur-X attachment or XMP metadata could not be added.
+    Hybrid(String),
+    Io(io::Error),
+}
+
+impl InvoiceError {
+    pub(crate) fn parse(message: impl Into<String>) -> Self {
+        InvoiceError::Parse(message.into())
+    }
+}
+
+impl fmt::Display for InvoiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvoiceError::FontNotFound { path, source } => {
+                write!(f, "font {} not found: {}", path.display(), source)
+            }
+            InvoiceError::InvalidFont { path: Some(path) } => {
+                write!(f, "font {} could not be loaded", path.display())
+            }
+            InvoiceError::InvalidFont { path: None } => write!(f, "font data could not be loaded"),
+            InvoiceError::Logo(reason) => write!(f, "logo could not be loaded: {}", reason),
+            InvoiceError::InvalidInvoice(reason) => write!(f, "invalid invoice data: {}", reason),
+            InvoiceError::Profile(err) => err.fmt(f),
+            InvoiceError::UnsupportedProfile(profile) => {
+                write!(f, "the {} profile is not defined for UBL", profile)
+            }
+            InvoiceError::Xml(err) => write!(f, "XML processing failed: {}", err),
+            InvoiceError::Parse(reason) => write!(f, "invoice could not be read: {}", reason),
+            InvoiceError::Render(reason) => write!(f, "PDF rendering failed: {}", reason),
+            InvoiceError::PdfFinalization(err) => match err {
+                KrillaError::Font(_, reason) => {
+                    write!(
+                        f,
+                        "PDF finalization failed: font could not be embedded: {}",
+                        reason
+                    )
+                }
+                KrillaError::Validation(errors) => {
+                    write!(f, "PDF finalization failed: PDF/A validation: {:?}", errors)
+                }
+                other => write!(f, "PDF finalization failed: {:?}", other),
+            },
+            InvoiceError::Hybrid(reason) => {
+                write!(f, "hybrid e-invoice could not be created: {}", reason)
+            }
+            InvoiceError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InvoiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InvoiceError::FontNotFound { source, .. } => Some(source),
+            InvoiceError::Profile(err) => Some(err),
+            InvoiceError::Xml(err) => Some(err),
+            InvoiceError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProfileError> for InvoiceError {
+    fn from(err: ProfileError) -> Self {
+        InvoiceError::Profile(err)
+    }
+}
+
+impl From<ParseDecimalError> for InvoiceError {
+    fn from(err: ParseDecimalError) -> Self {
+        InvoiceError::InvalidInvoice(err.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for InvoiceError {
+    fn from(err: quick_xml::Error) -> Self {
+        InvoiceError::Xml(err)
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for InvoiceError {
+    fn from(err: quick_xml::events::attributes::AttrError) -> Self {
+        InvoiceError::Xml(err.into())
+    }
+}
+
+impl From<std::str::Utf8Error> for InvoiceError {
+    fn from(err: std::str::Utf8Error) -> Self {
+        InvoiceError::Xml(err.into())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for InvoiceError {
+    fn from(err: std::string::FromUtf8Error) -> Self {
+        InvoiceError::Xml(err.utf8_error().into())
+    }
+}
+
+impl From<io::Error> for InvoiceError {
+    fn from(err: io::Error) -> Self {
+        InvoiceError::Io(err)
+    }
+}
+
+impl From<KrillaError> for InvoiceError {
+    fn from(err: KrillaError) -> Self {
+        InvoiceError::PdfFinalization(err)
+    }
+}
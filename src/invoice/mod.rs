@@ -1 +1,5 @@
-pub use crate::models::{Address, Buyer, Invoice, Product, Seller};
+pub use crate::models::{
+    Address, AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind, BillingReference, Buyer,
+    Country, Currency, Decimal, DocumentType, ElectronicAddress, Invoice, ItemAttribute,
+    ItemClassification, ItemIdentifier, Money, Payment, Product, Seller, TaxCategory, Unit,
+};
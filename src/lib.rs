@@ -2,9 +2,20 @@
 mod test;
 
 pub mod einvoice;
+pub mod error;
 pub mod invoice;
 pub mod models;
 pub mod pdf;
 
+// Compiles the examples of the README.
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+struct ReadmeDoctests;
+
+pub use error::InvoiceError;
 pub use locale_rs::Locale;
-pub use models::{Address, Buyer, Invoice, Product, Seller};
+pub use models::{
+    Address, AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind, BillingReference, Buyer,
+    Country, Currency, Decimal, DocumentType, ElectronicAddress, Invoice, ItemAttribute,
+    ItemClassification, ItemIdentifier, Money, Payment, Product, Seller, TaxCategory, Unit,
+};
@@ -1,6 +1,7 @@
 use ::invoice::Locale;
+use ::invoice::einvoice::EInvoiceProfile;
 use ::invoice::models::*;
-use ::invoice::pdf::generate_invoice_pdf;
+use ::invoice::pdf::{FontSet, HybridInvoice, Logo, PdfOptions, generate_invoice_pdf_with_fonts};
 use locale_rs::datetime_formats::DateTime;
 use std::fs::File;
 use std::io::Write;
@@ -8,25 +9,37 @@ use std::io::Write;
 fn main() {
     let seller_addr = Address {
         street: "Main Street".to_string(),
-        house_number: 123,
-        code: 90210,
+        house_number: "123".to_string(),
+        additional_lines: Vec::new(),
+        code: "90210".to_string(),
         town: "Anytown".to_string(),
+        subdivision: None,
+        country: Country::DE,
     };
 
     let buyer_addr = Address {
         street: "Oak Avenue".to_string(),
-        house_number: 456,
-        code: 10001,
+        house_number: "456".to_string(),
+        additional_lines: Vec::new(),
+        code: "10001".to_string(),
         town: "Cityville".to_string(),
+        subdivision: None,
+        country: Country::AT,
     };
 
     let invoice = Invoice {
         number: "INV-2025-EXAMPLE".to_string(),
+        document_type: DocumentType::CommercialInvoice,
+        billing_reference: None,
         date: DateTime { year: 2025, month: 7, day: 15, hour: 0, minute: 0, second: 0 },
         seller: Seller {
             name: "Example Corp".to_string(),
             address: seller_addr,
             vat_id: "VAT-EX-00000000".to_string(),
+            tax_registration_number: Some("12/345/67890".to_string()),
+            legal_registration_id: Some("HRB 12345".to_string()),
+            legal_form: Some("GmbH".to_string()),
+            electronic_address: None,
             website: "examplecorp.com".to_string(),
             phone: Some("+49 123 456789".to_string()),
             email: Some("contact@examplecorp.com".to_string()),
@@ -35,11 +48,17 @@ fn main() {
             name: "John Doe".to_string(),
             address: buyer_addr,
             email: "john.doe@example.com".to_string(),
+            vat_id: Some("ATU12345678".to_string()),
+            tax_registration_number: None,
+            legal_registration_id: None,
+            legal_form: None,
+            electronic_address: None,
         },
         payment_due: DateTime { year: 2025, month: 8, day: 15, hour: 0, minute: 0, second: 0 },
         delivery_date: None,
         // Some(DateTime { year: 2025, month: 7, day: 14, hour: 0, minute: 0, second: 0 }),
         delivery_type: None,
+        delivery_country: None,
         // Some("Standard Shipping".to_string()),
         extra_info: None,
         // Some(vec![
@@ -58,26 +77,67 @@ fn main() {
         products: vec![
             Product {
                 description: "Rusty Widget with very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very long description".to_string(),
-                units: 10,
-                cost_per_unit: 9.99,
-                tax_rate: 0.19,
+                quantity: Decimal::from(10),
+                unit: Unit::PIECE,
+                cost_per_unit: Decimal::new(999, 2),
+                price_base_quantity: None,
+                tax_rate: Decimal::new(19, 2),
+                tax_category: TaxCategory::Standard,
                 tax_exempt_reason: None,
+                tax_exemption_code: None,
+                allowances_charges: Vec::new(),
+                note: None,
+                seller_item_id: None,
+                buyer_item_id: None,
+                standard_item_id: None,
+                classifications: Vec::new(),
+                origin_country: None,
+                attributes: Vec::new(),
             },
             Product {
                 description: "Gadget Pro".to_string(),
-                units: 5,
-                cost_per_unit: 19.95,
-                tax_rate: 0.07,
-                tax_exempt_reason: Some("WOOW".to_string()),
+                quantity: Decimal::from(5),
+                unit: Unit::PIECE,
+                cost_per_unit: Decimal::new(1995, 2),
+                price_base_quantity: None,
+                tax_rate: Decimal::new(7, 2),
+                tax_category: TaxCategory::Standard,
+                tax_exempt_reason: None,
+                tax_exemption_code: None,
+                allowances_charges: Vec::new(),
+                note: None,
+                seller_item_id: None,
+                buyer_item_id: None,
+                standard_item_id: None,
+                classifications: Vec::new(),
+                origin_country: None,
+                attributes: Vec::new(),
             },
             Product {
                 description: "Exported Item (Reverse Charge)".to_string(),
-                units: 2,
-                cost_per_unit: 100.0,
-                tax_rate: 0.0,
+                quantity: Decimal::from(2),
+                unit: Unit::PIECE,
+                cost_per_unit: Decimal::new(100, 0),
+                price_base_quantity: None,
+                tax_rate: Decimal::ZERO,
+                tax_category: TaxCategory::ReverseCharge,
                 tax_exempt_reason: Some("Intra-EU reverse charge".to_string()),
+                tax_exemption_code: Some("VATEX-EU-AE".to_string()),
+                allowances_charges: Vec::new(),
+                note: None,
+                seller_item_id: None,
+                buyer_item_id: None,
+                standard_item_id: None,
+                classifications: Vec::new(),
+                origin_country: None,
+                attributes: Vec::new(),
             },
         ],
+        allowances_charges: Vec::new(),
+        prepaid_amount: None,
+        payments: Vec::new(),
+        rounding_amount: None,
+        currency: Currency::EUR,
         locale: Locale::de,
     };
 
@@ -90,12 +150,16 @@ fn main() {
 
     println!("Invoice XML saved to 'invoice.xml'");
 
-    let pdf_bytes = generate_invoice_pdf(
-        &invoice,
-        "./fonts/OpenSans-Medium.ttf",
-        Some("./res/logo.jpg"),
-    )
-    .expect("Failed to create PDF");
+    let hybrid = HybridInvoice::from_invoice(&invoice, EInvoiceProfile::En16931)
+        .expect("Failed to generate CII XML");
+    let options = PdfOptions {
+        logo: Some(Logo::from_path("./res/logo.jpg")),
+        hybrid: Some(hybrid),
+        ..Default::default()
+    };
+    let pdf_bytes =
+        generate_invoice_pdf_with_fonts(&invoice, &FontSet::open_sans("./fonts"), &options)
+            .expect("Failed to create PDF");
 
     let mut file = File::create("./invoice.pdf").expect("Unable to create output file");
     file.write_all(&pdf_bytes).expect("Failed to write PDF");
@@ -1,18 +1,88 @@
+use super::Country;
 use std::fmt;
 
 pub struct Address {
     pub street: String,
-    pub house_number: u16,
-    pub code: u32,
+    /// Free text, e.g. `12a` or `4-6`. May be empty if it is part of `street`.
+    pub house_number: String,
+    /// Further lines such as a building, floor or c/o (BT-36, BT-162 / BT-51, BT-163).
+    pub additional_lines: Vec<String>,
+    /// Postal code as written in the country, e.g. `01067`, `SW1A 1AA` or `1012 AB`.
+    pub code: String,
     pub town: String,
+    /// State, province or region (BT-39 / BT-54), e.g. `CA` or `Ontario`.
+    pub subdivision: Option<String>,
+    pub country: Country,
+}
+
+/// How a country orders the parts of an address.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// `Hauptstraße 12` / `10115 Berlin`
+    StreetFirst,
+    /// `12 rue de la Paix` / `75002 Paris`
+    NumberFirst,
+    /// `10 Downing Street` / `London` / `SW1A 2AA`
+    PostcodeLast,
+    /// `1600 Pennsylvania Ave` / `Washington, DC 20500`
+    NorthAmerican,
+}
+
+impl Layout {
+    fn of(country: Country) -> Layout {
+        match country.code() {
+            "FR" | "LU" | "MC" => Layout::NumberFirst,
+            "GB" | "IE" => Layout::PostcodeLast,
+            "US" | "CA" | "AU" | "NZ" => Layout::NorthAmerican,
+            _ => Layout::StreetFirst,
+        }
+    }
+}
+
+impl Address {
+    /// Street and house number in the order of the address's country.
+    pub fn street_line(&self) -> String {
+        let (first, second) = match Layout::of(self.country) {
+            Layout::StreetFirst => (&self.street, &self.house_number),
+            _ => (&self.house_number, &self.street),
+        };
+        join(first, " ", second)
+    }
+
+    /// Lines of a postal address block without the country.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![self.street_line()];
+        lines.extend(self.additional_lines.iter().cloned());
+        let subdivision = self.subdivision.as_deref().unwrap_or_default();
+        match Layout::of(self.country) {
+            Layout::StreetFirst | Layout::NumberFirst => {
+                lines.push(join(&join(&self.code, " ", &self.town), " ", subdivision))
+            }
+            Layout::PostcodeLast => {
+                lines.push(self.town.clone());
+                lines.push(subdivision.to_string());
+                lines.push(self.code.clone());
+            }
+            Layout::NorthAmerican => {
+                lines.push(join(&self.town, ", ", &join(subdivision, " ", &self.code)));
+            }
+        }
+        lines.retain(|line| !line.is_empty());
+        lines
+    }
+}
+
+/// Joins two parts, leaving out the separator if either is empty.
+fn join(first: &str, separator: &str, second: &str) -> String {
+    match (first.trim().is_empty(), second.trim().is_empty()) {
+        (true, _) => second.trim().to_string(),
+        (_, true) => first.trim().to_string(),
+        _ => format!("{}{}{}", first.trim(), separator, second.trim()),
+    }
 }
 
 impl fmt::Display for Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{} {}, {} {}",
-            self.street, self.house_number, self.code, self.town
-        )
+        write!(f, "{}, {}", self.lines().join(", "), self.country.name())
     }
 }
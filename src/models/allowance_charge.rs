@@ -0,0 +1,79 @@
+use super::money::AMOUNT_OVERFLOW;
+use super::{Decimal, Money, TaxCategory};
+
+/// Whether an [`AllowanceCharge`] lowers or raises the amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AllowanceChargeKind {
+    /// A discount (BG-20 / BG-27).
+    #[default]
+    Allowance,
+    /// A surcharge such as shipping or packaging (BG-21 / BG-28).
+    Charge,
+}
+
+/// How the amount of an [`AllowanceCharge`] is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllowanceChargeAmount {
+    Fixed(Decimal),
+    /// Fraction of the base amount, e.g. `0.1` for 10%.
+    Percentage(Decimal),
+}
+
+/// A discount or surcharge on the whole invoice or on a single line.
+///
+/// Percentages of a line apply to its quantity times unit price; those of the invoice
+/// apply to the net amount of all lines with the same VAT category and rate.
+pub struct AllowanceCharge {
+    pub kind: AllowanceChargeKind,
+    pub amount: AllowanceChargeAmount,
+    /// Free text, e.g. `Early customer discount` or `Shipping`.
+    pub reason: Option<String>,
+    /// UNCL5189 code for allowances (e.g. `95` discount) or UNCL7161 code for charges
+    /// (e.g. `FC` freight).
+    pub reason_code: Option<String>,
+    /// VAT of an invoice level entry. Line level entries are taxed like their line and
+    /// ignore these.
+    pub tax_category: TaxCategory,
+    pub tax_rate: Decimal,
+}
+
+impl AllowanceCharge {
+    pub fn is_charge(&self) -> bool {
+        self.kind == AllowanceChargeKind::Charge
+    }
+
+    pub fn percentage(&self) -> Option<Decimal> {
+        match self.amount {
+            AllowanceChargeAmount::Percentage(rate) => Some(rate),
+            AllowanceChargeAmount::Fixed(_) => None,
+        }
+    }
+
+    /// The amount for the given base, rounded to the currency's minor units.
+    pub fn amount_on(&self, base: Money) -> Money {
+        self.checked_amount_on(base).expect(AMOUNT_OVERFLOW)
+    }
+
+    /// Like [`AllowanceCharge::amount_on`], `None` if the amount does not fit.
+    pub fn checked_amount_on(&self, base: Money) -> Option<Money> {
+        match self.amount {
+            AllowanceChargeAmount::Fixed(amount) => Some(Money::new(amount, base.currency).round()),
+            AllowanceChargeAmount::Percentage(rate) => Some(base.checked_scale_by(rate)?.round()),
+        }
+    }
+
+    /// Like [`AllowanceCharge::amount_on`], negative for allowances.
+    pub fn signed_amount_on(&self, base: Money) -> Money {
+        let amount = self.amount_on(base);
+        if self.is_charge() { amount } else { -amount }
+    }
+
+    /// The rate actually applied, which is zero unless the entry is standard rated.
+    pub fn effective_tax_rate(&self) -> Decimal {
+        if self.tax_category.is_taxed() {
+            self.tax_rate
+        } else {
+            Decimal::ZERO
+        }
+    }
+}
@@ -1,7 +1,26 @@
-use super::Address;
+use super::{Address, ElectronicAddress};
 
 pub struct Buyer {
     pub name: String,
     pub address: Address,
     pub email: String,
+    /// VAT identifier (BT-48), required for reverse charge and intra-community supplies.
+    pub vat_id: Option<String>,
+    /// Local tax number. EN 16931 has no field for it, so it is only printed on the PDF.
+    pub tax_registration_number: Option<String>,
+    /// Trade register entry (BT-47).
+    pub legal_registration_id: Option<String>,
+    /// Legal form. Like the tax number only printed on the PDF.
+    pub legal_form: Option<String>,
+    /// Routing address (BT-49); the email is used if not set.
+    pub electronic_address: Option<ElectronicAddress>,
+}
+
+impl Buyer {
+    /// The electronic address written to the XML.
+    pub fn endpoint(&self) -> Option<ElectronicAddress> {
+        self.electronic_address.clone().or_else(|| {
+            (!self.email.trim().is_empty()).then(|| ElectronicAddress::email(&self.email))
+        })
+    }
 }
@@ -0,0 +1,95 @@
+use std::fmt;
+
+/// ISO 3166-1 alpha-2 country code, e.g. `DE` or `GB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Country([u8; 2]);
+
+/// English names of the countries invoices are most often exchanged with.
+const NAMES: &[(&str, &str)] = &[
+    ("AT", "Austria"),
+    ("AU", "Australia"),
+    ("BE", "Belgium"),
+    ("BG", "Bulgaria"),
+    ("CA", "Canada"),
+    ("CH", "Switzerland"),
+    ("CN", "China"),
+    ("CY", "Cyprus"),
+    ("CZ", "Czechia"),
+    ("DE", "Germany"),
+    ("DK", "Denmark"),
+    ("EE", "Estonia"),
+    ("ES", "Spain"),
+    ("FI", "Finland"),
+    ("FR", "France"),
+    ("GB", "United Kingdom"),
+    ("GR", "Greece"),
+    ("HR", "Croatia"),
+    ("HU", "Hungary"),
+    ("IE", "Ireland"),
+    ("IS", "Iceland"),
+    ("IT", "Italy"),
+    ("JP", "Japan"),
+    ("LI", "Liechtenstein"),
+    ("LT", "Lithuania"),
+    ("LU", "Luxembourg"),
+    ("LV", "Latvia"),
+    ("MT", "Malta"),
+    ("NL", "Netherlands"),
+    ("NO", "Norway"),
+    ("NZ", "New Zealand"),
+    ("PL", "Poland"),
+    ("PT", "Portugal"),
+    ("RO", "Romania"),
+    ("SE", "Sweden"),
+    ("SI", "Slovenia"),
+    ("SK", "Slovakia"),
+    ("US", "United States"),
+];
+
+impl Country {
+    pub const AT: Country = Country(*b"AT");
+    pub const CH: Country = Country(*b"CH");
+    pub const DE: Country = Country(*b"DE");
+    pub const ES: Country = Country(*b"ES");
+    pub const FR: Country = Country(*b"FR");
+    pub const GB: Country = Country(*b"GB");
+    pub const IT: Country = Country(*b"IT");
+    pub const NL: Country = Country(*b"NL");
+    pub const US: Country = Country(*b"US");
+
+    /// Accepts any two ASCII letters, case-insensitively.
+    pub fn from_code(code: &str) -> Option<Country> {
+        match code.trim().as_bytes() {
+            [a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+                Some(Country([a.to_ascii_uppercase(), b.to_ascii_uppercase()]))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        // Only ASCII letters are ever stored.
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    /// English name, or the code for countries not in the built-in list.
+    pub fn name(&self) -> &str {
+        NAMES
+            .iter()
+            .find(|(code, _)| *code == self.code())
+            .map_or(self.code(), |(_, name)| name)
+    }
+}
+
+/// Addresses are German unless stated otherwise.
+impl Default for Country {
+    fn default() -> Self {
+        Country::DE
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
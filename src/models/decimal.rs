@@ -0,0 +1,303 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Exact base-10 number stored as `mantissa * 10^-scale`.
+///
+/// The operators panic when the result does not fit, like the integer types; the
+/// `checked_*` methods return `None` instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError(String);
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal literal: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal::new(0, 0);
+    pub const ONE: Decimal = Decimal::new(1, 0);
+    pub const ONE_HUNDRED: Decimal = Decimal::new(100, 0);
+
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Decimal { mantissa, scale }
+    }
+
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_sign_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn abs(&self) -> Self {
+        Decimal::new(self.mantissa.abs(), self.scale)
+    }
+
+    /// Removes trailing fractional zeros, e.g. `19.00` becomes `19`.
+    pub fn normalize(&self) -> Self {
+        let mut mantissa = self.mantissa;
+        let mut scale = self.scale;
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Decimal::new(mantissa, scale)
+    }
+
+    /// Re-expresses the value with a larger scale without changing it.
+    fn rescale(&self, scale: u32) -> Option<i128> {
+        debug_assert!(scale >= self.scale);
+        10i128
+            .checked_pow(scale - self.scale)
+            .and_then(|factor| self.mantissa.checked_mul(factor))
+    }
+
+    pub fn checked_add(self, rhs: Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(rhs.scale);
+        let mantissa = self.rescale(scale)?.checked_add(rhs.rescale(scale)?)?;
+        Some(Decimal::new(mantissa, scale))
+    }
+
+    pub fn checked_sub(self, rhs: Decimal) -> Option<Decimal> {
+        self.checked_add(Decimal::new(rhs.mantissa.checked_neg()?, rhs.scale))
+    }
+
+    pub fn checked_mul(self, rhs: Decimal) -> Option<Decimal> {
+        let (lhs, rhs) = (self.normalize(), rhs.normalize());
+        let mantissa = lhs.mantissa.checked_mul(rhs.mantissa)?;
+        Some(Decimal::new(mantissa, lhs.scale.checked_add(rhs.scale)?).normalize())
+    }
+
+    /// Rounds to `dp` decimal places, half away from zero (commercial rounding).
+    /// Values too large to carry `dp` places keep their exact, shorter form.
+    pub fn round_dp(&self, dp: u32) -> Self {
+        if dp >= self.scale {
+            return self
+                .rescale(dp)
+                .map_or(*self, |mantissa| Decimal::new(mantissa, dp));
+        }
+        let Some(divisor) = 10i128.checked_pow(self.scale - dp) else {
+            return Decimal::new(0, dp);
+        };
+        let quotient = self.mantissa / divisor;
+        let remainder = (self.mantissa % divisor).abs();
+        let rounded = if remainder >= divisor - remainder {
+            quotient + self.mantissa.signum()
+        } else {
+            quotient
+        };
+        Decimal::new(rounded, dp)
+    }
+
+    /// Divides by `rhs`, rounding the quotient to `dp` decimal places; `None` for a zero
+    /// divisor or a quotient that does not fit.
+    pub fn div_round(&self, rhs: Decimal, dp: u32) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        // Carry one extra digit so the final rounding step sees the remainder.
+        let extra = dp.checked_add(1)?.checked_add(rhs.scale)?;
+        let numerator = if extra >= self.scale {
+            self.rescale(extra)?
+        } else {
+            10i128
+                .checked_pow(self.scale - extra)
+                .map_or(0, |divisor| self.mantissa / divisor)
+        };
+        let quotient = Decimal::new(numerator / rhs.mantissa, dp + 1);
+        Some(quotient.round_dp(dp))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        match (self.rescale(scale), other.rescale(scale)) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(&rhs),
+            // A side that does not fit at the common scale is the larger in magnitude.
+            (None, _) => self.mantissa.cmp(&0),
+            (_, None) => 0.cmp(&other.mantissa),
+        }
+    }
+}
+
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalize();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl Add for Decimal {
+    type Output = Decimal;
+
+    fn add(self, rhs: Decimal) -> Decimal {
+        self.checked_add(rhs).expect("decimal addition overflowed")
+    }
+}
+
+impl AddAssign for Decimal {
+    fn add_assign(&mut self, rhs: Decimal) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Decimal {
+    type Output = Decimal;
+
+    fn sub(self, rhs: Decimal) -> Decimal {
+        self.checked_sub(rhs)
+            .expect("decimal subtraction overflowed")
+    }
+}
+
+impl SubAssign for Decimal {
+    fn sub_assign(&mut self, rhs: Decimal) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Decimal) -> Decimal {
+        self.checked_mul(rhs)
+            .expect("decimal multiplication overflowed")
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::new(-self.mantissa, self.scale)
+    }
+}
+
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item = Decimal>>(iter: I) -> Decimal {
+        iter.fold(Decimal::ZERO, Add::add)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+impl From<i32> for Decimal {
+    fn from(value: i32) -> Self {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+impl From<u32> for Decimal {
+    fn from(value: u32) -> Self {
+        Decimal::new(value as i128, 0)
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDecimalError(s.to_string());
+        let trimmed = s.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(err());
+        }
+        if !int_part
+            .chars()
+            .chain(frac_part.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(err());
+        }
+        let mut mantissa: i128 = 0;
+        for c in int_part.chars().chain(frac_part.chars()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(c.to_digit(10).unwrap() as i128))
+                .ok_or_else(err)?;
+        }
+        if negative {
+            mantissa = -mantissa;
+        }
+        Ok(Decimal::new(mantissa, frac_part.len() as u32))
+    }
+}
+
+/// Prints the exact value; a precision (`{:.2}`) rounds half away from zero first.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match f.precision() {
+            Some(dp) => self.round_dp(dp as u32),
+            None => *self,
+        };
+        let digits = value.mantissa.unsigned_abs().to_string();
+        let scale = value.scale as usize;
+        let mut body = if scale == 0 {
+            digits
+        } else if digits.len() > scale {
+            let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+            format!("{}.{}", int_part, frac_part)
+        } else {
+            format!("0.{}{}", "0".repeat(scale - digits.len()), digits)
+        };
+        // Values too large to be rounded up to the precision are padded instead.
+        if let Some(dp) = f.precision().filter(|dp| *dp > scale) {
+            if scale == 0 {
+                body.push('.');
+            }
+            body.push_str(&"0".repeat(dp - scale));
+        }
+        f.pad_integral(value.mantissa >= 0, "", &body)
+    }
+}
@@ -0,0 +1,71 @@
+use locale_rs::datetime_formats::DateTime;
+use std::fmt;
+
+/// Kind of document (BT-3, UNTDID 1001).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DocumentType {
+    /// `380`
+    #[default]
+    CommercialInvoice,
+    /// `381`: refunds or reduces a previous invoice. Written with the UBL `CreditNote` root.
+    CreditNote,
+    /// `384`: replaces a previous invoice.
+    CorrectedInvoice,
+    /// `386`: requests payment before delivery.
+    PrepaymentInvoice,
+    /// `389`: issued by the buyer on behalf of the seller.
+    SelfBilledInvoice,
+}
+
+impl DocumentType {
+    pub const ALL: [DocumentType; 5] = [
+        DocumentType::CommercialInvoice,
+        DocumentType::CreditNote,
+        DocumentType::CorrectedInvoice,
+        DocumentType::PrepaymentInvoice,
+        DocumentType::SelfBilledInvoice,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            DocumentType::CommercialInvoice => "380",
+            DocumentType::CreditNote => "381",
+            DocumentType::CorrectedInvoice => "384",
+            DocumentType::PrepaymentInvoice => "386",
+            DocumentType::SelfBilledInvoice => "389",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<DocumentType> {
+        DocumentType::ALL
+            .into_iter()
+            .find(|t| t.code() == code.trim())
+    }
+
+    /// Whether UBL needs the `CreditNote` instead of the `Invoice` root.
+    pub fn is_credit_note(&self) -> bool {
+        *self == DocumentType::CreditNote
+    }
+
+    /// Credit notes and corrections must name the invoice they refer to (BG-3).
+    pub fn requires_billing_reference(&self) -> bool {
+        matches!(
+            self,
+            DocumentType::CreditNote | DocumentType::CorrectedInvoice
+        )
+    }
+}
+
+impl fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// The earlier invoice a credit note or correction refers to (BG-3).
+pub struct BillingReference {
+    /// Number of the preceding invoice (BT-25).
+    pub number: String,
+    /// Issue date of the preceding invoice (BT-26).
+    pub date: Option<DateTime>,
+}
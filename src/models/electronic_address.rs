@@ -0,0 +1,22 @@
+/// Electronic address of a party (BT-34 / BT-49) used to route e-invoices, e.g. a Peppol
+/// participant ID or a Leitweg-ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElectronicAddress {
+    /// Electronic Address Scheme (EAS) code, e.g. `0088` for GLN, `9930` for German VAT IDs
+    /// or `EM` for email.
+    pub scheme: String,
+    pub value: String,
+}
+
+impl ElectronicAddress {
+    pub fn new(scheme: impl Into<String>, value: impl Into<String>) -> Self {
+        ElectronicAddress {
+            scheme: scheme.into(),
+            value: value.into(),
+        }
+    }
+
+    pub fn email(address: impl Into<String>) -> Self {
+        ElectronicAddress::new("EM", address)
+    }
+}
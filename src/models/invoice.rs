@@ -1,38 +1,225 @@
-use super::{Buyer, Product, Seller};
+use super::money::AMOUNT_OVERFLOW;
+use super::{
+    AllowanceCharge, BillingReference, Buyer, Country, Currency, Decimal, DocumentType, Money,
+    Payment, Product, Seller, TaxCategory,
+};
+use crate::error::InvoiceError;
 use locale_rs::Locale;
 use locale_rs::datetime_formats::DateTime;
-use ordered_float::OrderedFloat;
 use std::collections::BTreeMap;
 
 pub struct Invoice {
     pub number: String,
+    pub document_type: DocumentType,
+    /// Required for credit notes and corrected invoices.
+    pub billing_reference: Option<BillingReference>,
     pub date: DateTime,
     pub seller: Seller,
     pub buyer: Buyer,
     pub payment_due: DateTime,
     pub delivery_date: Option<DateTime>,
     pub delivery_type: Option<String>,
+    /// Where the goods go (BT-80); intra-community supplies need it.
+    pub delivery_country: Option<Country>,
     pub extra_info: Option<Vec<(String, String)>>,
     pub payment_type: Option<String>,
     pub payment_info: Option<Vec<(String, String)>>,
     pub products: Vec<Product>,
+    /// Discounts and surcharges on the whole invoice, e.g. shipping.
+    pub allowances_charges: Vec<AllowanceCharge>,
+    /// Paid in advance, e.g. a deposit invoiced earlier (BT-113 together with `payments`).
+    pub prepaid_amount: Option<Decimal>,
+    /// Payments received before this invoice was issued.
+    pub payments: Vec<Payment>,
+    /// Added to the amount due to round it, e.g. to 0.05 for cash (BT-114).
+    pub rounding_amount: Option<Decimal>,
+    pub currency: Currency,
     pub locale: Locale,
 }
 
+/// Taxable base and tax for one VAT category/rate combination.
+pub struct TaxSubtotal {
+    pub category: TaxCategory,
+    pub rate: Decimal,
+    pub taxable_amount: Money,
+    pub tax_amount: Money,
+    /// Reasons of the lines, or the category's default; empty for `S` and `Z`.
+    pub exemption_reasons: Vec<String>,
+    /// First VATEX code of the lines, or the category's default.
+    pub exemption_code: Option<String>,
+}
+
+impl TaxSubtotal {
+    fn empty(category: TaxCategory, rate: Decimal, currency: Currency) -> Self {
+        TaxSubtotal {
+            category,
+            rate,
+            taxable_amount: Money::zero(currency),
+            tax_amount: Money::zero(currency),
+            exemption_reasons: Vec::new(),
+            exemption_code: None,
+        }
+    }
+}
+
+/// Document totals shared by the PDF renderer and the e-invoice writers.
+///
+/// Line amounts are rounded to minor units first and each subtotal's tax is computed
+/// from its rounded taxable amount (EN 16931 BR-CO-17), so every output agrees to the cent.
+pub struct InvoiceSummary {
+    /// Sum of the line net amounts.
+    pub subtotal: Money,
+    /// Sum of the invoice level allowances.
+    pub allowance_total: Money,
+    /// Sum of the invoice level charges.
+    pub charge_total: Money,
+    /// Subtotal less allowances plus charges; the base of the VAT.
+    pub net_total: Money,
+    pub tax_subtotals: Vec<TaxSubtotal>,
+    pub tax_total: Money,
+    pub total: Money,
+    /// `prepaid_amount` plus all `payments`.
+    pub prepaid: Money,
+    pub rounding: Money,
+    /// Total less prepaid plus rounding.
+    pub amount_due: Money,
+}
+
 impl Invoice {
-    pub fn calculate_summary(&self) -> (f64, BTreeMap<OrderedFloat<f64>, f64>, f64) {
-        let mut subtotal = 0.0;
-        let mut tax_totals = BTreeMap::new();
+    /// Net amount of the lines taxed like `entry`, which its percentage applies to.
+    pub fn allowance_charge_base(&self, entry: &AllowanceCharge) -> Money {
+        self.checked_allowance_charge_base(entry)
+            .expect(AMOUNT_OVERFLOW)
+    }
+
+    /// Like [`Invoice::allowance_charge_base`], `None` if the amount does not fit.
+    pub fn checked_allowance_charge_base(&self, entry: &AllowanceCharge) -> Option<Money> {
+        self.products
+            .iter()
+            .filter(|p| {
+                p.tax_category == entry.tax_category
+                    && p.effective_tax_rate() == entry.effective_tax_rate()
+            })
+            .try_fold(Money::zero(self.currency), |acc, p| {
+                acc.checked_add(p.checked_net_amount(self.currency)?)
+            })
+    }
+
+    /// # Panics
+    ///
+    /// If an amount does not fit into a [`Decimal`]; use
+    /// [`Invoice::try_calculate_summary`] for invoices from untrusted sources.
+    pub fn calculate_summary(&self) -> InvoiceSummary {
+        self.try_calculate_summary().expect(AMOUNT_OVERFLOW)
+    }
+
+    /// Computes the totals, failing with [`InvoiceError::InvalidInvoice`] if an amount
+    /// or percentage does not fit into a [`Decimal`]. Once it succeeds, the line and
+    /// allowance amounts of the invoice can be computed without overflow.
+    pub fn try_calculate_summary(&self) -> Result<InvoiceSummary, InvoiceError> {
+        self.checked_summary().ok_or_else(|| {
+            InvoiceError::InvalidInvoice("amounts exceed the supported range".to_string())
+        })
+    }
+
+    fn checked_summary(&self) -> Option<InvoiceSummary> {
+        let currency = self.currency;
+        // Rates are written and printed as percentages.
+        let line_rates = self.products.iter().flat_map(|p| {
+            let percentages = p.allowances_charges.iter().filter_map(|e| e.percentage());
+            std::iter::once(p.tax_rate).chain(percentages)
+        });
+        let entry_rates = self
+            .allowances_charges
+            .iter()
+            .flat_map(|e| std::iter::once(e.tax_rate).chain(e.percentage()));
+        for rate in line_rates.chain(entry_rates) {
+            rate.checked_mul(Decimal::ONE_HUNDRED)?;
+        }
+        let mut subtotal = Money::zero(currency);
+        let mut groups: BTreeMap<(TaxCategory, Decimal), TaxSubtotal> = BTreeMap::new();
         for product in &self.products {
-            let line_total = product.units as f64 * product.cost_per_unit;
-            subtotal += line_total;
-            if product.tax_rate > 0.0 {
-                *tax_totals
-                    .entry(OrderedFloat(product.tax_rate))
-                    .or_insert(0.0) += line_total * product.tax_rate;
+            let line_total = product.checked_net_amount(currency)?;
+            subtotal = subtotal.checked_add(line_total)?;
+            let category = product.tax_category;
+            let rate = product.effective_tax_rate();
+            let entry = groups
+                .entry((category, rate))
+                .or_insert_with(|| TaxSubtotal::empty(category, rate, currency));
+            entry.taxable_amount = entry.taxable_amount.checked_add(line_total)?;
+            if !category.requires_exemption_reason() {
+                continue;
+            }
+            if let Some(reason) = &product.tax_exempt_reason
+                && !entry.exemption_reasons.contains(reason)
+            {
+                entry.exemption_reasons.push(reason.clone());
+            }
+            if entry.exemption_code.is_none() {
+                entry.exemption_code = product.tax_exemption_code.clone();
+            }
+        }
+
+        let mut allowance_total = Money::zero(currency);
+        let mut charge_total = Money::zero(currency);
+        for entry in &self.allowances_charges {
+            let base = self.checked_allowance_charge_base(entry)?;
+            let amount = entry.checked_amount_on(base)?;
+            let signed = if entry.is_charge() {
+                charge_total = charge_total.checked_add(amount)?;
+                amount
+            } else {
+                allowance_total = allowance_total.checked_add(amount)?;
+                -amount
+            };
+            let (category, rate) = (entry.tax_category, entry.effective_tax_rate());
+            let group = groups
+                .entry((category, rate))
+                .or_insert_with(|| TaxSubtotal::empty(category, rate, currency));
+            group.taxable_amount = group.taxable_amount.checked_add(signed)?;
+        }
+
+        let mut tax_subtotals = Vec::new();
+        let mut tax_total = Money::zero(currency);
+        for mut group in groups.into_values() {
+            group.tax_amount = group.taxable_amount.checked_scale_by(group.rate)?.round();
+            tax_total = tax_total.checked_add(group.tax_amount)?;
+            if group.exemption_reasons.is_empty()
+                && let Some(reason) = group.category.default_exemption_reason()
+            {
+                group.exemption_reasons.push(reason.to_string());
+            }
+            if group.exemption_code.is_none() {
+                group.exemption_code = group.category.default_exemption_code().map(str::to_string);
             }
+            tax_subtotals.push(group);
         }
-        let total = subtotal + tax_totals.values().sum::<f64>();
-        (subtotal, tax_totals, total)
+        let net_total = subtotal
+            .checked_sub(allowance_total)?
+            .checked_add(charge_total)?;
+        let total = net_total.checked_add(tax_total)?;
+        let prepaid = self
+            .payments
+            .iter()
+            .map(|p| p.amount)
+            .chain(self.prepaid_amount)
+            .try_fold(Money::zero(currency), |acc, amount| {
+                acc.checked_add(Money::new(amount, currency))
+            })?
+            .round();
+        let rounding = Money::new(self.rounding_amount.unwrap_or(Decimal::ZERO), currency).round();
+        let amount_due = total.checked_sub(prepaid)?.checked_add(rounding)?;
+        Some(InvoiceSummary {
+            subtotal,
+            allowance_total,
+            charge_total,
+            net_total,
+            tax_subtotals,
+            tax_total,
+            total,
+            prepaid,
+            rounding,
+            amount_due,
+        })
     }
 }
@@ -0,0 +1,59 @@
+/// Registered identifier of an item (BT-157), e.g. a GTIN.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemIdentifier {
+    /// ISO 6523 ICD scheme, e.g. `0160` for GTIN.
+    pub scheme: String,
+    pub id: String,
+}
+
+impl ItemIdentifier {
+    pub fn new(scheme: impl Into<String>, id: impl Into<String>) -> Self {
+        ItemIdentifier {
+            scheme: scheme.into(),
+            id: id.into(),
+        }
+    }
+
+    pub fn gtin(id: impl Into<String>) -> Self {
+        ItemIdentifier::new("0160", id)
+    }
+
+    pub fn is_gtin(&self) -> bool {
+        self.scheme == "0160"
+    }
+}
+
+/// Classification of an item in a public scheme (BT-158), e.g. a customs tariff number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemClassification {
+    /// UNTDID 7143 code of the scheme, e.g. `HS`, `CPV` or `STI`.
+    pub list_id: String,
+    pub list_version: Option<String>,
+    pub code: String,
+}
+
+impl ItemClassification {
+    pub fn new(list_id: impl Into<String>, code: impl Into<String>) -> Self {
+        ItemClassification {
+            list_id: list_id.into(),
+            list_version: None,
+            code: code.into(),
+        }
+    }
+}
+
+/// A property of an item such as colour or size (BG-32).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+impl ItemAttribute {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        ItemAttribute {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
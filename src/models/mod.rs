@@ -1,11 +1,31 @@
 pub mod address;
+pub mod allowance_charge;
 pub mod buyer;
+pub mod country;
+pub mod decimal;
+pub mod document_type;
+pub mod electronic_address;
 pub mod invoice;
+pub mod item;
+pub mod money;
+pub mod payment;
 pub mod product;
 pub mod seller;
+pub mod tax;
+pub mod unit;
 
 pub use address::Address;
+pub use allowance_charge::{AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind};
 pub use buyer::Buyer;
-pub use invoice::Invoice;
+pub use country::Country;
+pub use decimal::Decimal;
+pub use document_type::{BillingReference, DocumentType};
+pub use electronic_address::ElectronicAddress;
+pub use invoice::{Invoice, InvoiceSummary, TaxSubtotal};
+pub use item::{ItemAttribute, ItemClassification, ItemIdentifier};
+pub use money::{Currency, Money};
+pub use payment::Payment;
 pub use product::Product;
 pub use seller::Seller;
+pub use tax::TaxCategory;
+pub use unit::Unit;
@@ -0,0 +1,219 @@
+use super::Decimal;
+use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub};
+
+/// ISO 4217 currency together with the number of minor-unit digits it is settled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: &'static str,
+    minor_units: u32,
+    symbol: &'static str,
+}
+
+/// `(code, minor units, symbol)` for the ISO 4217 currencies we know about.
+const CURRENCIES: &[(&str, u32, &str)] = &[
+    ("AED", 2, "AED"),
+    ("AUD", 2, "A$"),
+    ("BGN", 2, "лв."),
+    ("BHD", 3, "BHD"),
+    ("BRL", 2, "R$"),
+    ("CAD", 2, "CA$"),
+    ("CHF", 2, "CHF"),
+    ("CLP", 0, "CLP"),
+    ("CNY", 2, "CN¥"),
+    ("CZK", 2, "Kč"),
+    ("DKK", 2, "kr."),
+    ("EUR", 2, "€"),
+    ("GBP", 2, "£"),
+    ("HKD", 2, "HK$"),
+    ("HUF", 2, "Ft"),
+    ("IDR", 2, "Rp"),
+    ("ILS", 2, "₪"),
+    ("INR", 2, "₹"),
+    ("IQD", 3, "IQD"),
+    ("ISK", 0, "kr"),
+    ("JOD", 3, "JOD"),
+    ("JPY", 0, "¥"),
+    ("KRW", 0, "₩"),
+    ("KWD", 3, "KWD"),
+    ("LYD", 3, "LYD"),
+    ("MXN", 2, "MX$"),
+    ("NOK", 2, "kr"),
+    ("NZD", 2, "NZ$"),
+    ("OMR", 3, "OMR"),
+    ("PLN", 2, "zł"),
+    ("RON", 2, "lei"),
+    ("RSD", 2, "RSD"),
+    ("SAR", 2, "SAR"),
+    ("SEK", 2, "kr"),
+    ("SGD", 2, "SGD"),
+    ("THB", 2, "฿"),
+    ("TND", 3, "TND"),
+    ("TRY", 2, "₺"),
+    ("TWD", 2, "NT$"),
+    ("UAH", 2, "₴"),
+    ("UGX", 0, "UGX"),
+    ("USD", 2, "$"),
+    ("VND", 0, "₫"),
+    ("XAF", 0, "FCFA"),
+    ("XOF", 0, "F CFA"),
+    ("ZAR", 2, "R"),
+];
+
+impl Currency {
+    pub const EUR: Currency = Currency::new("EUR", 2, "€");
+    pub const USD: Currency = Currency::new("USD", 2, "$");
+    pub const GBP: Currency = Currency::new("GBP", 2, "£");
+    pub const CHF: Currency = Currency::new("CHF", 2, "CHF");
+    pub const JPY: Currency = Currency::new("JPY", 0, "¥");
+    pub const KWD: Currency = Currency::new("KWD", 3, "KWD");
+
+    /// Defines a currency that is not part of the built-in table.
+    pub const fn new(code: &'static str, minor_units: u32, symbol: &'static str) -> Self {
+        Currency {
+            code,
+            minor_units,
+            symbol,
+        }
+    }
+
+    /// Looks up a currency by its ISO 4217 alphabetic code (case-insensitive).
+    pub fn from_code(code: &str) -> Option<Self> {
+        CURRENCIES
+            .iter()
+            .find(|(c, _, _)| c.eq_ignore_ascii_case(code.trim()))
+            .map(|&(code, minor_units, symbol)| Currency::new(code, minor_units, symbol))
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn minor_units(&self) -> u32 {
+        self.minor_units
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        self.symbol
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::EUR
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code)
+    }
+}
+
+/// Panic message of the infallible amount calculations, which callers guard by checking
+/// [`Invoice::try_calculate_summary`](super::Invoice::try_calculate_summary) first.
+pub(crate) const AMOUNT_OVERFLOW: &str = "invoice amount out of range";
+
+/// An amount in a specific currency.
+///
+/// Arithmetic keeps full precision; call [`Money::round`] to settle the amount to the
+/// currency's minor units before it is printed or summed into a document total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Money::new(Decimal::ZERO, currency)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn round(&self) -> Self {
+        Money::new(
+            self.amount.round_dp(self.currency.minor_units),
+            self.currency,
+        )
+    }
+
+    /// Multiplies by a plain factor such as a quantity or tax rate.
+    pub fn scale_by(&self, factor: Decimal) -> Self {
+        Money::new(self.amount * factor, self.currency)
+    }
+
+    pub fn checked_scale_by(&self, factor: Decimal) -> Option<Self> {
+        Some(Money::new(self.amount.checked_mul(factor)?, self.currency))
+    }
+
+    pub fn checked_add(self, rhs: Money) -> Option<Money> {
+        self.assert_same_currency(&rhs);
+        Some(Money::new(
+            self.amount.checked_add(rhs.amount)?,
+            self.currency,
+        ))
+    }
+
+    pub fn checked_sub(self, rhs: Money) -> Option<Money> {
+        self.assert_same_currency(&rhs);
+        Some(Money::new(
+            self.amount.checked_sub(rhs.amount)?,
+            self.currency,
+        ))
+    }
+
+    fn assert_same_currency(&self, other: &Money) {
+        assert_eq!(
+            self.currency, other.currency,
+            "cannot combine amounts in {} and {}",
+            self.currency, other.currency
+        );
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        self.assert_same_currency(&rhs);
+        Money::new(self.amount + rhs.amount, self.currency)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        self.assert_same_currency(&rhs);
+        Money::new(self.amount - rhs.amount, self.currency)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::new(-self.amount, self.currency)
+    }
+}
+
+/// Prints the amount with exactly the currency's minor-unit digits, without a symbol.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dp = f.precision().unwrap_or(self.currency.minor_units as usize);
+        write!(f, "{:.*}", dp, self.amount)
+    }
+}
@@ -0,0 +1,11 @@
+use super::Decimal;
+use locale_rs::datetime_formats::DateTime;
+
+/// A payment already received for the invoice, e.g. an installment.
+pub struct Payment {
+    pub date: DateTime,
+    /// In the invoice currency.
+    pub amount: Decimal,
+    /// Bank reference or note shown next to the payment on the PDF.
+    pub reference: Option<String>,
+}
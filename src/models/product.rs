@@ -1,7 +1,80 @@
+use super::money::AMOUNT_OVERFLOW;
+use super::{
+    AllowanceCharge, Country, Currency, Decimal, ItemAttribute, ItemClassification, ItemIdentifier,
+    Money, TaxCategory, Unit,
+};
+
 pub struct Product {
     pub description: String,
-    pub units: u32,
-    pub cost_per_unit: f64,
-    pub tax_rate: f64,
+    /// May be fractional, e.g. `2.5` hours.
+    pub quantity: Decimal,
+    pub unit: Unit,
+    /// Price of `price_base_quantity` units, e.g. `100` for a price per 100 kg.
+    pub cost_per_unit: Decimal,
+    /// Number of units the price refers to (BT-149); one when unset.
+    pub price_base_quantity: Option<Decimal>,
+    pub tax_rate: Decimal,
+    pub tax_category: TaxCategory,
+    /// Why no VAT is charged (BT-120); only written for categories that need one.
     pub tax_exempt_reason: Option<String>,
+    /// VATEX exemption reason code (BT-121), e.g. `VATEX-EU-132-1I`.
+    pub tax_exemption_code: Option<String>,
+    /// Discounts and surcharges of this line, included in its net amount.
+    pub allowances_charges: Vec<AllowanceCharge>,
+    /// Free text about this line (BT-127).
+    pub note: Option<String>,
+    /// The seller's article number (BT-155).
+    pub seller_item_id: Option<String>,
+    /// The buyer's article number (BT-156).
+    pub buyer_item_id: Option<String>,
+    /// E.g. a GTIN (BT-157).
+    pub standard_item_id: Option<ItemIdentifier>,
+    pub classifications: Vec<ItemClassification>,
+    /// Where the item was produced (BT-159).
+    pub origin_country: Option<Country>,
+    pub attributes: Vec<ItemAttribute>,
+}
+
+impl Product {
+    /// The rate actually applied, which is zero unless the line is standard rated.
+    pub fn effective_tax_rate(&self) -> Decimal {
+        if self.tax_category.is_taxed() {
+            self.tax_rate
+        } else {
+            Decimal::ZERO
+        }
+    }
+
+    /// Quantity times price over the base quantity, rounded to the currency's minor units.
+    pub fn base_amount(&self, currency: Currency) -> Money {
+        self.checked_base_amount(currency).expect(AMOUNT_OVERFLOW)
+    }
+
+    /// Like [`Product::base_amount`], `None` if the amount does not fit.
+    pub fn checked_base_amount(&self, currency: Currency) -> Option<Money> {
+        let amount = self.cost_per_unit.checked_mul(self.quantity)?;
+        let amount = match self.price_base_quantity {
+            Some(base) if !base.is_zero() => amount.div_round(base, currency.minor_units())?,
+            _ => amount,
+        };
+        Some(Money::new(amount, currency).round())
+    }
+
+    /// Line net amount: the base amount less allowances plus charges of the line.
+    pub fn net_amount(&self, currency: Currency) -> Money {
+        self.checked_net_amount(currency).expect(AMOUNT_OVERFLOW)
+    }
+
+    /// Like [`Product::net_amount`], `None` if the amount does not fit.
+    pub fn checked_net_amount(&self, currency: Currency) -> Option<Money> {
+        let base = self.checked_base_amount(currency)?;
+        self.allowances_charges.iter().try_fold(base, |net, entry| {
+            let amount = entry.checked_amount_on(base)?;
+            if entry.is_charge() {
+                net.checked_add(amount)
+            } else {
+                net.checked_sub(amount)
+            }
+        })
+    }
 }
@@ -1,10 +1,30 @@
-use super::Address;
+use super::{Address, ElectronicAddress};
 
 pub struct Seller {
     pub name: String,
     pub address: Address,
     pub vat_id: String,
+    /// Local tax number (BT-32), e.g. the German Steuernummer.
+    pub tax_registration_number: Option<String>,
+    /// Trade register entry (BT-30), e.g. `HRB 12345`.
+    pub legal_registration_id: Option<String>,
+    /// Legal form and share capital (BT-33), e.g. `GmbH, Stammkapital 25.000 EUR`.
+    pub legal_form: Option<String>,
+    /// Routing address (BT-34); the contact email is used if not set.
+    pub electronic_address: Option<ElectronicAddress>,
     pub website: String,
     pub phone: Option<String>,
     pub email: Option<String>,
 }
+
+impl Seller {
+    /// The electronic address written to the XML.
+    pub fn endpoint(&self) -> Option<ElectronicAddress> {
+        self.electronic_address.clone().or_else(|| {
+            self.email
+                .as_deref()
+                .filter(|e| !e.trim().is_empty())
+                .map(ElectronicAddress::email)
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+/// VAT category of a line (UNCL5305 subset used by EN 16931).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum TaxCategory {
+    /// `S`: taxed at the product's `tax_rate`.
+    #[default]
+    Standard,
+    /// `Z`: taxable, but at a rate of zero.
+    ZeroRated,
+    /// `E`: exempt from VAT; needs an exemption reason.
+    Exempt,
+    /// `AE`: VAT reverse charge, the buyer accounts for the tax.
+    ReverseCharge,
+    /// `K`: VAT exempt intra-community supply of goods and services in the EEA.
+    IntraCommunity,
+    /// `G`: free export item, VAT not charged.
+    Export,
+    /// `O`: services outside the scope of VAT.
+    OutOfScope,
+}
+
+impl TaxCategory {
+    pub const ALL: [TaxCategory; 7] = [
+        TaxCategory::Standard,
+        TaxCategory::ZeroRated,
+        TaxCategory::Exempt,
+        TaxCategory::ReverseCharge,
+        TaxCategory::IntraCommunity,
+        TaxCategory::Export,
+        TaxCategory::OutOfScope,
+    ];
+
+    /// UNCL5305 code written to the XML.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TaxCategory::Standard => "S",
+            TaxCategory::ZeroRated => "Z",
+            TaxCategory::Exempt => "E",
+            TaxCategory::ReverseCharge => "AE",
+            TaxCategory::IntraCommunity => "K",
+            TaxCategory::Export => "G",
+            TaxCategory::OutOfScope => "O",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<TaxCategory> {
+        TaxCategory::ALL
+            .into_iter()
+            .find(|c| c.code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Only standard rated lines carry a non-zero rate; all other categories are taxed at 0%.
+    pub fn is_taxed(&self) -> bool {
+        *self == TaxCategory::Standard
+    }
+
+    /// Out of scope lines have no VAT rate at all (BR-O-5), not even 0%. The breakdown
+    /// still states 0% since XRechnung requires a rate there (BR-DE-14).
+    pub fn has_rate(&self) -> bool {
+        *self != TaxCategory::OutOfScope
+    }
+
+    /// Whether the VAT breakdown must state why no VAT is charged (BR-E-10, BR-AE-10, ...).
+    pub fn requires_exemption_reason(&self) -> bool {
+        !matches!(self, TaxCategory::Standard | TaxCategory::ZeroRated)
+    }
+
+    /// VATEX code used when a line gives none. Exemptions (`E`) have no generic code,
+    /// the applicable article must be chosen per product.
+    pub fn default_exemption_code(&self) -> Option<&'static str> {
+        match self {
+            TaxCategory::ReverseCharge => Some("VATEX-EU-AE"),
+            TaxCategory::IntraCommunity => Some("VATEX-EU-IC"),
+            TaxCategory::Export => Some("VATEX-EU-G"),
+            TaxCategory::OutOfScope => Some("VATEX-EU-O"),
+            _ => None,
+        }
+    }
+
+    /// Exemption reason text used when a line gives none.
+    pub fn default_exemption_reason(&self) -> Option<&'static str> {
+        match self {
+            TaxCategory::Exempt => Some("Exempt from VAT"),
+            TaxCategory::ReverseCharge => Some("Reverse charge"),
+            TaxCategory::IntraCommunity => Some("Intra-community supply"),
+            TaxCategory::Export => Some("Export outside the EU"),
+            TaxCategory::OutOfScope => Some("Not subject to VAT"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TaxCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// UN/ECE Recommendation 20 unit of measure (BT-130), e.g. `HUR` for hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unit([u8; 3]);
+
+impl Unit {
+    pub const PIECE: Unit = Unit(*b"H87");
+    /// A unit without a more specific meaning, e.g. a licence.
+    pub const ONE: Unit = Unit(*b"C62");
+    pub const HOUR: Unit = Unit(*b"HUR");
+    pub const MINUTE: Unit = Unit(*b"MIN");
+    pub const DAY: Unit = Unit(*b"DAY");
+    pub const WEEK: Unit = Unit(*b"WEE");
+    pub const MONTH: Unit = Unit(*b"MON");
+    pub const YEAR: Unit = Unit(*b"ANN");
+    pub const GRAM: Unit = Unit(*b"GRM");
+    pub const KILOGRAM: Unit = Unit(*b"KGM");
+    pub const TONNE: Unit = Unit(*b"TNE");
+    pub const METRE: Unit = Unit(*b"MTR");
+    pub const KILOMETRE: Unit = Unit(*b"KMT");
+    pub const SQUARE_METRE: Unit = Unit(*b"MTK");
+    pub const CUBIC_METRE: Unit = Unit(*b"MTQ");
+    pub const LITRE: Unit = Unit(*b"LTR");
+    pub const KILOWATT_HOUR: Unit = Unit(*b"KWH");
+    pub const LUMP_SUM: Unit = Unit(*b"LS ");
+    pub const SET: Unit = Unit(*b"SET");
+
+    /// Accepts any code of two or three ASCII letters or digits, case-insensitively.
+    pub fn from_code(code: &str) -> Option<Unit> {
+        let code = code.trim().as_bytes();
+        if !(2..=3).contains(&code.len()) || !code.iter().all(u8::is_ascii_alphanumeric) {
+            return None;
+        }
+        let mut bytes = [b' '; 3];
+        for (byte, c) in bytes.iter_mut().zip(code) {
+            *byte = c.to_ascii_uppercase();
+        }
+        Some(Unit(bytes))
+    }
+
+    pub fn code(&self) -> &str {
+        // Only ASCII is ever stored; two letter codes are padded with a space.
+        std::str::from_utf8(&self.0).unwrap_or_default().trim_end()
+    }
+}
+
+/// Lines are counted in pieces unless stated otherwise.
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::PIECE
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
@@ -0,0 +1,218 @@
+use crate::error::InvoiceError;
+use crate::pdf::fonts::LoadedFonts;
+use crate::pdf::logo::VectorShape;
+use crate::pdf::theme::{Color, LineStyle, TextStyle};
+use krilla::Document;
+use krilla::color::rgb;
+use krilla::geom::{Point, Size, Transform};
+use krilla::image::Image;
+use krilla::page::PageSettings;
+use krilla::paint::{Fill, Stroke};
+use krilla::text::TextDirection;
+use std::sync::Arc;
+
+enum Element {
+    Text {
+        x: f32,
+        y: f32,
+        style: TextStyle,
+        text: String,
+    },
+    Line {
+        x1: f32,
+        x2: f32,
+        y: f32,
+        style: LineStyle,
+    },
+    Image {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        image: Image,
+    },
+    Shapes {
+        x: f32,
+        y: f32,
+        scale: f32,
+        shapes: Arc<Vec<VectorShape>>,
+    },
+}
+
+/// Records drawing operations page by page so the layout code can break pages
+/// freely and the krilla document is only written once everything is placed.
+pub struct Canvas {
+    pages: Vec<Vec<Element>>,
+    width: f32,
+    height: f32,
+}
+
+impl Canvas {
+    pub fn new(width: f32, height: f32) -> Self {
+        Canvas {
+            pages: vec![Vec::new()],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, style: TextStyle, text: impl Into<String>) {
+        self.current().push(Element::Text {
+            x,
+            y,
+            style,
+            text: text.into(),
+        });
+    }
+
+    /// Adds text to an already laid out page, e.g. a page number footer.
+    pub fn text_on_page(&mut self, page: usize, x: f32, y: f32, style: TextStyle, text: String) {
+        self.pages[page].push(Element::Text { x, y, style, text });
+    }
+
+    pub fn line(&mut self, x1: f32, x2: f32, y: f32, style: LineStyle) {
+        self.current().push(Element::Line { x1, x2, y, style });
+    }
+
+    /// Draws a raster image with its top-left corner at `(x, y)`.
+    pub fn image(&mut self, x: f32, y: f32, width: f32, height: f32, image: Image) {
+        self.current().push(Element::Image {
+            x,
+            y,
+            width,
+            height,
+            image,
+        });
+    }
+
+    /// Draws vector shapes scaled by `scale` with their origin at `(x, y)`.
+    pub fn shapes(&mut self, x: f32, y: f32, scale: f32, shapes: Arc<Vec<VectorShape>>) {
+        self.current().push(Element::Shapes {
+            x,
+            y,
+            scale,
+            shapes,
+        });
+    }
+
+    fn current(&mut self) -> &mut Vec<Element> {
+        self.pages.last_mut().expect("canvas always has a page")
+    }
+
+    pub fn render(self, doc: &mut Document, fonts: &LoadedFonts) -> Result<(), InvoiceError> {
+        let settings = PageSettings::from_wh(self.width, self.height)
+            .ok_or_else(|| InvoiceError::Render("Invalid page size".to_string()))?;
+        for elements in self.pages {
+            let mut page = doc.start_page_with(settings.clone());
+            let mut surface = page.surface();
+            let mut text_color = Color::BLACK;
+            for element in elements {
+                match element {
+                    Element::Text { x, y, style, text } => {
+                        if style.color != text_color {
+                            surface.set_fill(Some(fill(style.color)));
+                            text_color = style.color;
+                        }
+                        surface.draw_text(
+                            Point::from_xy(x, y),
+                            fonts.font(style.font).clone(),
+                            style.size,
+                            &text,
+                            false,
+                            TextDirection::Auto,
+                        );
+                    }
+                    Element::Line { x1, x2, y, style } => {
+                        draw_line(&mut surface, x1, x2, y, style)?
+                    }
+                    Element::Image {
+                        x,
+                        y,
+                        width,
+                        height,
+                        image,
+                    } => {
+                        let size = Size::from_wh(width, height).ok_or_else(|| {
+                            InvoiceError::Render("Invalid image size".to_string())
+                        })?;
+                        surface.push_transform(&Transform::from_translate(x, y));
+                        surface.draw_image(image, size);
+                        surface.pop();
+                    }
+                    Element::Shapes {
+                        x,
+                        y,
+                        scale,
+                        shapes,
+                    } => {
+                        surface.push_transform(&Transform::from_row(scale, 0.0, 0.0, scale, x, y));
+                        for shape in shapes.iter() {
+                            surface.set_fill(shape.fill.clone());
+                            surface.set_stroke(shape.stroke.clone());
+                            surface.draw_path(&shape.path);
+                        }
+                        surface.pop();
+                        // Text is drawn with the current fill, so restore the default.
+                        surface.set_fill(Some(Fill::default()));
+                        surface.set_stroke(None);
+                        text_color = Color::BLACK;
+                    }
+                }
+            }
+            surface.finish();
+            page.finish();
+        }
+        Ok(())
+    }
+}
+
+fn draw_line(
+    surface: &mut krilla::surface::Surface,
+    x1: f32,
+    x2: f32,
+    y: f32,
+    style: LineStyle,
+) -> Result<(), InvoiceError> {
+    let mut pb = krilla::geom::PathBuilder::new();
+    pb.move_to(x1, y);
+    pb.line_to(x2, y);
+    let path = pb
+        .finish()
+        .ok_or_else(|| InvoiceError::Render("Failed to create line".to_string()))?;
+
+    surface.set_stroke(Some(Stroke {
+        paint: paint(style.color).into(),
+        width: style.width,
+        ..Default::default()
+    }));
+    surface.draw_path(&path);
+
+    Ok(())
+}
+
+fn fill(color: Color) -> Fill {
+    Fill {
+        paint: paint(color).into(),
+        ..Default::default()
+    }
+}
+
+fn paint(color: Color) -> rgb::Color {
+    rgb::Color::new(color.r, color.g, color.b)
+}
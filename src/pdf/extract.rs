@@ -0,0 +1,642 @@
+//! Reads the XML attachment of incoming ZUGFeRD / Factur-X / XRechnung PDFs.
+//!
+//! Only the parts of the PDF syntax needed to reach file attachments are understood:
+//! objects are collected by scanning the file (so broken cross-reference tables do not
+//! matter), compressed object streams are unpacked and streams may be Flate encoded.
+
+use crate::einvoice::ParseReport;
+use crate::error::InvoiceError;
+use crate::invoice::Invoice;
+use crate::pdf::facturx::{FACTURX_FILE_NAME, HybridInvoice};
+use flate2::read::ZlibDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Attachment names used by Factur-X 1.0, ZUGFeRD 1.0 / 2.x and XRechnung, in order of
+/// preference. Names are compared case-insensitively.
+pub const EMBEDDED_XML_NAMES: [&str; 3] =
+    [FACTURX_FILE_NAME, "zugferd-invoice.xml", "xrechnung.xml"];
+
+/// Arrays and dictionaries nested deeper than this make an object unreadable.
+const MAX_NESTING: usize = 256;
+
+/// Largest decoded stream; Flate data that inflates beyond it is rejected.
+const MAX_STREAM_SIZE: u64 = 64 << 20;
+
+impl Invoice {
+    /// Reads an invoice from the XML embedded in a hybrid PDF.
+    pub fn from_pdf(pdf: &[u8]) -> Result<(Invoice, ParseReport), InvoiceError> {
+        let hybrid = extract_embedded_xml(pdf)?;
+        Invoice::from_einvoice_xml(&hybrid.xml)
+    }
+}
+
+/// Finds the e-invoice attachment of a PDF.
+///
+/// The returned [`HybridInvoice`] carries the conformance level and version from the
+/// document's XMP metadata where present, and empty strings otherwise.
+pub fn extract_embedded_xml(pdf: &[u8]) -> Result<HybridInvoice, InvoiceError> {
+    let objects = collect_objects(pdf)?;
+
+    let mut attachments = Vec::new();
+    for object in objects.values() {
+        find_file_specs(object, &mut attachments);
+    }
+    let found = attachments
+        .iter()
+        .filter_map(|spec| Some((file_name(spec)?, spec)))
+        .collect::<Vec<_>>();
+    let (file_name, spec) = EMBEDDED_XML_NAMES
+        .iter()
+        .find_map(|name| {
+            found
+                .iter()
+                .find(|(file, _)| file.eq_ignore_ascii_case(name))
+        })
+        .ok_or_else(|| {
+            let names = found.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+            InvoiceError::parse(format!(
+                "PDF has no e-invoice attachment (attachments: {})",
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            ))
+        })?;
+
+    let embedded = get(spec, b"EF")
+        .and_then(|ef| {
+            get(resolve(ef, &objects), b"UF").or_else(|| get(resolve(ef, &objects), b"F"))
+        })
+        .map(|file| resolve(file, &objects))
+        .ok_or_else(|| InvoiceError::parse("Attachment has no embedded file stream"))?;
+    let Object::Stream(dict, data) = embedded else {
+        return Err(InvoiceError::parse("Embedded file is not a stream"));
+    };
+    let data = decode_stream(dict, data, &objects)?;
+    let xml = String::from_utf8(data)
+        .map_err(|_| InvoiceError::parse("Embedded XML is not UTF-8 encoded"))?;
+
+    let mut hybrid = HybridInvoice::new(
+        xml.trim_start_matches('\u{feff}').to_string(),
+        String::new(),
+    );
+    hybrid.file_name = file_name.clone();
+    hybrid.version = String::new();
+    for object in objects.values() {
+        if let Object::Stream(dict, data) = object
+            && get_name(dict, b"Type") == Some(b"Metadata")
+            && let Ok(xmp) = decode_stream(dict, data, &objects)
+        {
+            let xmp = String::from_utf8_lossy(&xmp);
+            if let Some(level) = xmp_property(&xmp, "ConformanceLevel") {
+                hybrid.conformance_level = level;
+            }
+            if let Some(version) = xmp_property(&xmp, "Version") {
+                hybrid.version = version;
+            }
+        }
+    }
+    Ok(hybrid)
+}
+
+/// The PDF object kinds; values that are never inspected are not kept.
+enum Object {
+    Null,
+    Bool,
+    Number(f64),
+    Name(Vec<u8>),
+    String(Vec<u8>),
+    Array(Vec<Object>),
+    Dict(Dict),
+    Ref(u32),
+    /// Dictionary and raw, still encoded data.
+    Stream(Dict, Vec<u8>),
+}
+
+type Dict = Vec<(Vec<u8>, Object)>;
+
+fn get<'a>(object: &'a Object, key: &[u8]) -> Option<&'a Object> {
+    match object {
+        Object::Dict(dict) | Object::Stream(dict, _) => dict_get(dict, key),
+        _ => None,
+    }
+}
+
+fn dict_get<'a>(dict: &'a Dict, key: &[u8]) -> Option<&'a Object> {
+    dict.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn get_name<'a>(dict: &'a Dict, key: &[u8]) -> Option<&'a [u8]> {
+    match dict_get(dict, key) {
+        Some(Object::Name(name)) => Some(name),
+        _ => None,
+    }
+}
+
+fn resolve<'a>(object: &'a Object, objects: &'a HashMap<u32, Object>) -> &'a Object {
+    match object {
+        Object::Ref(id) => objects.get(id).unwrap_or(&Object::Null),
+        other => other,
+    }
+}
+
+/// Collects every file specification, including ones written inline in name trees.
+fn find_file_specs<'a>(object: &'a Object, found: &mut Vec<&'a Object>) {
+    match object {
+        Object::Dict(dict) => {
+            if dict_get(dict, b"EF").is_some() {
+                found.push(object);
+            }
+            dict.iter().for_each(|(_, v)| find_file_specs(v, found));
+        }
+        Object::Array(items) => items.iter().for_each(|v| find_file_specs(v, found)),
+        _ => {}
+    }
+}
+
+fn file_name(spec: &Object) -> Option<String> {
+    let Some(Object::String(raw)) = get(spec, b"UF").or_else(|| get(spec, b"F")) else {
+        return None;
+    };
+    let name = text_string(raw);
+    Some(
+        name.rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    )
+}
+
+/// Decodes a PDF text string: UTF-16BE with byte order mark, otherwise one byte per char.
+fn text_string(raw: &[u8]) -> String {
+    match raw.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => String::from_utf16_lossy(
+            &utf16
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        None => raw.iter().map(|&b| b as char).collect(),
+    }
+}
+
+fn decode_stream(
+    dict: &Dict,
+    data: &[u8],
+    objects: &HashMap<u32, Object>,
+) -> Result<Vec<u8>, InvoiceError> {
+    let filters = match dict_get(dict, b"Filter").map(|f| resolve(f, objects)) {
+        None | Some(Object::Null) => Vec::new(),
+        Some(Object::Name(name)) => vec![name.as_slice()],
+        Some(Object::Array(names)) => names
+            .iter()
+            .filter_map(|n| match n {
+                Object::Name(name) => Some(name.as_slice()),
+                _ => None,
+            })
+            .collect(),
+        Some(_) => return Err(InvoiceError::parse("Invalid stream filter")),
+    };
+    let mut data = data.to_vec();
+    for filter in filters {
+        match filter {
+            b"FlateDecode" | b"Fl" => {
+                let mut decoded = Vec::new();
+                ZlibDecoder::new(data.as_slice())
+                    .take(MAX_STREAM_SIZE + 1)
+                    .read_to_end(&mut decoded)
+                    .map_err(|e| InvoiceError::parse(format!("Corrupt Flate stream: {}", e)))?;
+                if decoded.len() as u64 > MAX_STREAM_SIZE {
+                    return Err(InvoiceError::parse(format!(
+                        "Stream exceeds {} bytes when decoded",
+                        MAX_STREAM_SIZE
+                    )));
+                }
+                data = decoded;
+            }
+            other => {
+                return Err(InvoiceError::parse(format!(
+                    "Unsupported stream filter {}",
+                    String::from_utf8_lossy(other)
+                )));
+            }
+        }
+    }
+    Ok(data)
+}
+
+fn xmp_property(xmp: &str, name: &str) -> Option<String> {
+    // Either `<fx:Version>1.0</fx:Version>` or the attribute form `fx:Version="1.0"`.
+    xmp.match_indices(&format!(":{}", name))
+        .find_map(|(start, key)| {
+            let rest = &xmp[start + key.len()..];
+            let value = if let Some(rest) = rest.strip_prefix('>') {
+                &rest[..rest.find('<')?]
+            } else {
+                let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+                let quote = rest.chars().next().filter(|q| matches!(q, '"' | '\''))?;
+                let rest = &rest[1..];
+                &rest[..rest.find(quote)?]
+            };
+            Some(value.trim().to_string())
+        })
+}
+
+/// Scans the file for `N G obj` definitions. Later definitions win, as they do for
+/// incremental updates.
+fn collect_objects(pdf: &[u8]) -> Result<HashMap<u32, Object>, InvoiceError> {
+    let mut objects = HashMap::new();
+    let mut pos = 0;
+    while let Some(found) = find(pdf, b"obj", pos) {
+        pos = found + 3;
+        let Some(id) = object_number(pdf, found) else {
+            continue;
+        };
+        let mut parser = Parser {
+            data: pdf,
+            pos,
+            depth: 0,
+        };
+        let Some(object) = parser.parse_object() else {
+            continue;
+        };
+        if let Some(stream) = parser.parse_stream(object) {
+            objects.insert(id, stream);
+        }
+        pos = parser.pos;
+    }
+
+    let mut unpacked = Vec::new();
+    for object in objects.values() {
+        if let Object::Stream(dict, data) = object
+            && get_name(dict, b"Type") == Some(b"ObjStm")
+        {
+            let data = decode_stream(dict, data, &objects)?;
+            unpacked.extend(unpack_object_stream(dict, &data));
+        }
+    }
+    for (id, object) in unpacked {
+        objects.entry(id).or_insert(object);
+    }
+    Ok(objects)
+}
+
+/// Reads the object number in front of `obj`, requiring the `N G obj` shape.
+fn object_number(pdf: &[u8], obj: usize) -> Option<u32> {
+    if pdf.get(obj + 3).is_some_and(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let mut end = obj;
+    let skip_space = |end: &mut usize| {
+        let before = *end;
+        while *end > 0 && is_whitespace(pdf[*end - 1]) {
+            *end -= 1;
+        }
+        *end < before
+    };
+    let skip_digits = |end: &mut usize| {
+        let before = *end;
+        while *end > 0 && pdf[*end - 1].is_ascii_digit() {
+            *end -= 1;
+        }
+        *end < before
+    };
+    if !(skip_space(&mut end) && skip_digits(&mut end) && skip_space(&mut end)) {
+        return None;
+    }
+    let number_end = end;
+    if !skip_digits(&mut end) {
+        return None;
+    }
+    std::str::from_utf8(&pdf[end..number_end])
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn unpack_object_stream(dict: &Dict, data: &[u8]) -> Vec<(u32, Object)> {
+    let number = |key| match dict_get(dict, key) {
+        Some(Object::Number(n)) => Some(*n as usize),
+        _ => None,
+    };
+    let (Some(count), Some(first)) = (number(b"N"), number(b"First")) else {
+        return Vec::new();
+    };
+    let mut header = Parser {
+        data: &data[..first.min(data.len())],
+        pos: 0,
+        depth: 0,
+    };
+    let mut objects = Vec::new();
+    for _ in 0..count {
+        let (Some(Object::Number(id)), Some(Object::Number(offset))) =
+            (header.parse_object(), header.parse_object())
+        else {
+            break;
+        };
+        let mut parser = Parser {
+            data,
+            pos: first + offset as usize,
+            depth: 0,
+        };
+        if let Some(object) = parser.parse_object() {
+            objects.push((id as u32, object));
+        }
+    }
+    objects
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| pos + from)
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Objects currently being parsed, limited to `MAX_NESTING`.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn starts_with(&self, token: &[u8]) -> bool {
+        self.data[self.pos.min(self.data.len())..].starts_with(token)
+    }
+
+    fn parse_object(&mut self) -> Option<Object> {
+        if self.depth == MAX_NESTING {
+            return None;
+        }
+        self.depth += 1;
+        let object = self.parse_value();
+        self.depth -= 1;
+        object
+    }
+
+    fn parse_value(&mut self) -> Option<Object> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'<' if self.starts_with(b"<<") => {
+                self.pos += 2;
+                let mut dict = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.starts_with(b">>") {
+                        self.pos += 2;
+                        return Some(Object::Dict(dict));
+                    }
+                    let Object::Name(key) = self.parse_object()? else {
+                        return None;
+                    };
+                    let value = self.parse_object()?;
+                    dict.push((key, value));
+                }
+            }
+            b'<' => {
+                self.pos += 1;
+                let end = self.pos + self.data[self.pos..].iter().position(|&b| b == b'>')?;
+                let digits = self.data[self.pos..end]
+                    .iter()
+                    .filter(|b| b.is_ascii_hexdigit())
+                    .map(|&b| (b as char).to_digit(16).unwrap_or(0) as u8)
+                    .collect::<Vec<_>>();
+                self.pos = end + 1;
+                Some(Object::String(
+                    digits
+                        .chunks(2)
+                        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+                        .collect(),
+                ))
+            }
+            b'(' => self.parse_literal_string(),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Some(Object::Array(items));
+                    }
+                    items.push(self.parse_object()?);
+                }
+            }
+            b'/' => {
+                self.pos += 1;
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|b| !is_whitespace(b) && !is_delimiter(b))
+                {
+                    self.pos += 1;
+                }
+                Some(Object::Name(unescape_name(&self.data[start..self.pos])))
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => {
+                let number = self.parse_number()?;
+                // `N G R` is an indirect reference.
+                let after_number = self.pos;
+                self.skip_whitespace();
+                if self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                    let _generation = self.parse_number();
+                    self.skip_whitespace();
+                    if self.peek() == Some(b'R')
+                        && self
+                            .data
+                            .get(self.pos + 1)
+                            .is_none_or(|&b| is_whitespace(b) || is_delimiter(b))
+                    {
+                        self.pos += 1;
+                        return Some(Object::Ref(number as u32));
+                    }
+                }
+                self.pos = after_number;
+                Some(Object::Number(number))
+            }
+            _ => {
+                for (keyword, object) in [
+                    (&b"true"[..], Object::Bool),
+                    (b"false", Object::Bool),
+                    (b"null", Object::Null),
+                ] {
+                    if self.starts_with(keyword) {
+                        self.pos += keyword.len();
+                        return Some(object);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| matches!(b, b'+' | b'-' | b'.' | b'0'..=b'9'))
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn parse_literal_string(&mut self) -> Option<Object> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut depth = 0;
+        loop {
+            let b = self.peek()?;
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' if depth == 0 => return Some(Object::String(out)),
+                b')' => {
+                    depth -= 1;
+                    out.push(b);
+                }
+                b'\\' => {
+                    let escaped = self.peek()?;
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = (escaped - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // A backslash at the end of a line continues the string.
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                other => out.push(other),
+            }
+        }
+    }
+
+    /// Turns a dictionary followed by `stream` into a stream object, leaving `pos` behind
+    /// its data. Other objects are returned unchanged.
+    fn parse_stream(&mut self, object: Object) -> Option<Object> {
+        let Object::Dict(dict) = object else {
+            return Some(object);
+        };
+        self.skip_whitespace();
+        if !self.starts_with(b"stream") {
+            return Some(Object::Dict(dict));
+        }
+        self.pos += b"stream".len();
+        if self.starts_with(b"\r\n") {
+            self.pos += 2;
+        } else if self.starts_with(b"\n") || self.starts_with(b"\r") {
+            self.pos += 1;
+        }
+        let start = self.pos;
+
+        // Trust a direct /Length when `endstream` follows it; indirect lengths are
+        // resolved by searching for the keyword instead.
+        let end = match dict_get(&dict, b"Length") {
+            Some(Object::Number(length))
+                if {
+                    let mut check = Parser {
+                        data: self.data,
+                        pos: start + *length as usize,
+                        depth: 0,
+                    };
+                    check.skip_whitespace();
+                    check.starts_with(b"endstream")
+                } =>
+            {
+                start + *length as usize
+            }
+            _ => {
+                let keyword = find(self.data, b"endstream", start)?;
+                let mut end = keyword;
+                if end > start && self.data[end - 1] == b'\n' {
+                    end -= 1;
+                }
+                if end > start && self.data[end - 1] == b'\r' {
+                    end -= 1;
+                }
+                end
+            }
+        };
+        let data = self.data.get(start..end)?.to_vec();
+        self.pos = find(self.data, b"endstream", end)? + b"endstream".len();
+        Some(Object::Stream(dict, data))
+    }
+}
+
+fn unescape_name(raw: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#'
+            && let Some(hex) = raw.get(i + 1..i + 3)
+            && let Ok(value) = u8::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16)
+        {
+            out.push(value);
+            i += 3;
+        } else {
+            out.push(raw[i]);
+            i += 1;
+        }
+    }
+    out
+}
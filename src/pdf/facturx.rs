@@ -0,0 +1,287 @@
+use crate::einvoice::EInvoiceProfile;
+use crate::error::InvoiceError;
+use crate::invoice::Invoice;
+use krilla::Data;
+use krilla::embed::{AssociationKind, EmbeddedFile, MimeType};
+use krilla::metadata::DateTime;
+
+/// File name Factur-X 1.0 / ZUGFeRD 2.x readers look for.
+pub const FACTURX_FILE_NAME: &str = "factur-x.xml";
+
+/// The sRGB v4 profile used as the PDF/A output intent. krilla embeds a
+/// byte-identical copy of this profile for PDF/A-3 documents.
+pub const SRGB_ICC: &[u8] = include_bytes!("../../assets/sRGB.icc");
+
+const FACTURX_NAMESPACE: &str = "urn:factur-x:pdfa:CrossIndustryDocument:invoice:1p0#";
+
+/// The structured invoice embedded into a hybrid ZUGFeRD / Factur-X PDF.
+///
+/// Setting [`PdfOptions::hybrid`] turns the output into a PDF/A-3b file that carries
+/// `xml` as an associated file with `AFRelationship=Alternative`.
+///
+/// [`PdfOptions::hybrid`]: crate::pdf::PdfOptions::hybrid
+pub struct HybridInvoice {
+    pub xml: String,
+    pub file_name: String,
+    /// Factur-X conformance level written to the XMP metadata, e.g. `EN 16931`.
+    pub conformance_level: String,
+    pub version: String,
+}
+
+impl HybridInvoice {
+    pub fn new(xml: String, conformance_level: impl Into<String>) -> Self {
+        HybridInvoice {
+            xml,
+            file_name: FACTURX_FILE_NAME.to_string(),
+            conformance_level: conformance_level.into(),
+            version: "1.0".to_string(),
+        }
+    }
+
+    /// The CII document of `invoice` for `profile`, declared with the profile's
+    /// conformance level.
+    pub fn from_invoice(invoice: &Invoice, profile: EInvoiceProfile) -> Result<Self, InvoiceError> {
+        Ok(HybridInvoice::new(
+            invoice.to_cii_xml(profile)?,
+            profile.conformance_level(),
+        ))
+    }
+
+    pub(crate) fn embedded_file(
+        &self,
+        invoice: &Invoice,
+        description: String,
+    ) -> Result<EmbeddedFile, String> {
+        Ok(EmbeddedFile {
+            path: self.file_name.clone(),
+            mime_type: Some(MimeType::new("text/xml").ok_or("Invalid mime type")?),
+            description: Some(description),
+            association_kind: AssociationKind::Alternative,
+            data: Data::from(self.xml.clone().into_bytes()),
+            modification_date: Some(pdf_date(&invoice.date)),
+            compress: None,
+            location: None,
+        })
+    }
+
+    /// Adds the Factur-X extension schema and properties to the XMP packet krilla wrote.
+    ///
+    /// krilla does not accept custom XMP, so the packet is patched in place and the
+    /// cross-reference table is shifted by the number of inserted bytes. This relies on
+    /// krilla writing a single classic xref table; the patched file is checked and an
+    /// error returned rather than a PDF whose offsets do not resolve.
+    pub(crate) fn add_xmp_extension(&self, pdf: Vec<u8>) -> Result<Vec<u8>, String> {
+        let packet_start = find(&pdf, b"<x:xmpmeta", 0).ok_or("XMP metadata not found")?;
+        let bag_end = find(&pdf, b"</rdf:Bag></pdfaExtension:schemas>", packet_start)
+            .ok_or("PDF/A extension schemas not found in XMP metadata")?;
+        let rdf_end = find(&pdf, b"</rdf:RDF>", bag_end).ok_or("Malformed XMP metadata")?;
+
+        let schema = format!(
+            "<rdf:li rdf:parseType=\"Resource\">\
+             <pdfaSchema:schema>Factur-X PDFA Extension Schema</pdfaSchema:schema>\
+             <pdfaSchema:namespaceURI>{FACTURX_NAMESPACE}</pdfaSchema:namespaceURI>\
+             <pdfaSchema:prefix>fx</pdfaSchema:prefix>\
+             <pdfaSchema:property><rdf:Seq>{}{}{}{}</rdf:Seq></pdfaSchema:property>\
+             </rdf:li>",
+            schema_property("DocumentFileName", "The name of the embedded XML document"),
+            schema_property(
+                "DocumentType",
+                "The type of the hybrid document in capital letters, e.g. INVOICE or ORDER"
+            ),
+            schema_property(
+                "Version",
+                "The actual version of the standard applying to the embedded XML document"
+            ),
+            schema_property(
+                "ConformanceLevel",
+                "The conformance level of the embedded XML document"
+            ),
+        );
+        let description = format!(
+            "<rdf:Description rdf:about=\"\" xmlns:fx=\"{FACTURX_NAMESPACE}\">\
+             <fx:DocumentType>INVOICE</fx:DocumentType>\
+             <fx:DocumentFileName>{}</fx:DocumentFileName>\
+             <fx:Version>{}</fx:Version>\
+             <fx:ConformanceLevel>{}</fx:ConformanceLevel>\
+             </rdf:Description>",
+            xml_escape(&self.file_name),
+            xml_escape(&self.version),
+            xml_escape(&self.conformance_level),
+        );
+
+        let inserted = schema.len() + description.len();
+        let mut out = Vec::with_capacity(pdf.len() + inserted);
+        out.extend_from_slice(&pdf[..bag_end]);
+        out.extend_from_slice(schema.as_bytes());
+        out.extend_from_slice(&pdf[bag_end..rdf_end]);
+        out.extend_from_slice(description.as_bytes());
+        out.extend_from_slice(&pdf[rdf_end..]);
+
+        fix_stream_length(&mut out, packet_start, inserted)?;
+        fix_xref(&mut out, packet_start)?;
+        check_xref(&out)?;
+        Ok(out)
+    }
+}
+
+fn schema_property(name: &str, description: &str) -> String {
+    format!(
+        "<rdf:li rdf:parseType=\"Resource\">\
+         <pdfaProperty:name>{name}</pdfaProperty:name>\
+         <pdfaProperty:valueType>Text</pdfaProperty:valueType>\
+         <pdfaProperty:category>external</pdfaProperty:category>\
+         <pdfaProperty:description>{description}</pdfaProperty:description>\
+         </rdf:li>"
+    )
+}
+
+/// Rewrites the `/Length` of the stream whose data contains `data_start` and checks that
+/// the new length ends at `endstream`.
+fn fix_stream_length(pdf: &mut Vec<u8>, data_start: usize, inserted: usize) -> Result<(), String> {
+    let dict_start = rfind(&pdf[..data_start], b"obj").ok_or("Metadata object not found")?;
+    let key = find(pdf, b"/Length ", dict_start)
+        .filter(|&pos| pos < data_start)
+        .ok_or("Metadata stream has no length")?
+        + b"/Length ".len();
+    let digits = pdf[key..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let length: usize = parse_ascii(&pdf[key..key + digits])?;
+    let replacement = (length + inserted).to_string();
+    pdf.splice(key..key + digits, replacement.bytes());
+
+    let keyword = find(pdf, b"stream", key).ok_or("Metadata stream not found")? + 6;
+    let data = &pdf[keyword..];
+    let data = data.strip_prefix(b"\r").unwrap_or(data);
+    let data = data.strip_prefix(b"\n").unwrap_or(data);
+    let after = data.get(length + inserted..).unwrap_or_default();
+    let after = after.strip_prefix(b"\r").unwrap_or(after);
+    let after = after.strip_prefix(b"\n").unwrap_or(after);
+    if !after.starts_with(b"endstream") {
+        return Err("Metadata stream length does not match its data".to_string());
+    }
+    Ok(())
+}
+
+/// Shifts every xref offset behind `after` and the `startxref` pointer to account for
+/// the bytes inserted into the metadata stream.
+fn fix_xref(pdf: &mut Vec<u8>, after: usize) -> Result<(), String> {
+    let value_start = rfind(pdf, b"startxref\n").ok_or("startxref not found")? + 10;
+    let digits = pdf[value_start..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    let old_xref: usize = parse_ascii(&pdf[value_start..value_start + digits])?;
+    let new_xref = rfind(pdf, b"\nxref\n").ok_or("xref table not found")? + 1;
+    let shift = new_xref - old_xref;
+
+    // Skip the "0 <count>" subsection header; each entry is exactly 20 bytes.
+    let mut pos = new_xref + 5;
+    pos += pdf[pos..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or("Malformed xref table")?
+        + 1;
+    while matches!(pdf.get(pos + 17), Some(b'n' | b'f')) {
+        if pdf[pos + 17] == b'n' {
+            let offset: usize = parse_ascii(&pdf[pos..pos + 10])?;
+            if offset > after {
+                pdf[pos..pos + 10].copy_from_slice(format!("{:010}", offset + shift).as_bytes());
+            }
+        }
+        pos += 20;
+    }
+
+    pdf.splice(
+        value_start..value_start + digits,
+        new_xref.to_string().bytes(),
+    );
+    Ok(())
+}
+
+/// Checks that `startxref` leads to a cross-reference table whose in-use entries all
+/// point at the objects they number.
+pub(crate) fn check_xref(pdf: &[u8]) -> Result<(), String> {
+    let mut pos = rfind(pdf, b"startxref").ok_or("startxref not found")? + 9;
+    let xref = read_number(pdf, &mut pos)?;
+    if !pdf
+        .get(xref..)
+        .is_some_and(|rest| rest.starts_with(b"xref"))
+    {
+        return Err("startxref does not point at a cross-reference table".to_string());
+    }
+    pos = xref + 4;
+    loop {
+        while pdf.get(pos).is_some_and(u8::is_ascii_whitespace) {
+            pos += 1;
+        }
+        if pdf
+            .get(pos..)
+            .is_some_and(|rest| rest.starts_with(b"trailer"))
+        {
+            return Ok(());
+        }
+        let first = read_number(pdf, &mut pos)?;
+        let count = read_number(pdf, &mut pos)?;
+        while pdf.get(pos).is_some_and(u8::is_ascii_whitespace) {
+            pos += 1;
+        }
+        for number in first..first + count {
+            let entry = pdf.get(pos..pos + 20).ok_or("Truncated xref table")?;
+            if entry[17] == b'n' {
+                let offset: usize = parse_ascii(&entry[..10])?;
+                let generation: usize = parse_ascii(&entry[11..16])?;
+                let header = format!("{} {} obj", number, generation);
+                if !pdf
+                    .get(offset..)
+                    .is_some_and(|rest| rest.starts_with(header.as_bytes()))
+                {
+                    return Err(format!("xref entry of object {} does not resolve", number));
+                }
+            }
+            pos += 20;
+        }
+    }
+}
+
+/// Reads the unsigned number after any whitespace at `pos` and moves past it.
+fn read_number(pdf: &[u8], pos: &mut usize) -> Result<usize, String> {
+    while pdf.get(*pos).is_some_and(u8::is_ascii_whitespace) {
+        *pos += 1;
+    }
+    let start = *pos;
+    while pdf.get(*pos).is_some_and(u8::is_ascii_digit) {
+        *pos += 1;
+    }
+    parse_ascii(&pdf[start..*pos])
+}
+
+fn parse_ascii<T: std::str::FromStr>(bytes: &[u8]) -> Result<T, String> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| "Malformed number in PDF".to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| pos + from)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub(crate) fn pdf_date(date: &locale_rs::datetime_formats::DateTime) -> DateTime {
+    DateTime::new(date.year as u16)
+        .month(date.month as u8)
+        .day(date.day as u8)
+}
@@ -0,0 +1,155 @@
+use crate::error::InvoiceError;
+use crate::pdf::text::TextMeasurer;
+use crate::pdf::theme::FontRole;
+use krilla::text::Font;
+use std::path::{Path, PathBuf};
+
+/// Where a font comes from: a TrueType / OpenType file or its contents, e.g. for
+/// servers without filesystem access.
+pub enum FontSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// The fonts of a family by role. Roles without a font of their own fall back to a
+/// related one: semibold to bold, bold and italic to the regular font.
+pub struct FontSet {
+    pub regular: FontSource,
+    pub bold: Option<FontSource>,
+    pub italic: Option<FontSource>,
+    pub semibold: Option<FontSource>,
+}
+
+impl FontSet {
+    /// A single font drawing all roles.
+    pub fn new(regular: FontSource) -> Self {
+        FontSet {
+            regular,
+            bold: None,
+            italic: None,
+            semibold: None,
+        }
+    }
+
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Self {
+        FontSet::new(FontSource::Path(path.into()))
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        FontSet::new(FontSource::Bytes(bytes))
+    }
+
+    /// Sets the font of `role`.
+    pub fn with(mut self, role: FontRole, source: FontSource) -> Self {
+        match role {
+            FontRole::Regular => self.regular = source,
+            FontRole::Bold => self.bold = Some(source),
+            FontRole::Italic => self.italic = Some(source),
+            FontRole::SemiBold => self.semibold = Some(source),
+        }
+        self
+    }
+
+    /// Open Sans from a directory with the files of the family, such as the `fonts/`
+    /// directory of this repository.
+    pub fn open_sans<P: AsRef<Path>>(dir: P) -> Self {
+        let file = |name: &str| FontSource::Path(dir.as_ref().join(name));
+        FontSet {
+            regular: file("OpenSans-Medium.ttf"),
+            bold: Some(file("OpenSans-Bold.ttf")),
+            italic: Some(file("OpenSans-MediumItalic.ttf")),
+            semibold: Some(file("OpenSans-SemiBold.ttf")),
+        }
+    }
+
+    /// Open Sans compiled into the binary.
+    #[cfg(feature = "embedded-font")]
+    pub fn embedded() -> Self {
+        let bytes = |data: &[u8]| FontSource::Bytes(data.to_vec());
+        FontSet {
+            regular: bytes(include_bytes!("../../fonts/OpenSans-Medium.ttf")),
+            bold: Some(bytes(include_bytes!("../../fonts/OpenSans-Bold.ttf"))),
+            italic: Some(bytes(include_bytes!(
+                "../../fonts/OpenSans-MediumItalic.ttf"
+            ))),
+            semibold: Some(bytes(include_bytes!("../../fonts/OpenSans-SemiBold.ttf"))),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Result<LoadedFonts, InvoiceError> {
+        let load = |source: &Option<FontSource>| source.as_ref().map(Face::load).transpose();
+        Ok(LoadedFonts {
+            regular: Face::load(&self.regular)?,
+            bold: load(&self.bold)?,
+            italic: load(&self.italic)?,
+            semibold: load(&self.semibold)?,
+        })
+    }
+}
+
+#[cfg(feature = "embedded-font")]
+impl Default for FontSet {
+    fn default() -> Self {
+        FontSet::embedded()
+    }
+}
+
+pub(crate) struct LoadedFonts {
+    regular: Face,
+    bold: Option<Face>,
+    italic: Option<Face>,
+    semibold: Option<Face>,
+}
+
+impl LoadedFonts {
+    pub(crate) fn font(&self, role: FontRole) -> &Font {
+        &self.face(role).font
+    }
+
+    pub(crate) fn measurer(&self, role: FontRole) -> Result<TextMeasurer<'_>, InvoiceError> {
+        let face = self.face(role);
+        TextMeasurer::new(&face.data).ok_or_else(|| face.invalid())
+    }
+
+    fn face(&self, role: FontRole) -> &Face {
+        let bold = self.bold.as_ref();
+        let face = match role {
+            FontRole::Regular => None,
+            FontRole::Bold => bold,
+            FontRole::Italic => self.italic.as_ref(),
+            FontRole::SemiBold => self.semibold.as_ref().or(bold),
+        };
+        face.unwrap_or(&self.regular)
+    }
+}
+
+struct Face {
+    font: Font,
+    data: Vec<u8>,
+    path: Option<PathBuf>,
+}
+
+impl Face {
+    fn load(source: &FontSource) -> Result<Face, InvoiceError> {
+        let (data, path) = match source {
+            FontSource::Path(path) => {
+                let data = std::fs::read(path).map_err(|source| InvoiceError::FontNotFound {
+                    path: path.clone(),
+                    source,
+                })?;
+                (data, Some(path.clone()))
+            }
+            FontSource::Bytes(bytes) => (bytes.clone(), None),
+        };
+        let invalid = || InvoiceError::InvalidFont { path: path.clone() };
+        let font = Font::new(krilla::Data::from(data.clone()), 0).ok_or_else(invalid)?;
+        TextMeasurer::new(&data).ok_or_else(invalid)?;
+        Ok(Face { font, data, path })
+    }
+
+    fn invalid(&self) -> InvoiceError {
+        InvoiceError::InvalidFont {
+            path: self.path.clone(),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::models::{Currency, Decimal, Money};
+use locale_rs::Locale;
+use locale_rs::num_formats::{ToFormattedString, translate_digits};
+
+/// Languages whose CLDR percent pattern separates the sign from the number (`19 %`).
+const SPACED_PERCENT: &[&str] = &["cs", "da", "de", "es", "fi", "fr", "nb", "sk", "sv"];
+
+/// Formats an amount for display using the locale's CLDR currency pattern
+/// (symbol placement, separators, digits) and the currency's minor units.
+pub fn format_money(locale: &Locale, money: &Money) -> String {
+    let rounded = money.round();
+    with_currency(locale, money.currency, rounded.amount)
+}
+
+/// Like [`format_money`], but keeps digits beyond the minor units, e.g. a unit price
+/// of `0.125`.
+pub fn format_price(locale: &Locale, money: &Money) -> String {
+    with_currency(locale, money.currency, money.amount)
+}
+
+/// Formats a number such as a quantity with the locale's separators and digits,
+/// without trailing zeros.
+pub fn format_number(locale: &Locale, value: Decimal) -> String {
+    with_sign(locale, value, digits(locale, value, 0))
+}
+
+/// Formats a fractional rate as a percentage, e.g. `0.055` as `5,5 %` in French.
+pub fn format_percent(locale: &Locale, rate: Decimal) -> String {
+    let number = format_number(locale, rate * Decimal::ONE_HUNDRED);
+    let language = locale.as_str().split('-').next().unwrap_or_default();
+    if SPACED_PERCENT.contains(&language) {
+        format!("{}\u{a0}%", number)
+    } else {
+        format!("{}%", number)
+    }
+}
+
+fn with_currency(locale: &Locale, currency: Currency, amount: Decimal) -> String {
+    let pattern = locale
+        .currency_standard_pattern()
+        .split(';')
+        .next()
+        .unwrap_or("¤#,##0.00");
+    let number = digits(locale, amount, currency.minor_units());
+
+    let symbol = currency.symbol();
+    let formatted = match (pattern.find(['#', '0']), pattern.rfind(['#', '0'])) {
+        (Some(start), Some(end)) => {
+            let mut prefix = pattern[..start].replace('¤', symbol);
+            let mut suffix = pattern[end + 1..].replace('¤', symbol);
+            // Patterns such as "¤#,##0.00" assume a symbol like "$"; keep codes like
+            // "CHF" from running into the digits.
+            if prefix.ends_with(symbol) && symbol.ends_with(char::is_alphabetic) {
+                prefix.push('\u{a0}');
+            }
+            if suffix.starts_with(symbol) && symbol.starts_with(char::is_alphabetic) {
+                suffix.insert(0, '\u{a0}');
+            }
+            format!("{}{}{}", prefix, number, suffix)
+        }
+        _ => format!("{}{}", symbol, number),
+    };
+    with_sign(locale, amount, formatted)
+}
+
+fn with_sign(locale: &Locale, value: Decimal, formatted: String) -> String {
+    if value.is_sign_negative() {
+        format!("{}{}", locale.minus_sign(), formatted)
+    } else {
+        formatted
+    }
+}
+
+/// The absolute value with grouping and decimal separators, showing at least `min_dp`
+/// decimal places and any further significant ones.
+fn digits(locale: &Locale, value: Decimal, min_dp: u32) -> String {
+    let value = value.normalize();
+    let scale = value.scale() as usize;
+    let digits = format!(
+        "{:0>width$}",
+        value.mantissa().unsigned_abs(),
+        width = scale + 1
+    );
+    let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+    let int_value: u128 = int_part.parse().unwrap_or(0);
+    let mut number = int_value.to_formatted_string(locale);
+    let frac_part = format!("{:0<width$}", frac_part, width = min_dp as usize);
+    if !frac_part.is_empty() {
+        number.push_str(locale.decimal_separator());
+        number.push_str(&translate_digits(frac_part, locale));
+    }
+    number
+}
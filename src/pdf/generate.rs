@@ -1,429 +1,935 @@
-use crate::invoice::Invoice;
-use krilla::Document;
-use krilla::color::rgb;
-use krilla::geom::Point;
-use krilla::paint::Stroke;
-use krilla::text::Font;
-use krilla::text::TextDirection;
+use crate::error::InvoiceError;
+use crate::invoice::{
+    Address, AllowanceCharge, Decimal, ElectronicAddress, Invoice, Money, Product, Seller,
+    TaxCategory, Unit,
+};
+use crate::models::TaxSubtotal;
+use crate::pdf::canvas::Canvas;
+use crate::pdf::facturx;
+use crate::pdf::fonts::FontSet;
+use crate::pdf::format::{format_money, format_number, format_percent, format_price};
+use crate::pdf::labels::{Label, Translations};
+use crate::pdf::logo::{Logo, LogoGraphic};
+use crate::pdf::options::PdfOptions;
+use crate::pdf::text::TextMeasurer;
+use crate::pdf::theme::{LineStyle, Slot, Slots, TextStyle, Theme};
+use krilla::configure::{Configuration, Validator};
+use krilla::metadata::Metadata;
+use krilla::{Document, SerializeSettings};
+use locale_rs::Locale;
 use std::path::Path;
 
 pub fn generate_invoice_pdf<P: AsRef<Path>>(
     invoice: &Invoice,
     font_path: P,
-    _logo_path: Option<P>,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let mut doc = Document::new();
+    logo_path: Option<P>,
+) -> Result<Vec<u8>, InvoiceError> {
+    let options = PdfOptions {
+        logo: logo_path.map(|p| Logo::from_path(p.as_ref())),
+        ..Default::default()
+    };
+    generate_invoice_pdf_with_options(invoice, font_path, &options)
+}
 
-    let font_bytes = std::fs::read(font_path)?;
-    let font = Font::new(krilla::Data::from(font_bytes), 0).ok_or("Failed to load font")?;
+pub fn generate_invoice_pdf_with_options<P: AsRef<Path>>(
+    invoice: &Invoice,
+    font_path: P,
+    options: &PdfOptions,
+) -> Result<Vec<u8>, InvoiceError> {
+    let fonts = FontSet::from_path(font_path.as_ref());
+    generate_invoice_pdf_with_fonts(invoice, &fonts, options)
+}
 
-    let mut page = doc.start_page();
-    let mut surface = page.surface();
+/// Renders the invoice with the fonts of `fonts`; the theme picks the font of each text.
+pub fn generate_invoice_pdf_with_fonts(
+    invoice: &Invoice,
+    fonts: &FontSet,
+    options: &PdfOptions,
+) -> Result<Vec<u8>, InvoiceError> {
+    let mut doc = match &options.hybrid {
+        Some(_) => Document::new_with(SerializeSettings {
+            configuration: Configuration::new_with_validator(Validator::A3_B),
+            ..Default::default()
+        }),
+        None => Document::new(),
+    };
+
+    // Checks once that all amounts fit before any of them is computed.
+    let summary = invoice.try_calculate_summary()?;
+    let theme = &options.theme;
+    let fonts = fonts.load()?;
+    let measurer = fonts.measurer(theme.body.font)?;
+    let small_measurer = fonts.measurer(theme.small.font)?;
+
+    let margin_left = theme.margins.left;
+    let mut canvas = Canvas::new(theme.page.width, theme.page.height);
+    let page_bottom = canvas.height() - theme.margins.bottom;
+    let right_edge = canvas.width() - theme.margins.right;
+    let gap = theme.gap();
+
+    if let Some(logo) = &options.logo {
+        draw_logo(&mut canvas, logo, right_edge, theme)?;
+    }
 
-    // Page dimensions (A4 in points: 595x842)
-    let margin_left = 40.0;
-    let margin_top = 40.0;
-    let margin_right = 40.0;
-    let page_width = 595.0;
-    let _page_height = 842.0;
+    let captions = Captions {
+        translations: &options.translations,
+        locale: &invoice.locale,
+    };
+    let mut y = draw_header(&mut canvas, invoice, &captions, theme);
+
+    // Delivery, payment method and any further details
+    let metadata = metadata_lines(invoice, &captions);
+    if !metadata.is_empty() {
+        for line in metadata {
+            canvas.text(margin_left, y, theme.body, line);
+            y += theme.body.line_height();
+        }
+        y += gap;
+    }
 
-    let mut y = margin_top;
+    // Horizontal line
+    canvas.line(margin_left, right_edge, y, theme.rule);
 
-    // Title
-    surface.draw_text(
-        Point::from_xy(margin_left, y),
-        font.clone(),
-        28.0,
-        "INVOICE",
-        false,
-        TextDirection::Auto,
-    );
+    y += 2.0 * gap;
 
-    y += 40.0;
+    let table = Table::new(theme);
+    // Space kept free below the last row for the rule and the "subtotal carried over"
+    // row.
+    let carry_over_height = gap / 2.0 + 2.0 * table.row;
+    y = table.draw_header(&mut canvas, y, right_edge, &captions);
 
-    // Invoice details (right aligned)
-    let details_x = page_width - margin_right - 150.0;
-    surface.draw_text(
-        Point::from_xy(details_x, y),
-        font.clone(),
-        10.0,
-        &format!("Invoice #: {}", invoice.number),
-        false,
-        TextDirection::Auto,
-    );
+    // Products
+    let mut running_total = Money::zero(invoice.currency);
+    let mut rows_on_page = 0;
+    for product in &invoice.products {
+        let line_total = product.net_amount(invoice.currency);
+        let base = product.base_amount(invoice.currency);
+        let unit = captions.unit(&product.unit);
+        let mut desc_lines =
+            measurer.wrap(&product.description, theme.body.size, table.desc_width());
+        for text in item_details(product, &captions) {
+            desc_lines.extend(measurer.wrap(&text, theme.body.size, table.desc_width()));
+        }
+        if let Some(reason) = product
+            .tax_exempt_reason
+            .as_deref()
+            .filter(|_| product.tax_category.requires_exemption_reason())
+        {
+            desc_lines.extend(measurer.wrap(reason, theme.body.size, table.desc_width()));
+        }
+        if let Some(base) = product.price_base_quantity {
+            let text = format!(
+                "{} {} {}",
+                captions.get(Label::PricePer),
+                format_number(&invoice.locale, base),
+                unit
+            );
+            desc_lines.extend(measurer.wrap(&text, theme.body.size, table.desc_width()));
+        }
+        for entry in &product.allowances_charges {
+            let text = format!(
+                "{} {}",
+                allowance_charge_label(entry, &captions),
+                format_money(&invoice.locale, &entry.signed_amount_on(base))
+            );
+            desc_lines.extend(measurer.wrap(&text, theme.body.size, table.desc_width()));
+        }
+        let mut desc_lines = desc_lines.into_iter().peekable();
+        let mut first_line = true;
+
+        while desc_lines.peek().is_some() {
+            // Rows that do not fit move to the next page as a whole; only descriptions
+            // longer than a full page are split line by line.
+            let remaining = desc_lines.len();
+            let needed = table.row + table.line * (remaining - 1) as f32;
+            let available = page_bottom - carry_over_height - y;
+            let fits = if needed <= available {
+                remaining
+            } else if rows_on_page == 0 {
+                (((available - table.row) / table.line) as usize + 1).min(remaining)
+            } else {
+                0
+            };
+
+            if fits == 0 {
+                y += gap / 2.0;
+                canvas.line(margin_left, right_edge, y, theme.rule);
+                y += table.row;
+                table.draw_amount_line(
+                    &mut canvas,
+                    y,
+                    &format!("{}:", captions.get(Label::SubtotalCarriedOver)),
+                    &format_money(&invoice.locale, &running_total),
+                );
+
+                canvas.new_page();
+                y = table.draw_header(&mut canvas, theme.margins.top, right_edge, &captions);
+                table.draw_amount_line(
+                    &mut canvas,
+                    y,
+                    &format!("{}:", captions.get(Label::CarriedForward)),
+                    &format_money(&invoice.locale, &running_total),
+                );
+                y += table.row + gap / 2.0;
+                rows_on_page = 0;
+                continue;
+            }
+
+            if first_line {
+                running_total += line_total;
+                canvas.text(
+                    table.qty,
+                    y,
+                    theme.body,
+                    format!(
+                        "{} {}",
+                        format_number(&invoice.locale, product.quantity),
+                        unit
+                    ),
+                );
+                canvas.text(
+                    table.price,
+                    y,
+                    theme.body,
+                    format_price(
+                        &invoice.locale,
+                        &Money::new(product.cost_per_unit, invoice.currency),
+                    ),
+                );
+                canvas.text(
+                    table.total,
+                    y,
+                    theme.body,
+                    format_money(&invoice.locale, &line_total),
+                );
+                first_line = false;
+            }
+
+            for (i, line) in desc_lines.by_ref().take(fits).enumerate() {
+                canvas.text(table.desc, y + table.line * i as f32, theme.body, line);
+            }
+            y += table.row + table.line * (fits - 1) as f32;
+            rows_on_page += 1;
+        }
+    }
 
-    y += 15.0;
-    surface.draw_text(
-        Point::from_xy(details_x, y),
-        font.clone(),
-        10.0,
-        &format!("Date: {}", invoice.locale.format_date(&invoice.date)),
-        false,
-        TextDirection::Auto,
-    );
+    y += gap / 2.0;
+    canvas.line(margin_left, right_edge, y, theme.rule);
+
+    // Totals and payment information stay together when they fit on one page; longer
+    // blocks continue row by row on the following pages.
+    let visible_taxes: Vec<_> = summary
+        .tax_subtotals
+        .iter()
+        .filter(|t| t.category != TaxCategory::Standard || !t.rate.is_zero())
+        .collect();
+    let payment_lines = invoice.payment_info.as_ref().map(|p| p.len());
+    let adjustment_rows = match invoice.allowances_charges.len() {
+        0 => 0,
+        n => n + 1,
+    };
+    // Payments already received, listed between the total and the amount due.
+    let mut settled: Vec<(String, Money)> = Vec::new();
+    if let Some(amount) = invoice.prepaid_amount {
+        settled.push((
+            format!("{}:", captions.get(Label::AlreadyPaid)),
+            -Money::new(amount, invoice.currency),
+        ));
+    }
+    for payment in &invoice.payments {
+        let date = invoice.locale.format_date(&payment.date);
+        let paid = captions.get(Label::Paid);
+        let label = match &payment.reference {
+            Some(reference) => format!("{} {} ({}):", paid, date, reference),
+            None => format!("{} {}:", paid, date),
+        };
+        settled.push((label, -Money::new(payment.amount, invoice.currency)));
+    }
+    if invoice.rounding_amount.is_some() {
+        settled.push((
+            format!("{}:", captions.get(Label::Rounding)),
+            summary.rounding,
+        ));
+    }
+    // The total is followed by a rule and the next caption at the height of its row.
+    let total_rule_height = gap / 2.0 + theme.total.line_height();
+    let settled_height = match settled.len() {
+        0 => 0.0,
+        n => gap / 2.0 + table.row * n as f32 + total_rule_height,
+    };
+    // Why no VAT is charged, as required on the document for exempt categories.
+    let exemption_notes: Vec<String> = summary
+        .tax_subtotals
+        .iter()
+        .filter(|t| t.category.requires_exemption_reason())
+        .flat_map(|t| {
+            measurer.wrap(
+                &exemption_note(t, &captions),
+                theme.body.size,
+                right_edge - margin_left,
+            )
+        })
+        .collect();
+    let notes_height = match exemption_notes.len() {
+        0 => 0.0,
+        n => gap + theme.body.line_height() * n as f32,
+    };
+    let closing_height = 2.0 * gap
+        + table.row * (1 + adjustment_rows + visible_taxes.len()) as f32
+        + total_rule_height
+        + settled_height
+        + notes_height
+        + 4.0 * gap
+        + payment_lines.map_or(0.0, |n| {
+            theme.caption.row_height() + theme.body.line_height() * n as f32
+        });
+    if y + closing_height > page_bottom {
+        canvas.new_page();
+        y = theme.margins.top;
+    }
+    // Where a row starting at `y` goes, with `below` more points that must fit with it.
+    let fit = |canvas: &mut Canvas, y: f32, below: f32| {
+        if y + below > page_bottom {
+            canvas.new_page();
+            theme.margins.top
+        } else {
+            y
+        }
+    };
+
+    // Totals section
+    y += 2.0 * gap;
 
-    y += 15.0;
-    surface.draw_text(
-        Point::from_xy(details_x, y),
-        font.clone(),
-        10.0,
-        &format!("Due: {}", invoice.locale.format_date(&invoice.payment_due)),
-        false,
-        TextDirection::Auto,
+    table.draw_amount_line(
+        &mut canvas,
+        y,
+        &format!("{}:", captions.get(Label::Subtotal)),
+        &format_money(&invoice.locale, &summary.subtotal),
     );
 
-    y += 40.0;
+    y += table.row;
+
+    // Invoice level allowances and charges
+    if !invoice.allowances_charges.is_empty() {
+        for entry in &invoice.allowances_charges {
+            let base = invoice.allowance_charge_base(entry);
+            y = fit(&mut canvas, y, 0.0);
+            table.draw_amount_line(
+                &mut canvas,
+                y,
+                &allowance_charge_label(entry, &captions),
+                &format_money(&invoice.locale, &entry.signed_amount_on(base)),
+            );
+            y += table.row;
+        }
+        y = fit(&mut canvas, y, 0.0);
+        table.draw_amount_line(
+            &mut canvas,
+            y,
+            &format!("{}:", captions.get(Label::NetTotal)),
+            &format_money(&invoice.locale, &summary.net_total),
+        );
+        y += table.row;
+    }
 
-    // Seller and Buyer sections
-    let col1_x = margin_left;
-    let col2_x = margin_left + 280.0;
+    // Tax breakdown
+    for tax in visible_taxes {
+        y = fit(&mut canvas, y, 0.0);
+        table.draw_amount_line(
+            &mut canvas,
+            y,
+            &tax_label(tax.category, tax.rate, &captions),
+            &format_money(&invoice.locale, &tax.tax_amount),
+        );
 
-    // FROM section
-    surface.draw_text(
-        Point::from_xy(col1_x, y),
-        font.clone(),
-        11.0,
-        "FROM:",
-        false,
-        TextDirection::Auto,
-    );
+        y += table.row;
+    }
 
-    y += 18.0;
-    surface.draw_text(
-        Point::from_xy(col1_x, y),
-        font.clone(),
-        10.0,
-        &invoice.seller.name,
-        false,
-        TextDirection::Auto,
-    );
+    y = fit(&mut canvas, y + gap / 2.0, theme.total.line_height());
+    canvas.line(table.label, right_edge, y, theme.rule);
 
-    y += 14.0;
-    surface.draw_text(
-        Point::from_xy(col1_x, y),
-        font.clone(),
-        9.0,
-        &format!(
-            "{} {}",
-            invoice.seller.address.street, invoice.seller.address.house_number
-        ),
-        false,
-        TextDirection::Auto,
-    );
+    y += theme.total.line_height();
 
-    y += 12.0;
-    surface.draw_text(
-        Point::from_xy(col1_x, y),
-        font.clone(),
-        9.0,
-        &format!(
-            "{} {}",
-            invoice.seller.address.code, invoice.seller.address.town
-        ),
-        false,
-        TextDirection::Auto,
+    // Total
+    canvas.text(
+        table.label,
+        y,
+        theme.total,
+        format!("{}:", captions.get(Label::Total)),
     );
-
-    y += 12.0;
-    surface.draw_text(
-        Point::from_xy(col1_x, y),
-        font.clone(),
-        9.0,
-        &format!("VAT: {}", invoice.seller.vat_id),
-        false,
-        TextDirection::Auto,
+    canvas.text(
+        table.total,
+        y,
+        theme.total,
+        format_money(&invoice.locale, &summary.total),
     );
 
-    // BILL TO section (at same height as FROM)
-    let mut y_bill_to = margin_top + 40.0;
-
-    surface.draw_text(
-        Point::from_xy(col2_x, y_bill_to),
-        font.clone(),
-        11.0,
-        "BILL TO:",
-        false,
-        TextDirection::Auto,
-    );
+    if !settled.is_empty() {
+        y += gap / 2.0;
+        for (label, amount) in &settled {
+            y = fit(&mut canvas, y + table.row, 0.0);
+            table.draw_amount_line(
+                &mut canvas,
+                y,
+                label,
+                &format_money(&invoice.locale, amount),
+            );
+        }
 
-    y_bill_to += 18.0;
-    surface.draw_text(
-        Point::from_xy(col2_x, y_bill_to),
-        font.clone(),
-        10.0,
-        &invoice.buyer.name,
-        false,
-        TextDirection::Auto,
-    );
+        y = fit(&mut canvas, y + gap / 2.0, theme.total.line_height());
+        canvas.line(table.label, right_edge, y, theme.rule);
 
-    y_bill_to += 14.0;
-    surface.draw_text(
-        Point::from_xy(col2_x, y_bill_to),
-        font.clone(),
-        9.0,
-        &format!(
-            "{} {}",
-            invoice.buyer.address.street, invoice.buyer.address.house_number
-        ),
-        false,
-        TextDirection::Auto,
-    );
+        y += theme.total.line_height();
+        canvas.text(
+            table.label,
+            y,
+            theme.total,
+            format!("{}:", captions.get(Label::AmountDue)),
+        );
+        canvas.text(
+            table.total,
+            y,
+            theme.total,
+            format_money(&invoice.locale, &summary.amount_due),
+        );
+    }
 
-    y_bill_to += 12.0;
-    surface.draw_text(
-        Point::from_xy(col2_x, y_bill_to),
-        font.clone(),
-        9.0,
-        &format!(
-            "{} {}",
-            invoice.buyer.address.code, invoice.buyer.address.town
-        ),
-        false,
-        TextDirection::Auto,
-    );
+    if !exemption_notes.is_empty() {
+        y += gap;
+        for note in exemption_notes {
+            y = fit(&mut canvas, y + theme.body.line_height(), 0.0);
+            canvas.text(margin_left, y, theme.body, note);
+        }
+    }
 
-    y_bill_to += 12.0;
-    surface.draw_text(
-        Point::from_xy(col2_x, y_bill_to),
-        font.clone(),
-        9.0,
-        &invoice.buyer.email,
-        false,
-        TextDirection::Auto,
-    );
+    // Payment info
+    if let Some(payment_info) = &invoice.payment_info {
+        // The caption stays with the first line.
+        y = fit(&mut canvas, y + 4.0 * gap, theme.caption.row_height());
+        canvas.text(
+            margin_left,
+            y,
+            theme.caption,
+            format!("{}:", captions.get(Label::PaymentInformation)),
+        );
 
-    // Move to next section
-    y = y.max(y_bill_to) + 30.0;
+        y += theme.caption.row_height();
+        for (key, value) in payment_info {
+            y = fit(&mut canvas, y, 0.0);
+            canvas.text(margin_left, y, theme.body, format!("{}: {}", key, value));
+            y += theme.body.line_height();
+        }
+    }
 
-    // Horizontal line
-    draw_line(&mut surface, margin_left, page_width - margin_right, y)?;
-
-    y += 20.0;
-
-    // Table headers
-    let col_desc = margin_left;
-    let col_qty = margin_left + 320.0;
-    let col_price = margin_left + 380.0;
-    let col_total = margin_left + 450.0;
-
-    surface.draw_text(
-        Point::from_xy(col_desc, y),
-        font.clone(),
-        10.0,
-        "Description",
-        false,
-        TextDirection::Auto,
+    let slots = SlotContext {
+        invoice,
+        captions: &captions,
+        measurer: &small_measurer,
+        theme,
+    };
+    slots.draw(&mut canvas, &theme.header, theme.margins.top / 2.0);
+    slots.draw(
+        &mut canvas,
+        &theme.footer,
+        page_bottom + theme.margins.bottom / 2.0,
     );
 
-    surface.draw_text(
-        Point::from_xy(col_qty, y),
-        font.clone(),
-        10.0,
-        "Qty",
-        false,
-        TextDirection::Auto,
-    );
+    canvas.render(&mut doc, &fonts)?;
 
-    surface.draw_text(
-        Point::from_xy(col_price, y),
-        font.clone(),
-        10.0,
-        "Unit Price",
-        false,
-        TextDirection::Auto,
-    );
+    if let Some(hybrid) = &options.hybrid {
+        let title = format!("{} {}", captions.get(Label::DocumentTitle), invoice.number);
+        let description = format!("{} {}", title, captions.get(Label::XmlAttachment));
+        doc.set_metadata(
+            Metadata::new()
+                .title(title)
+                .authors(vec![invoice.seller.name.clone()])
+                .creation_date(facturx::pdf_date(&invoice.date)),
+        );
+        let file = hybrid
+            .embedded_file(invoice, description)
+            .map_err(InvoiceError::Hybrid)?;
+        doc.embed_file(file)
+            .ok_or_else(|| InvoiceError::Hybrid("Embedded file name is not unique".to_string()))?;
+    }
 
-    surface.draw_text(
-        Point::from_xy(col_total, y),
-        font.clone(),
-        10.0,
-        "Total",
-        false,
-        TextDirection::Auto,
-    );
+    let pdf_bytes = doc.finish()?;
+    match &options.hybrid {
+        Some(hybrid) => hybrid
+            .add_xmp_extension(pdf_bytes)
+            .map_err(InvoiceError::Hybrid),
+        None => Ok(pdf_bytes),
+    }
+}
 
-    y += 12.0;
-    draw_line(&mut surface, margin_left, page_width - margin_right, y)?;
+/// Delivery, payment method and the free-form details shown below the parties.
+fn metadata_lines(invoice: &Invoice, captions: &Captions) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(date) = &invoice.delivery_date {
+        lines.push(format!(
+            "{}: {}",
+            captions.get(Label::DeliveryDate),
+            invoice.locale.format_date(date)
+        ));
+    }
+    if let Some(delivery_type) = &invoice.delivery_type {
+        lines.push(format!(
+            "{}: {}",
+            captions.get(Label::DeliveryType),
+            delivery_type
+        ));
+    }
+    if let Some(payment_type) = &invoice.payment_type {
+        lines.push(format!(
+            "{}: {}",
+            captions.get(Label::PaymentMethod),
+            payment_type
+        ));
+    }
+    for (key, value) in invoice.extra_info.iter().flatten() {
+        lines.push(format!("{}: {}", key, value));
+    }
+    lines
+}
+
+/// Website, phone and email of the seller, printed at the bottom of every page.
+fn seller_contact(seller: &Seller, captions: &Captions) -> String {
+    let mut parts = Vec::new();
+    if !seller.website.trim().is_empty() {
+        parts.push(seller.website.clone());
+    }
+    if let Some(phone) = seller.phone.as_deref().filter(|p| !p.trim().is_empty()) {
+        parts.push(format!("{} {}", captions.get(Label::Phone), phone));
+    }
+    if let Some(email) = seller.email.as_deref().filter(|e| !e.trim().is_empty()) {
+        parts.push(email.to_string());
+    }
+    parts.join(" · ")
+}
 
-    y += 15.0;
+/// What header and footer slots need to render their contents.
+struct SlotContext<'a> {
+    invoice: &'a Invoice,
+    captions: &'a Captions<'a>,
+    measurer: &'a TextMeasurer<'a>,
+    theme: &'a Theme,
+}
 
-    // Products
-    for product in &invoice.products {
-        let line_total = product.units as f64 * product.cost_per_unit;
+impl SlotContext<'_> {
+    /// Draws the slots at `y` on every page; the left slot wraps before the right one.
+    fn draw(&self, canvas: &mut Canvas, slots: &Slots, y: f32) {
+        let style = self.theme.small;
+        let right_x = canvas.width() - self.theme.margins.right - self.theme.columns.slot;
+        let left_width = right_x - self.theme.columns.gap - self.theme.margins.left;
+        let page_count = canvas.page_count();
+        for page in 0..page_count {
+            if let Some(text) = self.text(&slots.left, page, page_count) {
+                for (i, line) in self
+                    .measurer
+                    .wrap(&text, style.size, left_width)
+                    .into_iter()
+                    .enumerate()
+                {
+                    let line_y = y + style.line_height() * i as f32;
+                    canvas.text_on_page(page, self.theme.margins.left, line_y, style, line);
+                }
+            }
+            if let Some(text) = self.text(&slots.right, page, page_count) {
+                canvas.text_on_page(page, right_x, y, style, text);
+            }
+        }
+    }
 
-        // Truncate long descriptions
-        let desc = if product.description.len() > 45 {
-            format!("{}...", &product.description[..42])
-        } else {
-            product.description.clone()
+    fn text(&self, slot: &Slot, page: usize, page_count: usize) -> Option<String> {
+        let text = match slot {
+            Slot::Empty => return None,
+            Slot::Text(text) => text.clone(),
+            Slot::SellerContact => seller_contact(&self.invoice.seller, self.captions),
+            Slot::PageNumber if page_count > 1 => format!(
+                "{} {} / {}",
+                self.captions.get(Label::Page),
+                page + 1,
+                page_count
+            ),
+            Slot::PageNumber => return None,
         };
+        Some(text).filter(|t| !t.trim().is_empty())
+    }
+}
 
-        surface.draw_text(
-            Point::from_xy(col_desc, y),
-            font.clone(),
-            9.0,
-            &desc,
-            false,
-            TextDirection::Auto,
-        );
+/// The exemption reasons of a VAT category; the category's default reason is replaced
+/// by its localized caption.
+fn exemption_note(subtotal: &TaxSubtotal, captions: &Captions) -> String {
+    let caption = captions.get(Label::tax_category(subtotal.category));
+    let default = subtotal.category.default_exemption_reason();
+    let reasons: Vec<&str> = subtotal
+        .exemption_reasons
+        .iter()
+        .map(|reason| {
+            if Some(reason.as_str()) == default {
+                caption
+            } else {
+                reason
+            }
+        })
+        .collect();
+    if reasons.is_empty() {
+        caption.to_string()
+    } else {
+        reasons.join("; ")
+    }
+}
 
-        surface.draw_text(
-            Point::from_xy(col_qty, y),
-            font.clone(),
-            9.0,
-            &product.units.to_string(),
-            false,
-            TextDirection::Auto,
-        );
+/// Captions of one invoice: the user's translations for its locale over the built-in ones.
+struct Captions<'a> {
+    translations: &'a Translations,
+    locale: &'a Locale,
+}
 
-        surface.draw_text(
-            Point::from_xy(col_price, y),
-            font.clone(),
-            9.0,
-            &format!("${:.2}", product.cost_per_unit),
-            false,
-            TextDirection::Auto,
-        );
+impl Captions<'_> {
+    fn get(&self, label: Label) -> &str {
+        self.translations.text(self.locale, label)
+    }
 
-        surface.draw_text(
-            Point::from_xy(col_total, y),
-            font.clone(),
-            9.0,
-            &format!("${:.2}", line_total),
-            false,
-            TextDirection::Auto,
-        );
+    fn unit<'u>(&'u self, unit: &'u Unit) -> &'u str {
+        self.translations.unit(self.locale, unit)
+    }
+}
+
+/// Taxed categories show their rate, the others name why no VAT is charged.
+fn tax_label(category: TaxCategory, rate: Decimal, captions: &Captions) -> String {
+    let label = captions.get(Label::tax_category(category));
+    match category {
+        TaxCategory::Standard | TaxCategory::ZeroRated => {
+            format!("{} ({}):", label, format_percent(captions.locale, rate))
+        }
+        _ => format!("{}:", label),
+    }
+}
 
-        y += 14.0;
+/// The reason of an allowance or charge, with its percentage if it has one.
+fn allowance_charge_label(entry: &AllowanceCharge, captions: &Captions) -> String {
+    let label = match (&entry.reason, entry.is_charge()) {
+        (Some(reason), _) => reason,
+        (None, false) => captions.get(Label::Discount),
+        (None, true) => captions.get(Label::Surcharge),
+    };
+    match entry.percentage() {
+        Some(rate) => format!("{} ({}):", label, format_percent(captions.locale, rate)),
+        None => format!("{}:", label),
     }
+}
 
-    y += 5.0;
-    draw_line(&mut surface, margin_left, page_width - margin_right, y)?;
+/// Sub-lines below a product description: identifiers and origin, attributes and the
+/// line note.
+fn item_details(product: &Product, captions: &Captions) -> Vec<String> {
+    let mut ids = Vec::new();
+    if let Some(id) = &product.seller_item_id {
+        ids.push(format!("{} {}", captions.get(Label::ItemNumber), id));
+    }
+    if let Some(id) = &product.buyer_item_id {
+        ids.push(format!("{} {}", captions.get(Label::BuyerItemNumber), id));
+    }
+    if let Some(id) = &product.standard_item_id {
+        let scheme = if id.is_gtin() { "GTIN" } else { "ID" };
+        ids.push(format!("{} {}", scheme, id.id));
+    }
+    if let Some(country) = product.origin_country {
+        ids.push(format!(
+            "{}: {}",
+            captions.get(Label::Origin),
+            country.name()
+        ));
+    }
+    let attributes = product
+        .attributes
+        .iter()
+        .map(|a| format!("{}: {}", a.name, a.value))
+        .collect::<Vec<_>>();
+    [ids.join(", "), attributes.join(", ")]
+        .into_iter()
+        .chain(product.note.clone())
+        .filter(|text| !text.is_empty())
+        .collect()
+}
 
-    // Totals section
-    y += 20.0;
-    let totals_label_x = col_price - 60.0;
-    let totals_value_x = col_total;
-
-    let (subtotal, tax_totals, total) = invoice.calculate_summary();
-
-    surface.draw_text(
-        Point::from_xy(totals_label_x, y),
-        font.clone(),
-        10.0,
-        "Subtotal:",
-        false,
-        TextDirection::Auto,
-    );
+/// Places the logo right-aligned above the invoice details, its top `logo_rise` title
+/// sizes above the title baseline.
+fn draw_logo(
+    canvas: &mut Canvas,
+    logo: &Logo,
+    right_edge: f32,
+    theme: &Theme,
+) -> Result<(), InvoiceError> {
+    let graphic = logo.load()?;
+    let (width, height) = logo.fitted_size(&graphic);
+    let x = right_edge - width;
+    let y = theme.margins.top - theme.title.size * theme.logo_rise;
+    match graphic {
+        LogoGraphic::Raster(image) => canvas.image(x, y, width, height, image),
+        LogoGraphic::Vector {
+            shapes,
+            width: natural_width,
+            ..
+        } => canvas.shapes(x, y, width / natural_width, shapes),
+    }
+    Ok(())
+}
+
+/// Draws title, invoice details and the seller/buyer blocks; returns the y below them.
+fn draw_header(canvas: &mut Canvas, invoice: &Invoice, captions: &Captions, theme: &Theme) -> f32 {
+    let mut y = theme.margins.top;
 
-    surface.draw_text(
-        Point::from_xy(totals_value_x, y),
-        font.clone(),
-        10.0,
-        &format!("${:.2}", subtotal),
-        false,
-        TextDirection::Auto,
+    // Title
+    canvas.text(
+        theme.margins.left,
+        y,
+        theme.title,
+        captions.get(Label::title(invoice.document_type)),
     );
 
-    y += 14.0;
+    y += theme.title.row_height();
+    let below_title = y;
+    let gap = theme.gap();
 
-    // Tax breakdown
-    for (rate, amount) in tax_totals {
-        surface.draw_text(
-            Point::from_xy(totals_label_x, y),
-            font.clone(),
-            10.0,
-            &format!("Tax ({:.0}%):", rate.0 * 100.0),
-            false,
-            TextDirection::Auto,
-        );
+    // Invoice details (right aligned)
+    let details_x = canvas.width() - theme.margins.right - theme.columns.details;
+    canvas.text(
+        details_x,
+        y,
+        theme.text,
+        format!("{}: {}", captions.get(Label::InvoiceNumber), invoice.number),
+    );
 
-        surface.draw_text(
-            Point::from_xy(totals_value_x, y),
-            font.clone(),
-            10.0,
-            &format!("${:.2}", amount),
-            false,
-            TextDirection::Auto,
-        );
+    y += theme.text.row_height();
+    canvas.text(
+        details_x,
+        y,
+        theme.text,
+        format!(
+            "{}: {}",
+            captions.get(Label::Date),
+            invoice.locale.format_date(&invoice.date)
+        ),
+    );
 
-        y += 14.0;
+    y += theme.text.row_height();
+    canvas.text(
+        details_x,
+        y,
+        theme.text,
+        format!(
+            "{}: {}",
+            captions.get(Label::Due),
+            invoice.locale.format_date(&invoice.payment_due)
+        ),
+    );
+
+    if let Some(reference) = &invoice.billing_reference {
+        y += theme.text.row_height();
+        let refers_to = captions.get(Label::RefersTo);
+        let text = match &reference.date {
+            Some(date) => format!(
+                "{}: #{} ({})",
+                refers_to,
+                reference.number,
+                invoice.locale.format_date(date)
+            ),
+            None => format!("{}: #{}", refers_to, reference.number),
+        };
+        canvas.text(details_x, y, theme.text, text);
     }
 
-    y += 5.0;
-    draw_line(&mut surface, totals_label_x, page_width - margin_right, y)?;
+    y += 4.0 * gap;
 
-    y += 15.0;
+    // Seller and Buyer sections
+    let col1_x = theme.margins.left;
+    let col2_x = theme.margins.left + theme.columns.buyer;
 
-    // Total
-    surface.draw_text(
-        Point::from_xy(totals_label_x, y),
-        font.clone(),
-        12.0,
-        "TOTAL:",
-        false,
-        TextDirection::Auto,
+    // FROM section
+    canvas.text(
+        col1_x,
+        y,
+        theme.heading,
+        format!("{}:", captions.get(Label::From)),
     );
 
-    surface.draw_text(
-        Point::from_xy(totals_value_x, y),
-        font.clone(),
-        12.0,
-        &format!("${:.2}", total),
-        false,
-        TextDirection::Auto,
+    y += theme.heading.row_height();
+    canvas.text(col1_x, y, theme.text, invoice.seller.name.clone());
+
+    // Cross-border invoices name the country in both address blocks.
+    let international = invoice.seller.address.country != invoice.buyer.address.country;
+    // The name takes a line of the text style, the lines below it of the body style.
+    let name_height = theme.text.line_height() - theme.body.line_height();
+    y += name_height;
+    for line in address_lines(&invoice.seller.address, international) {
+        y += theme.body.line_height();
+        canvas.text(col1_x, y, theme.body, line);
+    }
+
+    y += theme.body.line_height();
+    canvas.text(
+        col1_x,
+        y,
+        theme.body,
+        format!("{}: {}", captions.get(Label::VatId), invoice.seller.vat_id),
     );
 
-    // Payment info
-    y += 40.0;
-    if let Some(payment_info) = &invoice.payment_info {
-        surface.draw_text(
-            Point::from_xy(margin_left, y),
-            font.clone(),
-            10.0,
-            "Payment Information:",
-            false,
-            TextDirection::Auto,
-        );
+    let seller = &invoice.seller;
+    for line in registration_lines(
+        None,
+        seller.tax_registration_number.as_deref(),
+        seller.legal_registration_id.as_deref(),
+        seller.legal_form.as_deref(),
+        seller.electronic_address.as_ref(),
+        captions,
+    ) {
+        y += theme.body.line_height();
+        canvas.text(col1_x, y, theme.body, line);
+    }
 
-        y += 14.0;
-        for (key, value) in payment_info {
-            surface.draw_text(
-                Point::from_xy(margin_left, y),
-                font.clone(),
-                9.0,
-                &format!("{}: {}", key, value),
-                false,
-                TextDirection::Auto,
-            );
-            y += 12.0;
-        }
+    // BILL TO section (at same height as FROM)
+    let mut y_bill_to = below_title;
+
+    canvas.text(
+        col2_x,
+        y_bill_to,
+        theme.heading,
+        format!("{}:", captions.get(Label::BillTo)),
+    );
+
+    y_bill_to += theme.heading.row_height();
+    canvas.text(col2_x, y_bill_to, theme.text, invoice.buyer.name.clone());
+
+    y_bill_to += name_height;
+    for line in address_lines(&invoice.buyer.address, international) {
+        y_bill_to += theme.body.line_height();
+        canvas.text(col2_x, y_bill_to, theme.body, line);
+    }
+
+    y_bill_to += theme.body.line_height();
+    canvas.text(col2_x, y_bill_to, theme.body, invoice.buyer.email.clone());
+
+    let buyer = &invoice.buyer;
+    for line in registration_lines(
+        buyer.vat_id.as_deref(),
+        buyer.tax_registration_number.as_deref(),
+        buyer.legal_registration_id.as_deref(),
+        buyer.legal_form.as_deref(),
+        buyer.electronic_address.as_ref(),
+        captions,
+    ) {
+        y_bill_to += theme.body.line_height();
+        canvas.text(col2_x, y_bill_to, theme.body, line);
     }
 
-    surface.finish();
-    page.finish();
+    // Move to next section
+    y.max(y_bill_to) + 3.0 * gap
+}
 
-    let pdf_bytes = doc.finish().map_err(|e| format!("{:?}", e))?;
-    Ok(pdf_bytes)
+fn address_lines(address: &Address, with_country: bool) -> Vec<String> {
+    let mut lines = address.lines();
+    if with_country {
+        lines.push(address.country.name().to_uppercase());
+    }
+    lines
 }
 
-fn draw_line(
-    surface: &mut krilla::surface::Surface,
-    x1: f32,
-    x2: f32,
-    y: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut pb = krilla::geom::PathBuilder::new();
-    pb.move_to(x1, y);
-    pb.line_to(x2, y);
-    let path = pb.finish().ok_or("Failed to create line")?;
-
-    surface.set_stroke(Some(Stroke {
-        paint: rgb::Color::new(0, 0, 0).into(),
-        width: 1.0,
-        ..Default::default()
-    }));
-    surface.draw_path(&path);
+/// Identifiers printed below a party's address; only the fields that are set.
+fn registration_lines(
+    vat_id: Option<&str>,
+    tax_number: Option<&str>,
+    registration_id: Option<&str>,
+    legal_form: Option<&str>,
+    electronic_address: Option<&ElectronicAddress>,
+    captions: &Captions,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(vat_id) = vat_id {
+        lines.push(format!("{}: {}", captions.get(Label::VatId), vat_id));
+    }
+    if let Some(number) = tax_number {
+        lines.push(format!("{}: {}", captions.get(Label::TaxNumber), number));
+    }
+    if let Some(id) = registration_id {
+        lines.push(format!("{}: {}", captions.get(Label::Registration), id));
+    }
+    if let Some(form) = legal_form {
+        lines.push(form.to_string());
+    }
+    if let Some(address) = electronic_address {
+        lines.push(format!(
+            "{}: {} ({})",
+            captions.get(Label::EInvoice),
+            address.value,
+            address.scheme
+        ));
+    }
+    lines
+}
 
-    Ok(())
+/// Column positions and styles of the product table.
+struct Table {
+    desc: f32,
+    qty: f32,
+    price: f32,
+    total: f32,
+    /// Left edge of the captions in the totals section.
+    label: f32,
+    /// Distance between rows, and between the wrapped lines of a description.
+    row: f32,
+    line: f32,
+    gap: f32,
+    caption: TextStyle,
+    text: TextStyle,
+    rule: LineStyle,
+}
+
+impl Table {
+    fn new(theme: &Theme) -> Self {
+        let columns = &theme.columns;
+        let qty = theme.margins.left + columns.description;
+        Table {
+            desc: theme.margins.left,
+            qty,
+            price: qty + columns.quantity,
+            total: qty + columns.quantity + columns.unit_price,
+            label: qty + columns.quantity - columns.totals_label,
+            row: theme.text.row_height(),
+            line: theme.body.line_height(),
+            gap: columns.gap,
+            caption: theme.caption,
+            text: theme.text,
+            rule: theme.rule,
+        }
+    }
+
+    /// Width available to the wrapped description, leaving a gap before the quantity.
+    fn desc_width(&self) -> f32 {
+        self.qty - self.desc - self.gap
+    }
+
+    /// Draws the column captions and the rule below them; returns the y of the first row.
+    fn draw_header(
+        &self,
+        canvas: &mut Canvas,
+        y: f32,
+        right_edge: f32,
+        captions: &Captions,
+    ) -> f32 {
+        canvas.text(self.desc, y, self.caption, captions.get(Label::Description));
+        canvas.text(self.qty, y, self.caption, captions.get(Label::Quantity));
+        canvas.text(self.price, y, self.caption, captions.get(Label::UnitPrice));
+        canvas.text(self.total, y, self.caption, captions.get(Label::LineTotal));
+
+        let rule_y = y + self.caption.line_height();
+        canvas.line(self.desc, right_edge, rule_y, self.rule);
+
+        rule_y + self.row
+    }
+
+    /// A caption in the totals label column with its amount in the total column.
+    fn draw_amount_line(&self, canvas: &mut Canvas, y: f32, label: &str, amount: &str) {
+        canvas.text(self.label, y, self.text, label);
+        canvas.text(self.total, y, self.text, amount);
+    }
 }
@@ -0,0 +1,707 @@
+use crate::models::{DocumentType, TaxCategory, Unit};
+use locale_rs::Locale;
+use std::collections::HashMap;
+
+/// A caption printed on the PDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Label {
+    InvoiceTitle,
+    CreditNoteTitle,
+    CorrectedInvoiceTitle,
+    PrepaymentInvoiceTitle,
+    SelfBilledInvoiceTitle,
+    InvoiceNumber,
+    Date,
+    Due,
+    RefersTo,
+    DeliveryDate,
+    DeliveryType,
+    PaymentMethod,
+    From,
+    BillTo,
+    VatId,
+    TaxNumber,
+    Registration,
+    EInvoice,
+    Description,
+    Quantity,
+    UnitPrice,
+    LineTotal,
+    PricePer,
+    ItemNumber,
+    BuyerItemNumber,
+    Origin,
+    SubtotalCarriedOver,
+    CarriedForward,
+    Subtotal,
+    Discount,
+    Surcharge,
+    NetTotal,
+    Tax,
+    Exempt,
+    ReverseCharge,
+    IntraCommunity,
+    Export,
+    OutOfScope,
+    Total,
+    AlreadyPaid,
+    Paid,
+    Rounding,
+    AmountDue,
+    PaymentInformation,
+    Page,
+    Phone,
+    /// Document title in the PDF metadata, followed by the invoice number.
+    DocumentTitle,
+    /// Description of the embedded e-invoice, following the document title.
+    XmlAttachment,
+    /// Short label of a unit of measure; units without one are printed as their code.
+    Unit(Unit),
+}
+
+/// Built-in captions in English, German, French, Italian, Spanish and Dutch, followed by
+/// the labels of the common units.
+const BUILTIN: &[(Label, [&str; 6])] = &[
+    (
+        Label::InvoiceTitle,
+        [
+            "INVOICE", "RECHNUNG", "FACTURE", "FATTURA", "FACTURA", "FACTUUR",
+        ],
+    ),
+    (
+        Label::CreditNoteTitle,
+        [
+            "CREDIT NOTE",
+            "RECHNUNGSKORREKTUR",
+            "AVOIR",
+            "NOTA DI CREDITO",
+            "FACTURA RECTIFICATIVA",
+            "CREDITNOTA",
+        ],
+    ),
+    (
+        Label::CorrectedInvoiceTitle,
+        [
+            "CORRECTED INVOICE",
+            "KORRIGIERTE RECHNUNG",
+            "FACTURE RECTIFIÉE",
+            "FATTURA CORRETTIVA",
+            "FACTURA CORREGIDA",
+            "GECORRIGEERDE FACTUUR",
+        ],
+    ),
+    (
+        Label::PrepaymentInvoiceTitle,
+        [
+            "PREPAYMENT INVOICE",
+            "VORAUSZAHLUNGSRECHNUNG",
+            "FACTURE D'ACOMPTE",
+            "FATTURA DI ACCONTO",
+            "FACTURA DE ANTICIPO",
+            "VOORSCHOTFACTUUR",
+        ],
+    ),
+    (
+        Label::SelfBilledInvoiceTitle,
+        [
+            "SELF-BILLED INVOICE",
+            "GUTSCHRIFT",
+            "AUTOFACTURATION",
+            "AUTOFATTURA",
+            "AUTOFACTURA",
+            "ZELFFACTURATIE",
+        ],
+    ),
+    (
+        Label::InvoiceNumber,
+        [
+            "Invoice #",
+            "Rechnungsnr.",
+            "Facture n°",
+            "Fattura n.",
+            "Factura n.º",
+            "Factuurnr.",
+        ],
+    ),
+    (
+        Label::Date,
+        ["Date", "Datum", "Date", "Data", "Fecha", "Datum"],
+    ),
+    (
+        Label::Due,
+        [
+            "Due",
+            "Fällig",
+            "Échéance",
+            "Scadenza",
+            "Vencimiento",
+            "Vervaldatum",
+        ],
+    ),
+    (
+        Label::RefersTo,
+        [
+            "Refers to",
+            "Bezug",
+            "Se rapporte à",
+            "Riferita a",
+            "Referida a",
+            "Betreft",
+        ],
+    ),
+    (
+        Label::DeliveryDate,
+        [
+            "Delivery date",
+            "Lieferdatum",
+            "Date de livraison",
+            "Data di consegna",
+            "Fecha de entrega",
+            "Leverdatum",
+        ],
+    ),
+    (
+        Label::DeliveryType,
+        [
+            "Delivery",
+            "Versandart",
+            "Livraison",
+            "Spedizione",
+            "Envío",
+            "Verzending",
+        ],
+    ),
+    (
+        Label::PaymentMethod,
+        [
+            "Payment method",
+            "Zahlungsart",
+            "Mode de paiement",
+            "Metodo di pagamento",
+            "Forma de pago",
+            "Betaalwijze",
+        ],
+    ),
+    (Label::From, ["FROM", "VON", "DE", "DA", "DE", "VAN"]),
+    (
+        Label::BillTo,
+        [
+            "BILL TO",
+            "RECHNUNG AN",
+            "FACTURER À",
+            "FATTURARE A",
+            "FACTURAR A",
+            "FACTUUR AAN",
+        ],
+    ),
+    (
+        Label::VatId,
+        ["VAT", "USt-IdNr.", "TVA", "P. IVA", "NIF-IVA", "Btw-nr."],
+    ),
+    (
+        Label::TaxNumber,
+        [
+            "Tax No.",
+            "Steuernr.",
+            "N° fiscal",
+            "Cod. fiscale",
+            "NIF",
+            "Fiscaal nr.",
+        ],
+    ),
+    (
+        Label::Registration,
+        [
+            "Registration",
+            "Register",
+            "Immatriculation",
+            "Registro imprese",
+            "Registro mercantil",
+            "KvK",
+        ],
+    ),
+    (
+        Label::EInvoice,
+        [
+            "E-Invoice",
+            "E-Rechnung",
+            "Facture électronique",
+            "Fattura elettronica",
+            "Factura electrónica",
+            "E-factuur",
+        ],
+    ),
+    (
+        Label::Description,
+        [
+            "Description",
+            "Beschreibung",
+            "Désignation",
+            "Descrizione",
+            "Descripción",
+            "Omschrijving",
+        ],
+    ),
+    (
+        Label::Quantity,
+        ["Qty", "Menge", "Qté", "Q.tà", "Cant.", "Aantal"],
+    ),
+    (
+        Label::UnitPrice,
+        [
+            "Unit Price",
+            "Einzelpreis",
+            "Prix unitaire",
+            "Prezzo unitario",
+            "Precio unitario",
+            "Stukprijs",
+        ],
+    ),
+    (
+        Label::LineTotal,
+        ["Total", "Gesamt", "Total", "Totale", "Total", "Totaal"],
+    ),
+    (
+        Label::PricePer,
+        [
+            "Price per",
+            "Preis pro",
+            "Prix par",
+            "Prezzo per",
+            "Precio por",
+            "Prijs per",
+        ],
+    ),
+    (
+        Label::ItemNumber,
+        [
+            "Item no.",
+            "Art.-Nr.",
+            "Réf.",
+            "Cod. art.",
+            "Ref.",
+            "Art.nr.",
+        ],
+    ),
+    (
+        Label::BuyerItemNumber,
+        [
+            "Your item no.",
+            "Ihre Art.-Nr.",
+            "Votre réf.",
+            "Vs. cod. art.",
+            "Su ref.",
+            "Uw art.nr.",
+        ],
+    ),
+    (
+        Label::Origin,
+        [
+            "Origin", "Herkunft", "Origine", "Origine", "Origen", "Herkomst",
+        ],
+    ),
+    (
+        Label::SubtotalCarriedOver,
+        [
+            "Subtotal carried over",
+            "Zwischensumme Übertrag",
+            "Sous-total à reporter",
+            "Subtotale da riportare",
+            "Suma y sigue",
+            "Subtotaal transport",
+        ],
+    ),
+    (
+        Label::CarriedForward,
+        [
+            "Carried forward",
+            "Übertrag",
+            "Report",
+            "Riporto",
+            "Suma anterior",
+            "Transport",
+        ],
+    ),
+    (
+        Label::Subtotal,
+        [
+            "Subtotal",
+            "Zwischensumme",
+            "Sous-total",
+            "Subtotale",
+            "Subtotal",
+            "Subtotaal",
+        ],
+    ),
+    (
+        Label::Discount,
+        [
+            "Discount",
+            "Rabatt",
+            "Remise",
+            "Sconto",
+            "Descuento",
+            "Korting",
+        ],
+    ),
+    (
+        Label::Surcharge,
+        [
+            "Surcharge",
+            "Zuschlag",
+            "Supplément",
+            "Maggiorazione",
+            "Recargo",
+            "Toeslag",
+        ],
+    ),
+    (
+        Label::NetTotal,
+        [
+            "Net total",
+            "Nettobetrag",
+            "Total HT",
+            "Totale imponibile",
+            "Base imponible",
+            "Totaal excl. btw",
+        ],
+    ),
+    (Label::Tax, ["Tax", "MwSt.", "TVA", "IVA", "IVA", "Btw"]),
+    (
+        Label::Exempt,
+        [
+            "Exempt from VAT",
+            "Steuerfrei",
+            "Exonéré de TVA",
+            "Esente IVA",
+            "Exento de IVA",
+            "Vrijgesteld van btw",
+        ],
+    ),
+    (
+        Label::ReverseCharge,
+        [
+            "Reverse charge",
+            "Steuerschuldumkehr",
+            "Autoliquidation",
+            "Inversione contabile",
+            "Inversión sujeto pasivo",
+            "Btw verlegd",
+        ],
+    ),
+    (
+        Label::IntraCommunity,
+        [
+            "Intra-community supply",
+            "Innergem. Lieferung",
+            "Livraison intracom.",
+            "Cessione intracom.",
+            "Entrega intracomunitaria",
+            "Intracomm. levering",
+        ],
+    ),
+    (
+        Label::Export,
+        [
+            "Export outside the EU",
+            "Ausfuhrlieferung",
+            "Exportation hors UE",
+            "Esportazione extra UE",
+            "Exportación extra UE",
+            "Export buiten de EU",
+        ],
+    ),
+    (
+        Label::OutOfScope,
+        [
+            "Not subject to VAT",
+            "Nicht steuerbar",
+            "Non soumis à la TVA",
+            "Fuori campo IVA",
+            "No sujeto a IVA",
+            "Niet onderworpen aan btw",
+        ],
+    ),
+    (
+        Label::Total,
+        [
+            "TOTAL",
+            "GESAMTBETRAG",
+            "TOTAL TTC",
+            "TOTALE",
+            "TOTAL",
+            "TOTAAL",
+        ],
+    ),
+    (
+        Label::AlreadyPaid,
+        [
+            "Already paid",
+            "Bereits bezahlt",
+            "Déjà payé",
+            "Già pagato",
+            "Ya pagado",
+            "Reeds betaald",
+        ],
+    ),
+    (
+        Label::Paid,
+        [
+            "Paid",
+            "Bezahlt am",
+            "Payé le",
+            "Pagato il",
+            "Pagado el",
+            "Betaald op",
+        ],
+    ),
+    (
+        Label::Rounding,
+        [
+            "Rounding",
+            "Rundung",
+            "Arrondi",
+            "Arrotondamento",
+            "Redondeo",
+            "Afronding",
+        ],
+    ),
+    (
+        Label::AmountDue,
+        [
+            "AMOUNT DUE",
+            "ZAHLBETRAG",
+            "NET À PAYER",
+            "IMPORTO DOVUTO",
+            "IMPORTE A PAGAR",
+            "TE BETALEN",
+        ],
+    ),
+    (
+        Label::PaymentInformation,
+        [
+            "Payment Information",
+            "Zahlungsinformationen",
+            "Informations de paiement",
+            "Informazioni di pagamento",
+            "Información de pago",
+            "Betalingsgegevens",
+        ],
+    ),
+    (
+        Label::Page,
+        ["Page", "Seite", "Page", "Pagina", "Página", "Pagina"],
+    ),
+    (
+        Label::Phone,
+        ["Phone", "Tel.", "Tél.", "Tel.", "Tel.", "Tel."],
+    ),
+    (
+        Label::DocumentTitle,
+        [
+            "Invoice", "Rechnung", "Facture", "Fattura", "Factura", "Factuur",
+        ],
+    ),
+    (
+        Label::XmlAttachment,
+        [
+            "in XML format",
+            "im XML-Format",
+            "au format XML",
+            "in formato XML",
+            "en formato XML",
+            "in XML-formaat",
+        ],
+    ),
+    (
+        Label::Unit(Unit::YEAR),
+        ["yr", "Jahr", "an", "anno", "año", "jaar"],
+    ),
+    (
+        Label::Unit(Unit::ONE),
+        ["unit", "Einh.", "unité", "unità", "unidad", "eenh."],
+    ),
+    (
+        Label::Unit(Unit::DAY),
+        ["day", "Tag", "jour", "giorno", "día", "dag"],
+    ),
+    (Label::Unit(Unit::GRAM), ["g", "g", "g", "g", "g", "g"]),
+    (
+        Label::Unit(Unit::PIECE),
+        ["pc", "Stk.", "pce", "pz.", "ud.", "st."],
+    ),
+    (Label::Unit(Unit::HOUR), ["h", "Std.", "h", "h", "h", "u"]),
+    (
+        Label::Unit(Unit::KILOGRAM),
+        ["kg", "kg", "kg", "kg", "kg", "kg"],
+    ),
+    (
+        Label::Unit(Unit::KILOMETRE),
+        ["km", "km", "km", "km", "km", "km"],
+    ),
+    (
+        Label::Unit(Unit::KILOWATT_HOUR),
+        ["kWh", "kWh", "kWh", "kWh", "kWh", "kWh"],
+    ),
+    (
+        Label::Unit(Unit::LUMP_SUM),
+        [
+            "lump sum",
+            "pauschal",
+            "forfait",
+            "forfait",
+            "tanto alzado",
+            "forfait",
+        ],
+    ),
+    (Label::Unit(Unit::LITRE), ["l", "l", "l", "l", "l", "l"]),
+    (
+        Label::Unit(Unit::MINUTE),
+        ["min", "Min.", "min", "min", "min", "min"],
+    ),
+    (
+        Label::Unit(Unit::MONTH),
+        ["mo", "Monat", "mois", "mese", "mes", "maand"],
+    ),
+    (
+        Label::Unit(Unit::SQUARE_METRE),
+        ["m²", "m²", "m²", "m²", "m²", "m²"],
+    ),
+    (
+        Label::Unit(Unit::CUBIC_METRE),
+        ["m³", "m³", "m³", "m³", "m³", "m³"],
+    ),
+    (Label::Unit(Unit::METRE), ["m", "m", "m", "m", "m", "m"]),
+    (
+        Label::Unit(Unit::SET),
+        ["set", "Satz", "jeu", "set", "juego", "set"],
+    ),
+    (Label::Unit(Unit::TONNE), ["t", "t", "t", "t", "t", "t"]),
+    (
+        Label::Unit(Unit::WEEK),
+        ["wk", "Woche", "sem.", "sett.", "sem.", "week"],
+    ),
+];
+
+/// Languages in the order of the columns of the built-in table.
+const LANGUAGES: [&str; 6] = ["en", "de", "fr", "it", "es", "nl"];
+
+/// Column of `locale`'s language in the built-in table; English for languages without
+/// one.
+fn language_column(locale: &Locale) -> usize {
+    let language = locale.as_str().split('-').next().unwrap_or_default();
+    LANGUAGES.iter().position(|l| *l == language).unwrap_or(0)
+}
+
+impl Label {
+    /// Heading of the PDF for a document type.
+    pub fn title(document_type: DocumentType) -> Label {
+        match document_type {
+            DocumentType::CommercialInvoice => Label::InvoiceTitle,
+            DocumentType::CreditNote => Label::CreditNoteTitle,
+            DocumentType::CorrectedInvoice => Label::CorrectedInvoiceTitle,
+            DocumentType::PrepaymentInvoice => Label::PrepaymentInvoiceTitle,
+            DocumentType::SelfBilledInvoice => Label::SelfBilledInvoiceTitle,
+        }
+    }
+
+    /// Caption of a VAT category in the totals; taxed categories show the rate instead.
+    pub fn tax_category(category: TaxCategory) -> Label {
+        match category {
+            TaxCategory::Standard | TaxCategory::ZeroRated => Label::Tax,
+            TaxCategory::Exempt => Label::Exempt,
+            TaxCategory::ReverseCharge => Label::ReverseCharge,
+            TaxCategory::IntraCommunity => Label::IntraCommunity,
+            TaxCategory::Export => Label::Export,
+            TaxCategory::OutOfScope => Label::OutOfScope,
+        }
+    }
+
+    /// The built-in caption in the language of `locale`, or in English for other
+    /// languages.
+    pub fn builtin(&self, locale: &Locale) -> &'static str {
+        BUILTIN
+            .iter()
+            .find(|(label, _)| label == self)
+            .map_or("", |(_, texts)| texts[language_column(locale)])
+    }
+}
+
+/// Captions for one language. Labels it does not define fall back to the built-in ones.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    texts: HashMap<Label, String>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// A copy of the built-in captions of `locale`'s language, to be adjusted.
+    pub fn builtin(locale: &Locale) -> Self {
+        Catalog {
+            texts: BUILTIN
+                .iter()
+                .map(|(label, _)| (*label, label.builtin(locale).to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn with(mut self, label: Label, text: impl Into<String>) -> Self {
+        self.insert(label, text);
+        self
+    }
+
+    pub fn insert(&mut self, label: Label, text: impl Into<String>) {
+        self.texts.insert(label, text.into());
+    }
+
+    pub fn get(&self, label: Label) -> Option<&str> {
+        self.texts.get(&label).map(String::as_str)
+    }
+}
+
+/// Catalogs added or overridden at runtime, keyed by locale.
+///
+/// A label is looked up in the catalog of the invoice's locale, then in those of its
+/// fallbacks (`de-AT` before `de`), and finally among the built-in captions.
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    catalogs: HashMap<Locale, Catalog>,
+}
+
+impl Translations {
+    pub fn new() -> Self {
+        Translations::default()
+    }
+
+    pub fn with(mut self, locale: Locale, catalog: Catalog) -> Self {
+        self.insert(locale, catalog);
+        self
+    }
+
+    /// Replaces the catalog of `locale`, returning the previous one.
+    pub fn insert(&mut self, locale: Locale, catalog: Catalog) -> Option<Catalog> {
+        self.catalogs.insert(locale, catalog)
+    }
+
+    pub fn text(&self, locale: &Locale, label: Label) -> &str {
+        let mut current = Some(*locale);
+        while let Some(locale) = current {
+            if let Some(text) = self.catalogs.get(&locale).and_then(|c| c.get(label)) {
+                return text;
+            }
+            current = locale.fallback();
+        }
+        label.builtin(locale)
+    }
+
+    /// Label of `unit`, or its code when neither a catalog nor the built-in table has one.
+    pub fn unit<'a>(&'a self, locale: &Locale, unit: &'a Unit) -> &'a str {
+        match self.text(locale, Label::Unit(*unit)) {
+            "" => unit.code(),
+            text => text,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::InvoiceError;
+use krilla::color::rgb;
+use krilla::geom::{Path, PathBuilder};
+use krilla::image::Image;
+use krilla::num::NormalizedF32;
+use krilla::paint::{Fill, FillRule, Stroke};
+use std::path::PathBuf;
+use std::sync::Arc;
+use usvg::tiny_skia_path::PathSegment;
+
+/// Where the seller logo comes from. SVG documents are detected by content,
+/// everything else is decoded as a PNG or JPEG raster image.
+pub enum LogoSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// A logo placed in the top-right corner of the first page, scaled to fit into
+/// `max_width` x `max_height` points while keeping its aspect ratio.
+pub struct Logo {
+    pub source: LogoSource,
+    pub max_width: f32,
+    pub max_height: f32,
+}
+
+impl Logo {
+    pub const DEFAULT_MAX_WIDTH: f32 = 150.0;
+    pub const DEFAULT_MAX_HEIGHT: f32 = 50.0;
+
+    pub fn from_path<P: Into<PathBuf>>(path: P) -> Self {
+        Logo {
+            source: LogoSource::Path(path.into()),
+            max_width: Self::DEFAULT_MAX_WIDTH,
+            max_height: Self::DEFAULT_MAX_HEIGHT,
+        }
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Logo {
+            source: LogoSource::Bytes(bytes),
+            max_width: Self::DEFAULT_MAX_WIDTH,
+            max_height: Self::DEFAULT_MAX_HEIGHT,
+        }
+    }
+
+    pub(crate) fn load(&self) -> Result<LogoGraphic, InvoiceError> {
+        let bytes = match &self.source {
+            LogoSource::Path(path) => std::fs::read(path)
+                .map_err(|e| InvoiceError::Logo(format!("{}: {}", path.display(), e)))?,
+            LogoSource::Bytes(bytes) => bytes.clone(),
+        };
+        if is_svg(&bytes) {
+            load_svg(&bytes)
+        } else {
+            let decoded = image::load_from_memory(&bytes)
+                .map_err(|e| InvoiceError::Logo(e.to_string()))?
+                .to_rgba8();
+            let (width, height) = decoded.dimensions();
+            Ok(LogoGraphic::Raster(Image::from_rgba8(
+                decoded.into_raw(),
+                width,
+                height,
+            )))
+        }
+    }
+
+    /// Size in points the graphic is drawn at inside the bounding box.
+    pub(crate) fn fitted_size(&self, graphic: &LogoGraphic) -> (f32, f32) {
+        let (width, height) = graphic.natural_size();
+        let scale = (self.max_width / width).min(self.max_height / height);
+        (width * scale, height * scale)
+    }
+}
+
+pub(crate) enum LogoGraphic {
+    Raster(Image),
+    Vector {
+        shapes: Arc<Vec<VectorShape>>,
+        width: f32,
+        height: f32,
+    },
+}
+
+impl LogoGraphic {
+    fn natural_size(&self) -> (f32, f32) {
+        match self {
+            LogoGraphic::Raster(image) => {
+                let (width, height) = image.size();
+                (width as f32, height as f32)
+            }
+            LogoGraphic::Vector { width, height, .. } => (*width, *height),
+        }
+    }
+}
+
+/// One SVG path with its transform already applied, in SVG user units.
+pub(crate) struct VectorShape {
+    pub path: Path,
+    pub fill: Option<Fill>,
+    pub stroke: Option<Stroke>,
+}
+
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]);
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))
+}
+
+/// Flattens an SVG into filled and stroked paths. Gradients and patterns are
+/// approximated by a solid color; embedded raster images and text are skipped.
+fn load_svg(bytes: &[u8]) -> Result<LogoGraphic, InvoiceError> {
+    let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())
+        .map_err(|e| InvoiceError::Logo(e.to_string()))?;
+    let mut shapes = Vec::new();
+    collect_shapes(tree.root(), &mut shapes);
+    Ok(LogoGraphic::Vector {
+        shapes: Arc::new(shapes),
+        width: tree.size().width(),
+        height: tree.size().height(),
+    })
+}
+
+fn collect_shapes(group: &usvg::Group, shapes: &mut Vec<VectorShape>) {
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(group) => collect_shapes(group, shapes),
+            usvg::Node::Path(path) if path.is_visible() => {
+                let Some(data) = path.data().clone().transform(path.abs_transform()) else {
+                    continue;
+                };
+                let Some(krilla_path) = convert_path(&data) else {
+                    continue;
+                };
+                let fill = path.fill().map(|fill| Fill {
+                    paint: paint_color(fill.paint()).into(),
+                    opacity: NormalizedF32::new(fill.opacity().get()).unwrap_or(NormalizedF32::ONE),
+                    rule: match fill.rule() {
+                        usvg::FillRule::NonZero => FillRule::NonZero,
+                        usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+                    },
+                });
+                let stroke = path.stroke().map(|stroke| Stroke {
+                    paint: paint_color(stroke.paint()).into(),
+                    width: stroke.width().get() * path.abs_transform().get_scale().0,
+                    opacity: NormalizedF32::new(stroke.opacity().get())
+                        .unwrap_or(NormalizedF32::ONE),
+                    ..Default::default()
+                });
+                shapes.push(VectorShape {
+                    path: krilla_path,
+                    fill,
+                    stroke,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn paint_color(paint: &usvg::Paint) -> rgb::Color {
+    let color = match paint {
+        usvg::Paint::Color(color) => *color,
+        usvg::Paint::LinearGradient(gradient) => gradient
+            .stops()
+            .first()
+            .map_or(usvg::Color::black(), |stop| stop.color()),
+        usvg::Paint::RadialGradient(gradient) => gradient
+            .stops()
+            .first()
+            .map_or(usvg::Color::black(), |stop| stop.color()),
+        usvg::Paint::Pattern(_) => usvg::Color::black(),
+    };
+    rgb::Color::new(color.red, color.green, color.blue)
+}
+
+fn convert_path(data: &usvg::tiny_skia_path::Path) -> Option<Path> {
+    let mut pb = PathBuilder::new();
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => pb.move_to(p.x, p.y),
+            PathSegment::LineTo(p) => pb.line_to(p.x, p.y),
+            PathSegment::QuadTo(p1, p) => pb.quad_to(p1.x, p1.y, p.x, p.y),
+            PathSegment::CubicTo(p1, p2, p) => pb.cubic_to(p1.x, p1.y, p2.x, p2.y, p.x, p.y),
+            PathSegment::Close => pb.close(),
+        }
+    }
+    pb.finish()
+}
@@ -1,6 +1,28 @@
+mod canvas;
+pub mod extract;
+pub mod facturx;
+pub mod fonts;
+pub mod format;
 pub mod generate;
+pub mod labels;
+pub mod logo;
+pub mod options;
+pub mod text;
+pub mod theme;
 
-pub use generate::generate_invoice_pdf;
+pub use extract::extract_embedded_xml;
+pub use facturx::HybridInvoice;
+pub use fonts::{FontSet, FontSource};
+pub use format::{format_money, format_number, format_percent, format_price};
+pub use generate::{
+    generate_invoice_pdf, generate_invoice_pdf_with_fonts, generate_invoice_pdf_with_options,
+};
+pub use labels::{Catalog, Label, Translations};
+pub use logo::{Logo, LogoSource};
+pub use options::PdfOptions;
+pub use theme::{
+    Color, Columns, FontRole, LineStyle, Margins, PageSize, Slot, Slots, TextStyle, Theme,
+};
 
 pub const LEFT_MARGIN: f32 = 20.0;
 pub const COL_1: f32 = LEFT_MARGIN;
@@ -8,3 +30,8 @@ pub const COL_2: f32 = 120.0;
 pub const PAGE_WIDTH: f32 = 210.0;
 pub const PAGE_HEIGHT: f32 = 297.0;
 pub const BOTTOM_MARGIN: f32 = 15.0;
+
+/// The constants above are in millimetres; krilla works in PostScript points.
+pub const fn mm_to_pt(mm: f32) -> f32 {
+    mm * 72.0 / 25.4
+}
@@ -0,0 +1,18 @@
+use crate::pdf::facturx::HybridInvoice;
+use crate::pdf::labels::Translations;
+use crate::pdf::logo::Logo;
+use crate::pdf::theme::Theme;
+
+/// Optional rendering settings for [`generate_invoice_pdf_with_options`].
+///
+/// [`generate_invoice_pdf_with_options`]: crate::pdf::generate::generate_invoice_pdf_with_options
+#[derive(Default)]
+pub struct PdfOptions {
+    pub logo: Option<Logo>,
+    /// Produces a PDF/A-3b ZUGFeRD / Factur-X hybrid invoice with the XML attached.
+    pub hybrid: Option<HybridInvoice>,
+    /// Captions overriding or extending the built-in ones.
+    pub translations: Translations,
+    /// Page geometry, text styles and header / footer contents.
+    pub theme: Theme,
+}
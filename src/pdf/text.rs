@@ -0,0 +1,81 @@
+use skrifa::instance::{LocationRef, Size};
+use skrifa::metrics::GlyphMetrics;
+use skrifa::{FontRef, GlyphId, MetadataProvider, charmap::Charmap};
+
+/// Measures and wraps strings using the advance widths of the font that draws them.
+pub struct TextMeasurer<'a> {
+    charmap: Charmap<'a>,
+    metrics: GlyphMetrics<'a>,
+}
+
+impl<'a> TextMeasurer<'a> {
+    pub fn new(font_data: &'a [u8]) -> Option<Self> {
+        let font = FontRef::new(font_data).ok()?;
+        Some(TextMeasurer {
+            charmap: font.charmap(),
+            // A 1pt size yields advances in ems, which scale linearly with the font size.
+            metrics: font.glyph_metrics(Size::new(1.0), LocationRef::default()),
+        })
+    }
+
+    /// Width of `text` in points when drawn at `size`.
+    pub fn width(&self, text: &str, size: f32) -> f32 {
+        text.chars()
+            .map(|c| {
+                let glyph = self.charmap.map(c).unwrap_or(GlyphId::NOTDEF);
+                self.metrics.advance_width(glyph).unwrap_or(0.0)
+            })
+            .sum::<f32>()
+            * size
+    }
+
+    /// Breaks `text` into lines no wider than `max_width`, preferring whitespace and
+    /// splitting words that do not fit on a line of their own. Explicit newlines are kept.
+    pub fn wrap(&self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut current = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if current.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", current, word)
+                };
+                if self.width(&candidate, size) <= max_width {
+                    current = candidate;
+                    continue;
+                }
+                if !current.is_empty() {
+                    lines.push(std::mem::take(&mut current));
+                }
+                if self.width(word, size) <= max_width {
+                    current = word.to_string();
+                } else {
+                    let mut pieces = self.break_word(word, size, max_width);
+                    current = pieces.pop().unwrap_or_default();
+                    lines.extend(pieces);
+                }
+            }
+            lines.push(current);
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines
+    }
+
+    fn break_word(&self, word: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut pieces = Vec::new();
+        let mut current = String::new();
+        for c in word.chars() {
+            current.push(c);
+            if self.width(&current, size) > max_width && current.chars().count() > 1 {
+                current.pop();
+                pieces.push(std::mem::take(&mut current));
+                current.push(c);
+            }
+        }
+        pieces.push(current);
+        pieces
+    }
+}
@@ -0,0 +1,225 @@
+use crate::pdf::{BOTTOM_MARGIN, PAGE_HEIGHT, PAGE_WIDTH, mm_to_pt};
+
+/// Look and geometry of the generated PDF. All measures are in PostScript points.
+/// The default reproduces the built-in A4 layout, with bold headings and totals when
+/// the font set has bold fonts.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub page: PageSize,
+    pub margins: Margins,
+    pub columns: Columns,
+    /// Document title, e.g. "INVOICE".
+    pub title: TextStyle,
+    /// Captions of the seller and buyer blocks.
+    pub heading: TextStyle,
+    /// Column captions of the product table and the payment information caption.
+    pub caption: TextStyle,
+    /// Invoice details, party names and the lines of the totals section.
+    pub text: TextStyle,
+    /// Addresses, product rows, notes and payment details.
+    pub body: TextStyle,
+    /// The total and the amount due.
+    pub total: TextStyle,
+    /// Header and footer slots.
+    pub small: TextStyle,
+    /// Horizontal rules below the header, the table and the totals.
+    pub rule: LineStyle,
+    /// How far the top of the logo sits above the title baseline, as a multiple of the
+    /// title size.
+    pub logo_rise: f32,
+    /// Repeated in the top margin of every page; raise `margins.top` to make room.
+    pub header: Slots,
+    /// Repeated in the bottom margin of every page.
+    pub footer: Slots,
+}
+
+impl Theme {
+    /// Space between the sections of the page, one em of the text style.
+    pub fn gap(&self) -> f32 {
+        self.text.size
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            page: PageSize::A4,
+            margins: Margins {
+                top: 40.0,
+                right: 40.0,
+                bottom: mm_to_pt(BOTTOM_MARGIN),
+                left: 40.0,
+            },
+            columns: Columns {
+                description: 320.0,
+                quantity: 60.0,
+                unit_price: 70.0,
+                buyer: 280.0,
+                totals_label: 60.0,
+                details: 150.0,
+                slot: 60.0,
+                gap: 10.0,
+            },
+            title: TextStyle::new(28.0).with_font(FontRole::Bold),
+            heading: TextStyle::new(11.0).with_font(FontRole::SemiBold),
+            caption: TextStyle::new(10.0).with_font(FontRole::SemiBold),
+            text: TextStyle::new(10.0),
+            body: TextStyle::new(9.0),
+            total: TextStyle::new(12.0).with_font(FontRole::Bold),
+            small: TextStyle::new(8.0),
+            rule: LineStyle {
+                width: 1.0,
+                color: Color::BLACK,
+            },
+            logo_rise: 0.9,
+            header: Slots {
+                left: Slot::Empty,
+                right: Slot::Empty,
+            },
+            footer: Slots {
+                left: Slot::SellerContact,
+                right: Slot::PageNumber,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PageSize {
+    pub const A4: PageSize = PageSize {
+        width: mm_to_pt(PAGE_WIDTH),
+        height: mm_to_pt(PAGE_HEIGHT),
+    };
+    pub const LETTER: PageSize = PageSize {
+        width: 612.0,
+        height: 792.0,
+    };
+
+    pub fn from_mm(width: f32, height: f32) -> Self {
+        PageSize {
+            width: mm_to_pt(width),
+            height: mm_to_pt(height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+/// Widths of the product table columns; the line total takes the remaining width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Columns {
+    pub description: f32,
+    pub quantity: f32,
+    pub unit_price: f32,
+    /// Offset of the buyer block from the left margin.
+    pub buyer: f32,
+    /// Space for the totals captions before the unit price column.
+    pub totals_label: f32,
+    /// Offset of the invoice details from the right margin.
+    pub details: f32,
+    /// Offset of the right header and footer slot from the right margin.
+    pub slot: f32,
+    /// Space kept free after a wrapped description and a wrapped left slot.
+    pub gap: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub size: f32,
+    pub color: Color,
+    pub font: FontRole,
+    /// Distance between the wrapped lines of one text, as a multiple of `size`.
+    pub line_spacing: f32,
+    /// Distance between separate rows, e.g. of the product table, as a multiple of
+    /// `size`.
+    pub row_spacing: f32,
+}
+
+impl TextStyle {
+    /// Black text in the regular font.
+    pub fn new(size: f32) -> Self {
+        TextStyle {
+            size,
+            color: Color::BLACK,
+            font: FontRole::Regular,
+            line_spacing: 1.25,
+            row_spacing: 1.4,
+        }
+    }
+
+    pub fn with_font(mut self, font: FontRole) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.size * self.line_spacing
+    }
+
+    pub fn row_height(&self) -> f32 {
+        self.size * self.row_spacing
+    }
+}
+
+/// Which font of the [`FontSet`] draws a text.
+///
+/// [`FontSet`]: crate::pdf::FontSet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FontRole {
+    #[default]
+    Regular,
+    Bold,
+    Italic,
+    SemiBold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineStyle {
+    pub width: f32,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+}
+
+/// The left and right positions of a header or footer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slots {
+    /// Starts at the left margin and wraps before the right slot.
+    pub left: Slot,
+    /// Starts `columns.slot` before the right margin.
+    pub right: Slot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Slot {
+    Empty,
+    Text(String),
+    /// Website, phone and email of the seller.
+    SellerContact,
+    /// "Page 1 / 3", only on invoices with more than one page.
+    PageNumber,
+}
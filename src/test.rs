@@ -1,28 +1,72 @@
 use crate::{
-    models::{Address, Buyer, Invoice, Product, Seller},
-    pdf::generate_invoice_pdf,
+    einvoice::{
+        EInvoiceProfile,
+        validate::{Severity, ValidationReport, validate_invoice, validate_xml},
+    },
+    error::InvoiceError,
+    models::{
+        Address, AllowanceCharge, AllowanceChargeAmount, AllowanceChargeKind, BillingReference,
+        Buyer, Country, Currency, Decimal, DocumentType, ElectronicAddress, Invoice, ItemAttribute,
+        ItemClassification, ItemIdentifier, Money, Payment, Product, Seller, TaxCategory, Unit,
+    },
+    pdf::{
+        Catalog, Color, FontRole, FontSet, FontSource, HybridInvoice, Label, Logo, PageSize,
+        PdfOptions, Slot, Theme, Translations, extract_embedded_xml, facturx, facturx::SRGB_ICC,
+        format_money, format_number, format_percent, format_price, generate_invoice_pdf,
+        generate_invoice_pdf_with_fonts, generate_invoice_pdf_with_options, text::TextMeasurer,
+    },
 };
 use locale_rs::{Locale, datetime_formats::DateTime};
 use std::io::{self, Write};
 use tempfile::NamedTempFile;
 
+fn make_product(description: &str, quantity: Decimal, cost_per_unit: Decimal) -> Product {
+    Product {
+        description: description.to_string(),
+        quantity,
+        unit: Unit::PIECE,
+        cost_per_unit,
+        price_base_quantity: None,
+        tax_rate: Decimal::new(19, 2),
+        tax_category: TaxCategory::Standard,
+        tax_exempt_reason: None,
+        tax_exemption_code: None,
+        allowances_charges: Vec::new(),
+        note: None,
+        seller_item_id: None,
+        buyer_item_id: None,
+        standard_item_id: None,
+        classifications: Vec::new(),
+        origin_country: None,
+        attributes: Vec::new(),
+    }
+}
+
 fn make_test_invoice() -> Invoice {
     let seller_addr = Address {
         street: "Seller Street".to_string(),
-        house_number: 67,
-        code: 42069,
+        house_number: "67".to_string(),
+        additional_lines: Vec::new(),
+        code: "42069".to_string(),
         town: "Rizzton".to_string(),
+        subdivision: None,
+        country: Country::DE,
     };
 
     let buyer_addr = Address {
         street: "Buyer Street".to_string(),
-        house_number: 67,
-        code: 69420,
+        house_number: "67".to_string(),
+        additional_lines: Vec::new(),
+        code: "69420".to_string(),
         town: "Rizzton".to_string(),
+        subdivision: None,
+        country: Country::DE,
     };
 
     Invoice {
         number: "TEST-001".to_string(),
+        document_type: DocumentType::CommercialInvoice,
+        billing_reference: None,
         date: DateTime {
             year: 2025,
             month: 7,
@@ -35,6 +79,10 @@ fn make_test_invoice() -> Invoice {
             name: "Test Seller".to_string(),
             address: seller_addr,
             vat_id: "VAT-TEST-123".to_string(),
+            tax_registration_number: None,
+            legal_registration_id: None,
+            legal_form: None,
+            electronic_address: None,
             website: "test.example.com".to_string(),
             phone: Some("+49 123 456789".to_string()),
             email: Some("contact@test.example.com".to_string()),
@@ -43,6 +91,11 @@ fn make_test_invoice() -> Invoice {
             name: "Test Buyer".to_string(),
             address: buyer_addr,
             email: "buyer@example.com".to_string(),
+            vat_id: None,
+            tax_registration_number: None,
+            legal_registration_id: None,
+            legal_form: None,
+            electronic_address: None,
         },
         payment_due: DateTime {
             year: 2025,
@@ -61,25 +114,22 @@ fn make_test_invoice() -> Invoice {
             second: 0,
         }),
         delivery_type: None,
+        delivery_country: None,
         extra_info: Some(vec![]),
         payment_type: None,
-        payment_info: Some(vec![]),
+        payment_info: Some(vec![(
+            "IBAN".to_string(),
+            "DE02120300000000202051".to_string(),
+        )]),
         products: vec![
-            Product {
-                description: "Widget".to_string(),
-                units: 2,
-                cost_per_unit: 10.0,
-                tax_rate: 0.19,
-                tax_exempt_reason: None,
-            },
-            Product {
-                description: "Gadget".to_string(),
-                units: 1,
-                cost_per_unit: 20.0,
-                tax_rate: 0.19,
-                tax_exempt_reason: None,
-            },
+            make_product("Widget", Decimal::from(2), Decimal::new(10, 0)),
+            make_product("Gadget", Decimal::from(1), Decimal::new(20, 0)),
         ],
+        allowances_charges: Vec::new(),
+        prepaid_amount: None,
+        payments: Vec::new(),
+        rounding_amount: None,
+        currency: Currency::EUR,
         locale: Locale::de,
     }
 }
@@ -120,25 +170,44 @@ fn test_generate_and_save_pdf_tempfile() -> io::Result<()> {
 fn test_xml_against_itb_api() {
     let seller_addr = Address {
         street: "Main Street".to_string(),
-        house_number: 123,
-        code: 90210,
+        house_number: "123".to_string(),
+        additional_lines: Vec::new(),
+        code: "90210".to_string(),
         town: "Anytown".to_string(),
+        subdivision: None,
+        country: Country::DE,
     };
 
     let buyer_addr = Address {
         street: "Oak Avenue".to_string(),
-        house_number: 456,
-        code: 10001,
+        house_number: "456".to_string(),
+        additional_lines: Vec::new(),
+        code: "10001".to_string(),
         town: "Cityville".to_string(),
+        subdivision: None,
+        country: Country::AT,
     };
 
     let invoice = Invoice {
         number: "INV-2025-EXAMPLE".to_string(),
-        date: DateTime { year: 2025, month: 7, day: 15, hour: 0, minute: 0, second: 0 },
+        document_type: DocumentType::CommercialInvoice,
+        billing_reference: None,
+        date: DateTime {
+            year: 2025,
+            month: 7,
+            day: 15,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        },
         seller: Seller {
             name: "Example Corp".to_string(),
             address: seller_addr,
             vat_id: "VAT-EX-00000000".to_string(),
+            tax_registration_number: Some("12/345/67890".to_string()),
+            legal_registration_id: Some("HRB 12345".to_string()),
+            legal_form: Some("GmbH".to_string()),
+            electronic_address: None,
             website: "examplecorp.com".to_string(),
             phone: Some("+49 123 456789".to_string()),
             email: Some("contact@examplecorp.com".to_string()),
@@ -147,10 +216,30 @@ fn test_xml_against_itb_api() {
             name: "John Doe".to_string(),
             address: buyer_addr,
             email: "john.doe@example.com".to_string(),
+            vat_id: Some("ATU12345678".to_string()),
+            tax_registration_number: None,
+            legal_registration_id: None,
+            legal_form: None,
+            electronic_address: None,
+        },
+        payment_due: DateTime {
+            year: 2025,
+            month: 8,
+            day: 15,
+            hour: 0,
+            minute: 0,
+            second: 0,
         },
-        payment_due: DateTime { year: 2025, month: 8, day: 15, hour: 0, minute: 0, second: 0 },
-        delivery_date: Some(DateTime { year: 2025, month: 7, day: 14, hour: 0, minute: 0, second: 0 }),
+        delivery_date: Some(DateTime {
+            year: 2025,
+            month: 7,
+            day: 14,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }),
         delivery_type: Some("Standard Shipping".to_string()),
+        delivery_country: None,
         extra_info: Some(vec![
             ("Order Reference".to_string(), "987654321".to_string()),
             ("Project".to_string(), "Example Project".to_string()),
@@ -164,28 +253,32 @@ fn test_xml_against_itb_api() {
             ("BIC".to_string(), "INGDDEFFXXX".to_string()),
         ]),
         products: vec![
+            make_product(
+                "Rusty Widget with very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very long description",
+                Decimal::from(10),
+                Decimal::new(999, 2),
+            ),
             Product {
-                description: "Rusty Widget with very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very very long description".to_string(),
-                units: 10,
-                cost_per_unit: 9.99,
-                tax_rate: 0.19,
-                tax_exempt_reason: None,
-            },
-            Product {
-                description: "Gadget Pro".to_string(),
-                units: 5,
-                cost_per_unit: 19.95,
-                tax_rate: 0.07,
-                tax_exempt_reason: Some("WOOW".to_string()),
+                tax_rate: Decimal::new(7, 2),
+                ..make_product("Gadget Pro", Decimal::from(5), Decimal::new(1995, 2))
             },
             Product {
-                description: "Exported Item (Reverse Charge)".to_string(),
-                units: 2,
-                cost_per_unit: 100.0,
-                tax_rate: 0.0,
+                tax_rate: Decimal::ZERO,
+                tax_category: TaxCategory::ReverseCharge,
                 tax_exempt_reason: Some("Intra-EU reverse charge".to_string()),
+                tax_exemption_code: Some("VATEX-EU-AE".to_string()),
+                ..make_product(
+                    "Exported Item (Reverse Charge)",
+                    Decimal::from(2),
+                    Decimal::new(100, 0),
+                )
             },
         ],
+        allowances_charges: Vec::new(),
+        prepaid_amount: None,
+        payments: Vec::new(),
+        rounding_amount: None,
+        currency: Currency::EUR,
         locale: Locale::de,
     };
 
@@ -216,7 +309,7 @@ fn test_xml_against_itb_api() {
     );
 
     let report: serde_json::Value = serde_json::from_str(&raw_body)
-        .expect(&format!("Failed to parse JSON. Body was: {}", raw_body));
+        .unwrap_or_else(|_| panic!("Failed to parse JSON. Body was: {}", raw_body));
 
     let is_success = report["success"].as_bool().unwrap_or(false) || report["result"] == "SUCCESS";
 
@@ -240,3 +333,1723 @@ fn test_xml_against_itb_api() {
 
     println!("Validation Passed Successfully!");
 }
+
+#[test]
+fn test_decimal_rounding_and_parsing() {
+    let value: Decimal = "2.345".parse().unwrap();
+    assert_eq!(value.round_dp(2), Decimal::new(235, 2));
+    assert_eq!((-value).round_dp(2), Decimal::new(-235, 2));
+    assert_eq!(format!("{:.2}", Decimal::new(5, 1)), "0.50");
+    assert_eq!(Decimal::new(1900, 2), Decimal::new(19, 0));
+    assert!("1.2.3".parse::<Decimal>().is_err());
+}
+
+#[test]
+fn test_out_of_range_amounts_are_errors() {
+    let huge: Decimal = "100000000000000000000".parse().unwrap();
+    assert_eq!(huge.checked_mul(huge), None);
+    assert_eq!(
+        huge.checked_mul(Decimal::new(5, 1)),
+        "50000000000000000000".parse().ok()
+    );
+    assert!(huge < huge * Decimal::new(10, 0));
+
+    let mut invoice = make_test_invoice();
+    invoice.products[0].quantity = huge;
+    invoice.products[0].cost_per_unit = huge;
+    assert!(matches!(
+        invoice.try_calculate_summary(),
+        Err(InvoiceError::InvalidInvoice(_))
+    ));
+    assert!(matches!(
+        invoice.to_xml(),
+        Err(InvoiceError::InvalidInvoice(_))
+    ));
+    assert!(matches!(
+        invoice.to_cii_xml(EInvoiceProfile::En16931),
+        Err(InvoiceError::InvalidInvoice(_))
+    ));
+    assert!(matches!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931),
+        Err(InvoiceError::InvalidInvoice(_))
+    ));
+    let pdf = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    );
+    assert!(matches!(pdf, Err(InvoiceError::InvalidInvoice(_))));
+
+    // Amounts that fit each on their own but not their product.
+    invoice.products[0].quantity = "123456789".parse().unwrap();
+    invoice.products[0].cost_per_unit = "987654321".parse().unwrap();
+    let xml = invoice
+        .to_xml()
+        .unwrap()
+        .replace(">123456789<", ">100000000000000000000<")
+        .replace(">987654321<", ">100000000000000000000<");
+    assert!(matches!(
+        Invoice::from_ubl_xml(&xml),
+        Err(InvoiceError::Parse(_))
+    ));
+    assert!(validate_xml(&xml).is_ok());
+}
+
+#[test]
+fn test_summary_matches_xml_totals() {
+    let mut invoice = make_test_invoice();
+    invoice.products = (0..7)
+        .map(|i| {
+            make_product(
+                &format!("Line {}", i),
+                Decimal::from(3),
+                Decimal::new(3333, 3),
+            )
+        })
+        .collect();
+
+    let summary = invoice.calculate_summary();
+    // 7 lines of 3 x 3.333 = 10.00 each after rounding; 19% of 70.00 is 13.30.
+    assert_eq!(summary.subtotal.amount, Decimal::new(7000, 2));
+    assert_eq!(summary.tax_total.amount, Decimal::new(1330, 2));
+    assert_eq!(summary.total.amount, Decimal::new(8330, 2));
+
+    let xml = invoice.to_xml().unwrap();
+    assert!(xml.contains(r#"<cbc:PayableAmount currencyID="EUR">83.30</cbc:PayableAmount>"#));
+    assert!(xml.contains(r#"<cbc:TaxAmount currencyID="EUR">13.30</cbc:TaxAmount>"#));
+}
+
+#[test]
+fn test_cii_matches_ubl_totals() {
+    let invoice = make_test_invoice();
+    let summary = invoice.calculate_summary();
+    let ubl = invoice.to_xml().unwrap();
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+
+    let mut reader = quick_xml::Reader::from_str(&cii);
+    loop {
+        match reader.read_event().unwrap() {
+            quick_xml::events::Event::Eof => break,
+            _ => continue,
+        }
+    }
+
+    assert!(cii.contains("<ram:ID>urn:cen.eu:en16931:2017</ram:ID>"));
+    assert!(!cii.contains("BusinessProcessSpecifiedDocumentContextParameter"));
+    assert!(cii.contains(r#"<udt:DateTimeString format="102">20250715</udt:DateTimeString>"#));
+    assert!(cii.contains(&format!(
+        "<ram:GrandTotalAmount>{}</ram:GrandTotalAmount>",
+        summary.total
+    )));
+    assert!(ubl.contains(&format!(
+        r#"<cbc:PayableAmount currencyID="EUR">{}</cbc:PayableAmount>"#,
+        summary.total
+    )));
+    assert!(cii.contains(&format!(
+        r#"<ram:TaxTotalAmount currencyID="EUR">{}</ram:TaxTotalAmount>"#,
+        summary.tax_total
+    )));
+    for subtotal in &summary.tax_subtotals {
+        assert!(cii.contains(&format!(
+            "<ram:BasisAmount>{}</ram:BasisAmount>",
+            subtotal.taxable_amount
+        )));
+        assert!(ubl.contains(&format!(
+            r#"<cbc:TaxableAmount currencyID="EUR">{}</cbc:TaxableAmount>"#,
+            subtotal.taxable_amount
+        )));
+    }
+    assert_eq!(
+        cii.matches("<ram:IncludedSupplyChainTradeLineItem>")
+            .count(),
+        invoice.products.len()
+    );
+
+    let xrechnung = invoice.to_cii_xml(EInvoiceProfile::XRechnung).unwrap();
+    assert!(xrechnung.contains("urn:xeinkauf.de:kosit:xrechnung_3.0"));
+    assert!(xrechnung.contains("<ram:ID>urn:fdc:peppol.eu:2017:poacc:billing:01:1.0</ram:ID>"));
+}
+
+#[test]
+fn test_profiles_control_emitted_fields() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![
+        ("IBAN".to_string(), "DE02120300000000202051".to_string()),
+        ("BIC".to_string(), "BYLADEM1001".to_string()),
+    ]);
+
+    let minimum = invoice.to_cii_xml(EInvoiceProfile::Minimum).unwrap();
+    assert!(minimum.contains("<ram:ID>urn:factur-x.eu:1p0:minimum</ram:ID>"));
+    assert!(!minimum.contains("<ram:IncludedSupplyChainTradeLineItem>"));
+    assert!(!minimum.contains("<ram:ApplicableTradeTax>"));
+    assert!(!minimum.contains("<ram:LineOne>"));
+    assert!(minimum.contains("<ram:CountryID>DE</ram:CountryID>"));
+    assert!(minimum.contains("<ram:GrandTotalAmount>"));
+
+    let basic_wl = invoice.to_cii_xml(EInvoiceProfile::BasicWl).unwrap();
+    assert!(!basic_wl.contains("<ram:IncludedSupplyChainTradeLineItem>"));
+    assert!(basic_wl.contains("<ram:ApplicableTradeTax>"));
+    assert!(basic_wl.contains("<ram:IBANID>DE02120300000000202051</ram:IBANID>"));
+
+    let basic = invoice.to_cii_xml(EInvoiceProfile::Basic).unwrap();
+    assert!(basic.contains("<ram:IncludedSupplyChainTradeLineItem>"));
+    assert!(!basic.contains("<ram:DefinedTradeContact>"));
+    assert!(!basic.contains("<ram:BICID>"));
+
+    let en16931 = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(en16931.contains("<ram:DefinedTradeContact>"));
+    assert!(en16931.contains("<ram:BICID>BYLADEM1001</ram:BICID>"));
+
+    for profile in EInvoiceProfile::ALL {
+        let xml = invoice.to_cii_xml(profile).unwrap();
+        assert!(xml.contains(&format!("<ram:ID>{}</ram:ID>", profile.guideline_id())));
+        match invoice.to_ubl_xml(profile) {
+            Ok(_) => assert!(profile.is_en16931()),
+            Err(InvoiceError::UnsupportedProfile(p)) => assert_eq!(p, profile),
+            Err(other) => panic!("unexpected error {}", other),
+        }
+    }
+
+    invoice.seller.email = None;
+    let err = invoice.to_cii_xml(EInvoiceProfile::XRechnung).unwrap_err();
+    assert!(err.to_string().contains("BR-DE-7"));
+    assert!(invoice.to_cii_xml(EInvoiceProfile::En16931).is_ok());
+
+    // Credit transfer is only written with the account it pays into.
+    invoice.payment_info = None;
+    let err = invoice.to_xml().unwrap_err();
+    assert!(err.to_string().contains("BR-DE-1"));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(!cii.contains("<ram:SpecifiedTradeSettlementPaymentMeans>"));
+    let ubl = invoice.to_ubl_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(!ubl.contains("<cac:PaymentMeans>"));
+    for xml in [cii, ubl] {
+        let report = validate_xml(&xml).unwrap();
+        assert!(report.is_valid(), "{:?}", report);
+    }
+
+    invoice.products.clear();
+    assert!(invoice.to_cii_xml(EInvoiceProfile::Basic).is_err());
+    assert!(invoice.to_cii_xml(EInvoiceProfile::BasicWl).is_ok());
+}
+
+#[test]
+fn test_parse_ubl_and_cii_round_trip() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![
+        ("IBAN".to_string(), "DE02120300000000202051".to_string()),
+        ("BIC".to_string(), "BYLADEM1001".to_string()),
+    ]);
+    let summary = invoice.calculate_summary();
+
+    let ubl = invoice.to_xml().unwrap();
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.number, invoice.number);
+        assert_eq!(parsed.date.day, invoice.date.day);
+        assert_eq!(parsed.seller.name, invoice.seller.name);
+        assert_eq!(parsed.seller.vat_id, invoice.seller.vat_id);
+        assert_eq!(parsed.seller.email, invoice.seller.email);
+        assert_eq!(parsed.seller.address.street, invoice.seller.address.street);
+        assert_eq!(parsed.seller.address.house_number, "67");
+        assert_eq!(parsed.buyer.address.code, invoice.buyer.address.code);
+        assert_eq!(parsed.buyer.email, invoice.buyer.email);
+        assert_eq!(parsed.payment_info, invoice.payment_info);
+        assert_eq!(parsed.products.len(), invoice.products.len());
+        assert_eq!(
+            parsed.products[1].tax_exempt_reason,
+            invoice.products[1].tax_exempt_reason
+        );
+
+        let parsed_summary = parsed.calculate_summary();
+        assert_eq!(parsed_summary.total, summary.total);
+        assert_eq!(parsed_summary.tax_total, summary.tax_total);
+        assert_eq!(parsed.to_xml().unwrap(), ubl);
+    }
+
+    let with_note = ubl.replace(
+        "<cbc:DueDate>",
+        "<cbc:Note>Thank you</cbc:Note><cbc:DueDate>",
+    );
+    let (_, report) = Invoice::from_ubl_xml(&with_note).unwrap();
+    assert_eq!(report.unsupported, vec!["Invoice/cbc:Note".to_string()]);
+
+    let basic_wl = invoice.to_cii_xml(EInvoiceProfile::BasicWl).unwrap();
+    let (parsed, report) = Invoice::from_cii_xml(&basic_wl).unwrap();
+    assert!(!report.lossy.is_empty());
+    // Stand-in lines are described by category code and rate in any language.
+    let descriptions: Vec<_> = parsed.products.iter().map(|p| &p.description).collect();
+    assert_eq!(descriptions, ["S 19%"]);
+    assert_eq!(parsed.calculate_summary().total, summary.total);
+
+    assert!(Invoice::from_cii_xml(&ubl).is_err());
+}
+
+#[test]
+fn test_offline_validation() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![
+        ("IBAN".to_string(), "DE02120300000000202051".to_string()),
+        ("BIC".to_string(), "BYLADEM1001".to_string()),
+    ]);
+
+    for profile in EInvoiceProfile::ALL {
+        let report = validate_invoice(&invoice, profile).unwrap();
+        assert!(report.findings.is_empty(), "{}: {:?}", profile, report);
+        let cii = invoice.to_cii_xml(profile).unwrap();
+        let report = validate_xml(&cii).unwrap();
+        assert!(report.findings.is_empty(), "{}: {:?}", profile, report);
+    }
+    let ubl = invoice.to_xml().unwrap();
+    assert!(validate_xml(&ubl).unwrap().findings.is_empty());
+
+    let tampered = ubl.replace(
+        r#"<cbc:PayableAmount currencyID="EUR">"#,
+        r#"<cbc:PayableAmount currencyID="EUR">1"#,
+    );
+    let report = validate_xml(&tampered).unwrap();
+    assert!(!report.is_valid());
+    let finding = report.errors().next().unwrap();
+    assert_eq!(finding.rule, "BR-CO-16");
+    assert_eq!(finding.field, "BT-115");
+    assert_eq!(finding.severity, Severity::Fatal);
+
+    invoice.products[1].description = " ".to_string();
+    invoice.seller.vat_id.clear();
+    invoice.seller.email = None;
+    invoice.payment_info = None;
+    let report = validate_invoice(&invoice, EInvoiceProfile::XRechnung).unwrap();
+    let line = report.findings.iter().find(|f| f.rule == "BR-25").unwrap();
+    assert_eq!(line.line, Some(2));
+    for rule in ["BR-S-2", "BR-DE-1", "BR-DE-7"] {
+        assert!(report.has(rule), "{} missing in {:?}", rule, report);
+    }
+    assert!(
+        !validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .has("BR-DE-7")
+    );
+
+    // The invoice is validated as the writers serialize it.
+    let rules = |report: ValidationReport| -> Vec<String> {
+        report.findings.into_iter().map(|f| f.rule).collect()
+    };
+    let mut invoice = make_test_invoice();
+    invoice.buyer.address.code.clear();
+    let account = invoice.payment_info.take();
+    for (profile, payment_info) in [
+        (EInvoiceProfile::En16931, None),
+        (EInvoiceProfile::XRechnung, account),
+    ] {
+        invoice.payment_info = payment_info;
+        let expected = rules(validate_invoice(&invoice, profile).unwrap());
+        assert_eq!(
+            expected.contains(&"BR-DE-9".to_string()),
+            profile == EInvoiceProfile::XRechnung
+        );
+        let ubl = invoice.to_ubl_xml(profile).unwrap();
+        let cii = invoice.to_cii_xml(profile).unwrap();
+        assert_eq!(rules(validate_xml(&ubl).unwrap()), expected);
+        assert_eq!(rules(validate_xml(&cii).unwrap()), expected);
+    }
+}
+
+#[test]
+fn test_tax_categories_and_exemption_codes() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    invoice.products[0].tax_rate = Decimal::new(7, 2);
+    invoice.products[0].tax_exempt_reason = Some("Not an exemption".to_string());
+    invoice.products[1].tax_category = TaxCategory::ReverseCharge;
+    invoice.products[1].tax_rate = Decimal::ZERO;
+    let mut intra = make_test_invoice().products.remove(0);
+    intra.tax_category = TaxCategory::IntraCommunity;
+    intra.tax_exempt_reason = Some("Intra-community supply of goods".to_string());
+    intra.tax_exemption_code = Some("VATEX-EU-IC".to_string());
+    invoice.products.push(intra);
+
+    let summary = invoice.calculate_summary();
+    let categories: Vec<_> = summary.tax_subtotals.iter().map(|t| t.category).collect();
+    assert_eq!(
+        categories,
+        vec![
+            TaxCategory::Standard,
+            TaxCategory::ReverseCharge,
+            TaxCategory::IntraCommunity
+        ]
+    );
+    let standard = &summary.tax_subtotals[0];
+    assert_eq!(standard.rate, Decimal::new(7, 2));
+    assert!(!standard.tax_amount.is_zero());
+    assert!(standard.exemption_reasons.is_empty());
+    let reverse = &summary.tax_subtotals[1];
+    assert_eq!(
+        reverse.exemption_reasons,
+        vec!["Reverse charge".to_string()]
+    );
+    assert_eq!(reverse.exemption_code.as_deref(), Some("VATEX-EU-AE"));
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains("<cbc:ID>AE</cbc:ID>"));
+    assert!(ubl.contains("<cbc:TaxExemptionReasonCode>VATEX-EU-IC</cbc:TaxExemptionReasonCode>"));
+    assert!(!ubl.contains("Not an exemption"));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains("<ram:CategoryCode>K</ram:CategoryCode>"));
+    assert!(cii.contains("<ram:ExemptionReason>Reverse charge</ram:ExemptionReason>"));
+
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.products[1].tax_category, TaxCategory::ReverseCharge);
+        assert_eq!(parsed.products[1].tax_exempt_reason, None);
+        assert_eq!(
+            parsed.products[2].tax_exempt_reason,
+            invoice.products[2].tax_exempt_reason
+        );
+        assert_eq!(parsed.calculate_summary().total, summary.total);
+    }
+
+    // Reverse charge and intra-community supplies need the buyer's VAT identifier.
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .has("BR-AE-2")
+    );
+    // Reverse charge also accepts the buyer's legal registration identifier.
+    invoice.buyer.legal_registration_id = Some("552100554".to_string());
+    for report in [
+        validate_invoice(&invoice, EInvoiceProfile::En16931).unwrap(),
+        validate_xml(&invoice.to_xml().unwrap()).unwrap(),
+        validate_xml(&invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap()).unwrap(),
+    ] {
+        assert!(!report.has("BR-AE-2"), "{:?}", report);
+        assert!(report.has("BR-IC-2"), "{:?}", report);
+    }
+    invoice.buyer.vat_id = Some("FR12345678901".to_string());
+    let reports = |invoice: &Invoice| {
+        [
+            validate_invoice(invoice, EInvoiceProfile::En16931).unwrap(),
+            validate_xml(&invoice.to_xml().unwrap()).unwrap(),
+            validate_xml(&invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap()).unwrap(),
+        ]
+    };
+    // Intra-community supplies also need the delivery date and country.
+    invoice.delivery_date = None;
+    for report in reports(&invoice) {
+        assert!(report.has("BR-IC-11"), "{:?}", report);
+        assert!(report.has("BR-IC-12"), "{:?}", report);
+        assert!(!report.has("BR-K-2"));
+    }
+    invoice.delivery_date = make_test_invoice().delivery_date;
+    invoice.delivery_country = Some(Country::FR);
+    for report in reports(&invoice) {
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    for (parsed, _) in [
+        Invoice::from_ubl_xml(&invoice.to_xml().unwrap()).unwrap(),
+        Invoice::from_cii_xml(&invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap()).unwrap(),
+    ] {
+        assert_eq!(parsed.delivery_country, Some(Country::FR));
+    }
+
+    let mut out_of_scope = make_test_invoice();
+    out_of_scope.seller.vat_id.clear();
+    out_of_scope.payment_info = invoice.payment_info.clone();
+    for product in &mut out_of_scope.products {
+        product.tax_category = TaxCategory::OutOfScope;
+    }
+    let ubl = out_of_scope.to_xml().unwrap();
+    assert!(!ubl.contains("<cbc:Percent>19</cbc:Percent>"));
+    assert!(ubl.contains("VATEX-EU-O"));
+    let report = validate_xml(&ubl).unwrap();
+    assert!(report.findings.is_empty(), "{:?}", report);
+    assert_eq!(
+        out_of_scope.calculate_summary().tax_total.amount,
+        Decimal::ZERO
+    );
+}
+
+#[test]
+fn test_party_identifiers_and_electronic_address() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    invoice.seller.vat_id = "DE123456789".to_string();
+    invoice.seller.tax_registration_number = Some("12/345/67890".to_string());
+    invoice.seller.legal_registration_id = Some("HRB 12345".to_string());
+    invoice.seller.legal_form = Some("GmbH".to_string());
+    invoice.buyer.vat_id = Some("FR12345678901".to_string());
+    invoice.buyer.legal_registration_id = Some("552100554".to_string());
+    invoice.buyer.electronic_address = Some(ElectronicAddress::new("0009", "55210055400013"));
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(
+        ubl.contains(r#"<cbc:EndpointID schemeID="EM">contact@test.example.com</cbc:EndpointID>"#)
+    );
+    assert!(ubl.contains(r#"<cbc:EndpointID schemeID="0009">55210055400013</cbc:EndpointID>"#));
+    assert!(ubl.contains("<cbc:CompanyLegalForm>GmbH</cbc:CompanyLegalForm>"));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains(r#"<ram:ID schemeID="FC">12/345/67890</ram:ID>"#));
+    assert!(cii.contains(r#"<ram:ID schemeID="VA">FR12345678901</ram:ID>"#));
+    let minimum = invoice.to_cii_xml(EInvoiceProfile::Minimum).unwrap();
+    assert!(!minimum.contains("URIUniversalCommunication"));
+    assert!(minimum.contains("<ram:ID>552100554</ram:ID>"));
+
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.seller.electronic_address, None);
+        assert_eq!(
+            parsed.seller.tax_registration_number,
+            invoice.seller.tax_registration_number
+        );
+        assert_eq!(
+            parsed.seller.legal_registration_id,
+            invoice.seller.legal_registration_id
+        );
+        assert_eq!(parsed.seller.legal_form, invoice.seller.legal_form);
+        assert_eq!(parsed.buyer.vat_id, invoice.buyer.vat_id);
+        assert_eq!(
+            parsed.buyer.legal_registration_id,
+            invoice.buyer.legal_registration_id
+        );
+        assert_eq!(
+            parsed.buyer.electronic_address,
+            invoice.buyer.electronic_address
+        );
+    }
+
+    for report in [
+        validate_invoice(&invoice, EInvoiceProfile::XRechnung).unwrap(),
+        validate_xml(&ubl).unwrap(),
+        validate_xml(&cii).unwrap(),
+    ] {
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    let unscheduled = ubl.replace(r#" schemeID="0009""#, "");
+    assert!(validate_xml(&unscheduled).unwrap().has("BR-63"));
+    invoice.buyer.vat_id = Some("12345678901".to_string());
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .has("BR-CO-9")
+    );
+
+    // The tax registration is enough for standard rated lines, not for exports.
+    let mut registered = make_test_invoice();
+    registered.seller.vat_id.clear();
+    registered.seller.tax_registration_number = Some("12/345/67890".to_string());
+    let reports = |invoice: &Invoice| {
+        [
+            validate_invoice(invoice, EInvoiceProfile::En16931).unwrap(),
+            validate_xml(&invoice.to_ubl_xml(EInvoiceProfile::En16931).unwrap()).unwrap(),
+            validate_xml(&invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap()).unwrap(),
+        ]
+    };
+    for report in reports(&registered) {
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    for product in &mut registered.products {
+        product.tax_category = TaxCategory::Export;
+    }
+    for report in reports(&registered) {
+        assert!(report.has("BR-G-2"), "{:?}", report);
+    }
+
+    let pdf = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert!(pdf.starts_with(b"%PDF"));
+}
+
+#[test]
+fn test_international_addresses() {
+    let address = |street: &str, number: &str, code: &str, town: &str, country| Address {
+        street: street.to_string(),
+        house_number: number.to_string(),
+        additional_lines: Vec::new(),
+        code: code.to_string(),
+        town: town.to_string(),
+        subdivision: None,
+        country,
+    };
+    assert_eq!(
+        address("Prager Straße", "12a", "01067", "Dresden", Country::DE).to_string(),
+        "Prager Straße 12a, 01067 Dresden, Germany"
+    );
+    assert_eq!(
+        address("rue de la Paix", "12", "75002", "Paris", Country::FR).lines(),
+        vec!["12 rue de la Paix", "75002 Paris"]
+    );
+    let mut london = address("Downing Street", "10", "SW1A 2AA", "London", Country::GB);
+    london.additional_lines = vec!["Cabinet Office".to_string()];
+    assert_eq!(
+        london.lines(),
+        vec!["10 Downing Street", "Cabinet Office", "London", "SW1A 2AA"]
+    );
+    let mut washington = address(
+        "Pennsylvania Ave NW",
+        "1600",
+        "20500",
+        "Washington",
+        Country::US,
+    );
+    washington.subdivision = Some("DC".to_string());
+    assert_eq!(
+        washington.to_string(),
+        "1600 Pennsylvania Ave NW, Washington, DC 20500, United States"
+    );
+    assert_eq!(Country::from_code("nl"), Some(Country::NL));
+    assert_eq!(Country::from_code("NLD"), None);
+
+    let mut invoice = make_test_invoice();
+    invoice.seller.address = address("Prager Straße", "12a", "01067", "Dresden", Country::DE);
+    london.additional_lines.push("Room 1".to_string());
+    london.additional_lines.push("Desk 2".to_string());
+    london.subdivision = Some("Greater London".to_string());
+    invoice.buyer.address = london;
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains("<cbc:IdentificationCode>GB</cbc:IdentificationCode>"));
+    assert!(ubl.contains("<cbc:Line>Room 1, Desk 2</cbc:Line>"));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains("<ram:PostcodeCode>01067</ram:PostcodeCode>"));
+
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.seller.address.house_number, "12a");
+        assert_eq!(parsed.seller.address.code, "01067");
+        let buyer = &parsed.buyer.address;
+        assert_eq!(buyer.country, Country::GB);
+        assert_eq!(
+            (buyer.street.as_str(), buyer.house_number.as_str()),
+            ("Downing Street", "10")
+        );
+        assert_eq!(
+            buyer.additional_lines,
+            vec!["Cabinet Office", "Room 1, Desk 2"]
+        );
+        assert_eq!(buyer.subdivision.as_deref(), Some("Greater London"));
+    }
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .is_valid()
+    );
+}
+
+#[test]
+fn test_credit_notes_and_billing_references() {
+    let mut invoice = make_test_invoice();
+    invoice.document_type = DocumentType::CreditNote;
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE00 5001 0517 5407 3249 31".to_string(),
+    )]);
+    let err = invoice.to_xml().unwrap_err();
+    assert!(matches!(err, InvoiceError::Profile(ref e) if e.to_string().contains("BG-3")));
+
+    invoice.billing_reference = Some(BillingReference {
+        number: "TEST-000".to_string(),
+        date: Some(DateTime {
+            year: 2025,
+            month: 6,
+            day: 30,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }),
+    });
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains(
+        "<CreditNote xmlns=\"urn:oasis:names:specification:ubl:schema:xsd:CreditNote-2\""
+    ));
+    assert!(ubl.contains("<cbc:CreditNoteTypeCode>381</cbc:CreditNoteTypeCode>"));
+    assert!(ubl.contains("<cac:CreditNoteLine>"));
+    assert!(!ubl.contains("InvoiceLine"));
+    assert!(ubl.contains("<cbc:ID>TEST-000</cbc:ID>"));
+    let report = validate_xml(&ubl).unwrap();
+    assert!(report.findings.is_empty(), "{:?}", report);
+
+    invoice.document_type = DocumentType::CorrectedInvoice;
+    let cii = invoice.to_cii_xml(EInvoiceProfile::XRechnung).unwrap();
+    assert!(cii.contains("<ram:TypeCode>384</ram:TypeCode>"));
+    assert!(cii.contains("<ram:IssuerAssignedID>TEST-000</ram:IssuerAssignedID>"));
+
+    for (xml, document_type) in [
+        (ubl, DocumentType::CreditNote),
+        (cii, DocumentType::CorrectedInvoice),
+    ] {
+        let (parsed, report) = Invoice::from_einvoice_xml(&xml).unwrap();
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.document_type, document_type);
+        let reference = parsed.billing_reference.unwrap();
+        assert_eq!(reference.number, "TEST-000");
+        assert_eq!(reference.date.map(|d| (d.month, d.day)), Some((6, 30)));
+    }
+    assert_eq!(
+        DocumentType::from_code("386"),
+        Some(DocumentType::PrepaymentInvoice)
+    );
+    assert_eq!(
+        Label::title(DocumentType::SelfBilledInvoice).builtin(&Locale::en),
+        "SELF-BILLED INVOICE"
+    );
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_allowances_and_charges() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    invoice.products[0].allowances_charges = vec![AllowanceCharge {
+        kind: AllowanceChargeKind::Allowance,
+        amount: AllowanceChargeAmount::Percentage(Decimal::new(5, 2)),
+        reason: Some("Volume discount".to_string()),
+        reason_code: Some("95".to_string()),
+        tax_category: TaxCategory::Standard,
+        tax_rate: Decimal::ZERO,
+    }];
+    invoice.allowances_charges = vec![
+        AllowanceCharge {
+            kind: AllowanceChargeKind::Allowance,
+            amount: AllowanceChargeAmount::Percentage(Decimal::new(1, 1)),
+            reason: Some("Early customer discount".to_string()),
+            reason_code: None,
+            tax_category: TaxCategory::Standard,
+            tax_rate: Decimal::new(19, 2),
+        },
+        AllowanceCharge {
+            kind: AllowanceChargeKind::Charge,
+            amount: AllowanceChargeAmount::Fixed(Decimal::new(490, 2)),
+            reason: Some("Shipping".to_string()),
+            reason_code: Some("FC".to_string()),
+            tax_category: TaxCategory::Standard,
+            tax_rate: Decimal::new(19, 2),
+        },
+    ];
+
+    let summary = invoice.calculate_summary();
+    assert_eq!(
+        invoice.products[0].net_amount(Currency::EUR).to_string(),
+        "19.00"
+    );
+    assert_eq!(summary.subtotal.to_string(), "39.00");
+    assert_eq!(summary.allowance_total.to_string(), "3.90");
+    assert_eq!(summary.charge_total.to_string(), "4.90");
+    assert_eq!(summary.net_total.to_string(), "40.00");
+    assert_eq!(summary.tax_total.to_string(), "7.60");
+    assert_eq!(summary.total.to_string(), "47.60");
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains("<cbc:MultiplierFactorNumeric>10</cbc:MultiplierFactorNumeric>"));
+    assert!(ubl.contains(r#"<cbc:BaseAmount currencyID="EUR">39.00</cbc:BaseAmount>"#));
+    assert!(
+        ubl.contains(
+            r#"<cbc:AllowanceTotalAmount currencyID="EUR">3.90</cbc:AllowanceTotalAmount>"#
+        )
+    );
+    assert!(
+        ubl.contains(r#"<cbc:ChargeTotalAmount currencyID="EUR">4.90</cbc:ChargeTotalAmount>"#)
+    );
+    assert!(
+        ubl.contains(r#"<cbc:TaxExclusiveAmount currencyID="EUR">40.00</cbc:TaxExclusiveAmount>"#)
+    );
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains("<ram:AllowanceTotalAmount>3.90</ram:AllowanceTotalAmount>"));
+
+    for xml in [&ubl, &cii] {
+        let report = validate_xml(xml).unwrap();
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::XRechnung)
+            .unwrap()
+            .is_valid()
+    );
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.allowances_charges.len(), 2);
+        assert_eq!(
+            parsed.allowances_charges[0].amount,
+            AllowanceChargeAmount::Percentage(Decimal::new(1, 1))
+        );
+        assert_eq!(
+            parsed.allowances_charges[1].reason_code.as_deref(),
+            Some("FC")
+        );
+        let line = &parsed.products[0].allowances_charges[0];
+        assert_eq!(line.reason.as_deref(), Some("Volume discount"));
+        assert_eq!(parsed.calculate_summary().total, summary.total);
+    }
+
+    let tampered = ubl.replace(
+        r#"<cbc:ChargeTotalAmount currencyID="EUR">4.90"#,
+        r#"<cbc:ChargeTotalAmount currencyID="EUR">5.90"#,
+    );
+    let report = validate_xml(&tampered).unwrap();
+    assert!(
+        report.has("BR-CO-12") && report.has("BR-CO-13"),
+        "{:?}",
+        report
+    );
+
+    invoice.allowances_charges[1].reason = None;
+    invoice.allowances_charges[1].reason_code = None;
+    assert!(matches!(invoice.to_xml(), Err(InvoiceError::Profile(_))));
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .has("BR-38")
+    );
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_prepaid_amounts_and_amount_due() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    invoice.prepaid_amount = Some(Decimal::new(1003, 2));
+    invoice.payments = vec![Payment {
+        date: DateTime {
+            year: 2025,
+            month: 7,
+            day: 1,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        },
+        amount: Decimal::new(5, 0),
+        reference: Some("Installment 1".to_string()),
+    }];
+    invoice.rounding_amount = Some(Decimal::new(3, 2));
+
+    let summary = invoice.calculate_summary();
+    assert_eq!(summary.total.to_string(), "47.60");
+    assert_eq!(summary.prepaid.to_string(), "15.03");
+    assert_eq!(summary.amount_due.to_string(), "32.60");
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains(r#"<cbc:PrepaidAmount currencyID="EUR">15.03</cbc:PrepaidAmount>"#));
+    assert!(ubl.contains(
+        r#"<cbc:PayableRoundingAmount currencyID="EUR">0.03</cbc:PayableRoundingAmount>"#
+    ));
+    assert!(ubl.contains(r#"<cbc:PayableAmount currencyID="EUR">32.60</cbc:PayableAmount>"#));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains("<ram:TotalPrepaidAmount>15.03</ram:TotalPrepaidAmount>"));
+    assert!(cii.contains("<ram:DuePayableAmount>32.60</ram:DuePayableAmount>"));
+    let minimum = invoice.to_cii_xml(EInvoiceProfile::Minimum).unwrap();
+    assert!(!minimum.contains("TotalPrepaidAmount"));
+
+    for xml in [&ubl, &cii, &minimum] {
+        let report = validate_xml(xml).unwrap();
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.prepaid_amount, Some(Decimal::new(1503, 2)));
+        assert_eq!(parsed.rounding_amount, Some(Decimal::new(3, 2)));
+        assert_eq!(parsed.calculate_summary().amount_due, summary.amount_due);
+    }
+
+    let tampered = ubl.replace(
+        r#"<cbc:PayableAmount currencyID="EUR">32.60"#,
+        r#"<cbc:PayableAmount currencyID="EUR">47.60"#,
+    );
+    assert!(validate_xml(&tampered).unwrap().has("BR-CO-16"));
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_units_and_fractional_quantities() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    invoice.products = vec![
+        Product {
+            unit: Unit::HOUR,
+            ..make_product("Consulting", Decimal::new(25, 1), Decimal::new(120, 0))
+        },
+        Product {
+            unit: Unit::KILOGRAM,
+            price_base_quantity: Some(Decimal::from(100)),
+            ..make_product("Copper cable", Decimal::new(125, 1), Decimal::new(850, 0))
+        },
+    ];
+
+    assert_eq!(
+        invoice.products[0].net_amount(Currency::EUR).to_string(),
+        "300.00"
+    );
+    assert_eq!(
+        invoice.products[1].net_amount(Currency::EUR).to_string(),
+        "106.25"
+    );
+    assert_eq!(Unit::from_code("hur"), Some(Unit::HOUR));
+    assert_eq!(Unit::from_code("ls").unwrap().code(), "LS");
+    assert_eq!(Unit::from_code("H-87"), None);
+    let translations = Translations::new().with(
+        Locale::de,
+        Catalog::new().with(Label::Unit(Unit::HOUR), "Stunden"),
+    );
+    assert_eq!(translations.unit(&Locale::de, &Unit::HOUR), "Stunden");
+    assert_eq!(translations.unit(&Locale::de_AT, &Unit::HOUR), "Stunden");
+    assert_eq!(translations.unit(&Locale::fr, &Unit::HOUR), "h");
+    assert_eq!(translations.unit(&Locale::de, &Unit::MONTH), "Monat");
+    assert_eq!(translations.unit(&Locale::ja, &Unit::MONTH), "mo");
+    let pallet = Unit::from_code("XPP").unwrap();
+    assert_eq!(translations.unit(&Locale::en, &pallet), "XPP");
+    assert_eq!(
+        Catalog::builtin(&Locale::de).get(Label::Unit(Unit::PIECE)),
+        Some("Stk.")
+    );
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains(r#"<cbc:InvoicedQuantity unitCode="HUR">2.5</cbc:InvoicedQuantity>"#));
+    assert!(ubl.contains(r#"<cbc:BaseQuantity unitCode="KGM">100</cbc:BaseQuantity>"#));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains(r#"<ram:BilledQuantity unitCode="KGM">12.5</ram:BilledQuantity>"#));
+    assert!(cii.contains(r#"<ram:BasisQuantity unitCode="KGM">100</ram:BasisQuantity>"#));
+
+    for xml in [&ubl, &cii] {
+        let report = validate_xml(xml).unwrap();
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+    ] {
+        assert!(report.is_lossless(), "{:?}", report);
+        assert_eq!(parsed.products[0].quantity, Decimal::new(25, 1));
+        assert_eq!(parsed.products[0].unit, Unit::HOUR);
+        assert_eq!(
+            parsed.products[1].price_base_quantity,
+            Some(Decimal::from(100))
+        );
+        assert_eq!(
+            parsed.calculate_summary().total,
+            invoice.calculate_summary().total
+        );
+    }
+
+    let mismatched = ubl.replace(
+        r#"<cbc:BaseQuantity unitCode="KGM">"#,
+        r#"<cbc:BaseQuantity unitCode="GRM">"#,
+    );
+    let (_, report) = Invoice::from_ubl_xml(&mismatched).unwrap();
+    assert!(!report.is_lossless());
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_item_identifiers_attributes_and_line_notes() {
+    let mut invoice = make_test_invoice();
+    invoice.payment_info = Some(vec![(
+        "IBAN".to_string(),
+        "DE02120300000000202051".to_string(),
+    )]);
+    let product = &mut invoice.products[0];
+    product.note = Some("Delivered to gate 4".to_string());
+    product.seller_item_id = Some("WID-100".to_string());
+    product.buyer_item_id = Some("4711-A".to_string());
+    product.standard_item_id = Some(ItemIdentifier::gtin("4006381333931"));
+    product.classifications = vec![ItemClassification {
+        list_id: "HS".to_string(),
+        list_version: Some("2022".to_string()),
+        code: "84719000".to_string(),
+    }];
+    product.origin_country = Some(Country::IT);
+    product.attributes = vec![
+        ItemAttribute::new("Colour", "Red"),
+        ItemAttribute::new("Size", "XL"),
+    ];
+
+    let ubl = invoice.to_xml().unwrap();
+    assert!(ubl.contains("<cbc:Note>Delivered to gate 4</cbc:Note>"));
+    assert!(ubl.contains("<cac:SellersItemIdentification>"));
+    assert!(ubl.contains("<cbc:ID>WID-100</cbc:ID>"));
+    assert!(ubl.contains(r#"<cbc:ID schemeID="0160">4006381333931</cbc:ID>"#));
+    assert!(ubl.contains(
+        r#"<cbc:ItemClassificationCode listID="HS" listVersionID="2022">84719000</cbc:ItemClassificationCode>"#
+    ));
+    assert!(ubl.contains("<cbc:IdentificationCode>IT</cbc:IdentificationCode>"));
+    assert!(ubl.contains("<cbc:Value>Red</cbc:Value>"));
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    assert!(cii.contains("<ram:BuyerAssignedID>4711-A</ram:BuyerAssignedID>"));
+    assert!(cii.contains("<ram:Description>Size</ram:Description>"));
+    let basic = invoice.to_cii_xml(EInvoiceProfile::Basic).unwrap();
+    assert!(basic.contains(r#"<ram:GlobalID schemeID="0160">4006381333931</ram:GlobalID>"#));
+    assert!(!basic.contains("SellerAssignedID") && !basic.contains("OriginTradeCountry"));
+    let extended = invoice.to_cii_xml(EInvoiceProfile::Extended).unwrap();
+    assert!(extended.contains("<ram:SellerAssignedID>WID-100</ram:SellerAssignedID>"));
+    assert!(extended.contains("<ram:BuyerAssignedID>4711-A</ram:BuyerAssignedID>"));
+    assert!(extended.contains("<ram:ApplicableProductCharacteristic>"));
+    assert!(extended.contains("<ram:DesignatedProductClassification>"));
+
+    for xml in [&ubl, &cii, &basic, &extended] {
+        let report = validate_xml(xml).unwrap();
+        assert!(report.findings.is_empty(), "{:?}", report);
+    }
+    for (parsed, report) in [
+        Invoice::from_ubl_xml(&ubl).unwrap(),
+        Invoice::from_cii_xml(&cii).unwrap(),
+        Invoice::from_cii_xml(&extended).unwrap(),
+    ] {
+        assert!(
+            report.is_lossless() && report.unsupported.is_empty(),
+            "{:?}",
+            report
+        );
+        let line = &parsed.products[0];
+        let product = &invoice.products[0];
+        assert_eq!(line.note, product.note);
+        assert_eq!(line.seller_item_id, product.seller_item_id);
+        assert_eq!(line.buyer_item_id, product.buyer_item_id);
+        assert_eq!(line.standard_item_id, product.standard_item_id);
+        assert_eq!(line.classifications, product.classifications);
+        assert_eq!(line.origin_country, Some(Country::IT));
+        assert_eq!(line.attributes, product.attributes);
+    }
+
+    invoice.products[0].attributes[1].value.clear();
+    match invoice.to_xml().unwrap_err() {
+        InvoiceError::Profile(err) => {
+            assert_eq!(err.missing, vec!["item attribute name and value (BR-54)"])
+        }
+        other => panic!("unexpected error {}", other),
+    }
+    assert!(
+        validate_invoice(&invoice, EInvoiceProfile::En16931)
+            .unwrap()
+            .has("BR-54")
+    );
+    assert!(invoice.to_cii_xml(EInvoiceProfile::Basic).is_ok());
+    let unschemed = ubl.replace(r#"schemeID="0160""#, "");
+    assert!(validate_xml(&unschemed).unwrap().has("BR-64"));
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_localized_pdf_labels() {
+    let builtin = Translations::new();
+    assert_eq!(builtin.text(&Locale::de, Label::BillTo), "RECHNUNG AN");
+    assert_eq!(
+        builtin.text(&Locale::de_AT, Label::UnitPrice),
+        "Einzelpreis"
+    );
+    assert_eq!(builtin.text(&Locale::fr, Label::Subtotal), "Sous-total");
+    assert_eq!(builtin.text(&Locale::it, Label::InvoiceTitle), "FATTURA");
+    assert_eq!(builtin.text(&Locale::es, Label::Quantity), "Cant.");
+    assert_eq!(
+        builtin.text(&Locale::nl, Label::PaymentInformation),
+        "Betalingsgegevens"
+    );
+    assert_eq!(builtin.text(&Locale::ja, Label::BillTo), "BILL TO");
+    assert_eq!(
+        builtin.text(&Locale::en, Label::title(DocumentType::CreditNote)),
+        "CREDIT NOTE"
+    );
+
+    let translations = Translations::new()
+        .with(Locale::de, Catalog::new().with(Label::BillTo, "KUNDE"))
+        .with(Locale::de_CH, Catalog::new().with(Label::VatId, "MWST-Nr."))
+        .with(
+            Locale::pl,
+            Catalog::new()
+                .with(Label::InvoiceTitle, "FAKTURA")
+                .with(Label::BillTo, "NABYWCA"),
+        );
+    assert_eq!(translations.text(&Locale::de, Label::BillTo), "KUNDE");
+    assert_eq!(translations.text(&Locale::de, Label::From), "VON");
+    assert_eq!(translations.text(&Locale::de_CH, Label::VatId), "MWST-Nr.");
+    assert_eq!(translations.text(&Locale::de_CH, Label::BillTo), "KUNDE");
+    assert_eq!(
+        translations.text(&Locale::pl, Label::InvoiceTitle),
+        "FAKTURA"
+    );
+    assert_eq!(translations.text(&Locale::pl, Label::Due), "Due");
+
+    let catalog = Catalog::builtin(&Locale::fr);
+    assert_eq!(catalog.get(Label::AmountDue), Some("NET À PAYER"));
+
+    let mut invoice = make_test_invoice();
+    invoice.locale = Locale::pl;
+    let options = PdfOptions {
+        translations,
+        ..Default::default()
+    };
+    assert!(
+        generate_invoice_pdf_with_options(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            &options
+        )
+        .is_ok()
+    );
+
+    // The metadata title of a hybrid PDF follows the invoice's locale as well.
+    invoice.locale = Locale::de;
+    let options = PdfOptions {
+        hybrid: Some(HybridInvoice::from_invoice(&invoice, EInvoiceProfile::En16931).unwrap()),
+        ..Default::default()
+    };
+    let pdf = generate_invoice_pdf_with_options(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        &options,
+    )
+    .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("Rechnung TEST-001"));
+    assert!(!text.contains("Invoice TEST-001"));
+}
+
+#[test]
+fn test_errors_are_structured() {
+    let invoice = make_test_invoice();
+    let missing = generate_invoice_pdf(&invoice, std::path::Path::new("./fonts/Missing.ttf"), None)
+        .unwrap_err();
+    assert!(matches!(
+        missing,
+        InvoiceError::FontNotFound { ref path, .. } if path.ends_with("Missing.ttf")
+    ));
+
+    let not_a_font =
+        generate_invoice_pdf(&invoice, std::path::Path::new("./Cargo.toml"), None).unwrap_err();
+    assert!(matches!(not_a_font, InvoiceError::InvalidFont { .. }));
+
+    let mut unnamed = make_test_invoice();
+    unnamed.number.clear();
+    match unnamed.to_xml().unwrap_err() {
+        InvoiceError::Profile(err) => assert_eq!(err.missing, vec!["invoice number (BT-1)"]),
+        other => panic!("unexpected error {}", other),
+    }
+
+    // `to_xml` writes XRechnung and therefore needs the seller contact and an IBAN.
+    let mut plain = make_test_invoice();
+    plain.seller.phone = None;
+    plain.seller.email = None;
+    plain.payment_info = None;
+    match plain.to_xml().unwrap_err() {
+        InvoiceError::Profile(err) => assert_eq!(
+            err.missing,
+            vec![
+                "seller contact telephone (BR-DE-6)",
+                "seller contact email (BR-DE-7)",
+                "payment account IBAN (BR-DE-1)",
+            ]
+        ),
+        other => panic!("unexpected error {}", other),
+    }
+    assert!(plain.to_ubl_xml(EInvoiceProfile::En16931).is_ok());
+
+    let truncated = Invoice::from_ubl_xml("<Invoice><cbc:ID>1</cbc:ID>");
+    assert!(matches!(truncated, Err(InvoiceError::Parse(_))));
+    let incomplete = Invoice::from_ubl_xml("<Invoice><cbc:ID>1</cbc:ID></Invoice>");
+    assert!(matches!(incomplete, Err(InvoiceError::Parse(_))));
+    let non_ascii_date = make_test_invoice()
+        .to_xml()
+        .unwrap()
+        .replace("<cbc:IssueDate>2025-07-15<", "<cbc:IssueDate>2025-07-1é<");
+    assert!(matches!(
+        Invoice::from_ubl_xml(&non_ascii_date),
+        Err(InvoiceError::Parse(_))
+    ));
+    let nested = format!(
+        "<Invoice>{}{}</Invoice>",
+        "<a>".repeat(200_000),
+        "</a>".repeat(200_000)
+    );
+    assert!(matches!(
+        Invoice::from_einvoice_xml(&nested),
+        Err(InvoiceError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_currency_minor_units_and_formatting() {
+    let mut invoice = make_test_invoice();
+    invoice.currency = Currency::from_code("jpy").unwrap();
+    invoice.products = vec![Product {
+        tax_rate: Decimal::new(10, 2),
+        ..make_product("Tea", Decimal::from(3), Decimal::new(3335, 1))
+    }];
+    let summary = invoice.calculate_summary();
+    assert_eq!(summary.subtotal.amount, Decimal::new(1001, 0));
+    assert_eq!(summary.total.amount, Decimal::new(1101, 0));
+
+    let xml = invoice.to_xml().unwrap();
+    assert!(xml.contains("<cbc:DocumentCurrencyCode>JPY</cbc:DocumentCurrencyCode>"));
+    assert!(xml.contains(r#"<cbc:PayableAmount currencyID="JPY">1101</cbc:PayableAmount>"#));
+
+    let kwd = Money::new(Decimal::new(12345, 4), Currency::KWD);
+    assert_eq!(kwd.round().to_string(), "1.235");
+    // ISO 4217 minor units, not the cash rounding.
+    let rsd = Currency::from_code("RSD").unwrap();
+    assert_eq!(rsd.minor_units(), 2);
+    assert_eq!(
+        Money::new(Decimal::new(12345, 3), rsd).round().to_string(),
+        "12.35"
+    );
+
+    let eur = Money::new(Decimal::new(123450, 2), Currency::EUR);
+    assert_eq!(format_money(&Locale::de, &eur), "1.234,50\u{a0}€");
+    assert_eq!(format_money(&Locale::en, &eur), "€1,234.50");
+    assert_eq!(format_money(&Locale::en, &summary.total), "¥1,101");
+}
+
+#[test]
+fn test_locale_number_and_percent_formatting() {
+    assert_eq!(
+        format_number(&Locale::de, Decimal::new(12345, 1)),
+        "1.234,5"
+    );
+    assert_eq!(
+        format_number(&Locale::en, Decimal::new(12345, 1)),
+        "1,234.5"
+    );
+    assert_eq!(format_number(&Locale::de, Decimal::new(2500, 3)), "2,5");
+    assert_eq!(format_number(&Locale::en, Decimal::new(-3, 0)), "-3");
+
+    assert_eq!(
+        format_percent(&Locale::fr, Decimal::new(55, 3)),
+        "5,5\u{a0}%"
+    );
+    assert_eq!(
+        format_percent(&Locale::de, Decimal::new(19, 2)),
+        "19\u{a0}%"
+    );
+    assert_eq!(format_percent(&Locale::en, Decimal::new(19, 2)), "19%");
+    assert_eq!(format_percent(&Locale::it, Decimal::new(225, 3)), "22,5%");
+
+    let price = Money::new(Decimal::new(125, 3), Currency::EUR);
+    assert_eq!(format_price(&Locale::de, &price), "0,125\u{a0}€");
+    assert_eq!(format_money(&Locale::de, &price), "0,13\u{a0}€");
+    let whole = Money::new(Decimal::from(5), Currency::EUR);
+    assert_eq!(format_price(&Locale::en, &whole), "€5.00");
+    assert_eq!(format_money(&Locale::en, &-whole), "-€5.00");
+
+    let mut invoice = make_test_invoice();
+    invoice.locale = Locale::fr;
+    invoice.products[0].tax_rate = Decimal::new(55, 3);
+    invoice.products[0].quantity = Decimal::new(15, 1);
+    assert!(
+        generate_invoice_pdf(
+            &invoice,
+            std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+            None
+        )
+        .is_ok()
+    );
+}
+
+/// Counts page objects in an uncompressed krilla PDF.
+fn count_pdf_pages(pdf: &[u8]) -> usize {
+    let needle = b"/Type /Page";
+    pdf.windows(needle.len() + 1)
+        .filter(|w| &w[..needle.len()] == needle && w[needle.len()] != b's')
+        .count()
+}
+
+#[test]
+fn test_long_product_table_breaks_pages() {
+    let mut invoice = make_test_invoice();
+    invoice.products = (0..120)
+        .map(|i| make_product(&format!("Item {}", i), Decimal::from(1), Decimal::new(1, 0)))
+        .collect();
+
+    let pdf_bytes = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert!(count_pdf_pages(&pdf_bytes) >= 3);
+
+    let single = generate_invoice_pdf(
+        &make_test_invoice(),
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(count_pdf_pages(&single), 1);
+
+    // Settled payments longer than a page continue on the next one.
+    let mut paid = make_test_invoice();
+    paid.payments = (1..=80)
+        .map(|i| Payment {
+            date: paid.date,
+            amount: Decimal::new(1, 2),
+            reference: Some(format!("Installment {}", i)),
+        })
+        .collect();
+    let pdf_bytes = generate_invoice_pdf(
+        &paid,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(count_pdf_pages(&pdf_bytes), 3);
+}
+
+#[test]
+fn test_descriptions_wrap_without_truncation() {
+    let font_bytes = std::fs::read("./fonts/OpenSans-Medium.ttf").unwrap();
+    let measurer = TextMeasurer::new(&font_bytes).unwrap();
+    let description = "Größenverstellbare Bürodrehstühle mit Armlehnen, Lordosenstütze und \
+                       Synchronmechanik – Lieferung inklusive Montage vor Ort";
+    let lines = measurer.wrap(description, 9.0, 120.0);
+    assert!(lines.len() > 1);
+    assert!(lines.iter().all(|l| measurer.width(l, 9.0) <= 120.0));
+    assert_eq!(lines.join(" "), description);
+
+    let mut invoice = make_test_invoice();
+    invoice.products[0].description = description.repeat(40);
+    invoice.products[1].description = "日本語の説明文".repeat(30);
+    let pdf_bytes = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert!(count_pdf_pages(&pdf_bytes) >= 2);
+}
+
+#[test]
+fn test_pdf_renders_delivery_contact_and_exemption_notes() {
+    let mut invoice = make_test_invoice();
+    invoice.delivery_type = Some("DHL Express".to_string());
+    invoice.payment_type = Some("SEPA credit transfer".to_string());
+    invoice.extra_info = Some(vec![
+        ("Order".to_string(), "PO-4711".to_string()),
+        ("Project".to_string(), "Office refurbishment".to_string()),
+    ]);
+    invoice.products[1].tax_category = TaxCategory::ReverseCharge;
+    invoice.products[1].tax_rate = Decimal::ZERO;
+    invoice.products[1].tax_exempt_reason =
+        Some("Reverse charge – Art. 196 Directive 2006/112/EC".to_string());
+
+    let pdf_bytes = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(count_pdf_pages(&pdf_bytes), 1);
+
+    invoice.products = (0..120)
+        .map(|i| Product {
+            tax_rate: Decimal::ZERO,
+            tax_category: TaxCategory::ReverseCharge,
+            tax_exempt_reason: Some("Reverse charge".to_string()),
+            ..make_product(&format!("Item {}", i), Decimal::from(1), Decimal::new(1, 0))
+        })
+        .collect();
+    let pdf_bytes = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert!(count_pdf_pages(&pdf_bytes) >= 3);
+}
+
+#[test]
+fn test_pdf_theme() {
+    let default = Theme::default();
+    assert_eq!(default.page, PageSize::A4);
+    assert_eq!(default.margins.left, 40.0);
+    assert_eq!(default.footer.right, Slot::PageNumber);
+    assert_eq!(default.body.line_height(), 11.25);
+
+    let mut theme = Theme {
+        page: PageSize::from_mm(148.0, 210.0),
+        ..Default::default()
+    };
+    theme.margins.top = 60.0;
+    theme.margins.left = 30.0;
+    theme.margins.right = 30.0;
+    theme.columns.description = 200.0;
+    theme.columns.buyer = 220.0;
+    theme.title.color = Color::rgb(0, 82, 147);
+    theme.title.font = FontRole::Bold;
+    theme.total.size = 14.0;
+    theme.rule.width = 0.5;
+    theme.rule.color = Color::rgb(200, 200, 200);
+    theme.header.right = Slot::PageNumber;
+    theme.footer.left = Slot::Text("Brand GmbH · Registered in Berlin".to_string());
+    theme.footer.right = Slot::SellerContact;
+    let options = PdfOptions {
+        theme,
+        ..Default::default()
+    };
+
+    let mut invoice = make_test_invoice();
+    invoice.products = (0..60)
+        .map(|i| make_product(&format!("Item {}", i), Decimal::from(1), Decimal::new(1, 0)))
+        .collect();
+    let a4 = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    let a5 = generate_invoice_pdf_with_options(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        &options,
+    )
+    .unwrap();
+    assert!(count_pdf_pages(&a5) > count_pdf_pages(&a4));
+
+    // Rows grow with the text sizes and spacing.
+    let mut large = Theme::default();
+    large.text.size = 14.0;
+    large.body.size = 12.0;
+    large.body.line_spacing = 1.5;
+    large.columns.gap = 20.0;
+    let options = PdfOptions {
+        theme: large,
+        ..Default::default()
+    };
+    let large = generate_invoice_pdf_with_options(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        &options,
+    )
+    .unwrap();
+    assert!(count_pdf_pages(&large) > count_pdf_pages(&a4));
+}
+
+#[test]
+fn test_font_sets() {
+    let invoice = make_test_invoice();
+    let options = PdfOptions::default();
+    let family =
+        generate_invoice_pdf_with_fonts(&invoice, &FontSet::open_sans("./fonts"), &options)
+            .unwrap();
+    assert_eq!(count_pdf_pages(&family), 1);
+
+    let regular = std::fs::read("./fonts/OpenSans-Regular.ttf").unwrap();
+    let bold = std::fs::read("./fonts/OpenSans-Bold.ttf").unwrap();
+    let fonts = FontSet::from_bytes(regular)
+        .with(FontRole::Bold, FontSource::Bytes(bold))
+        .with(
+            FontRole::Italic,
+            FontSource::Path("./fonts/OpenSans-Italic.ttf".into()),
+        );
+    let mut theme = Theme::default();
+    theme.body.font = FontRole::Italic;
+    let themed = PdfOptions {
+        theme,
+        ..Default::default()
+    };
+    assert!(generate_invoice_pdf_with_fonts(&invoice, &fonts, &themed).is_ok());
+
+    let garbage = FontSet::from_bytes(b"not a font".to_vec());
+    assert!(matches!(
+        generate_invoice_pdf_with_fonts(&invoice, &garbage, &options),
+        Err(InvoiceError::InvalidFont { path: None })
+    ));
+    let missing_bold = FontSet::from_path("./fonts/OpenSans-Medium.ttf").with(
+        FontRole::Bold,
+        FontSource::Path("./fonts/Missing-Bold.ttf".into()),
+    );
+    assert!(matches!(
+        generate_invoice_pdf_with_fonts(&invoice, &missing_bold, &options),
+        Err(InvoiceError::FontNotFound { ref path, .. }) if path.ends_with("Missing-Bold.ttf")
+    ));
+
+    #[cfg(feature = "embedded-font")]
+    assert!(generate_invoice_pdf_with_fonts(&invoice, &FontSet::default(), &options).is_ok());
+}
+
+#[test]
+fn test_logo_from_path_bytes_and_svg() {
+    let invoice = make_test_invoice();
+    let font = std::path::Path::new("./fonts/OpenSans-Medium.ttf");
+
+    let logo_path = std::path::Path::new("./res/logo.jpg");
+    let with_logo = generate_invoice_pdf(&invoice, font, Some(logo_path)).unwrap();
+    assert!(with_logo.windows(13).any(|w| w == b"/Subtype /Ima"));
+
+    let jpeg = Logo::from_bytes(std::fs::read("./res/logo.jpg").unwrap());
+    let graphic = jpeg.load().unwrap();
+    let (width, height) = jpeg.fitted_size(&graphic);
+    assert!(width <= Logo::DEFAULT_MAX_WIDTH + 0.01 && height <= Logo::DEFAULT_MAX_HEIGHT + 0.01);
+    assert!(
+        (width - Logo::DEFAULT_MAX_WIDTH).abs() < 0.01
+            || (height - Logo::DEFAULT_MAX_HEIGHT).abs() < 0.01
+    );
+
+    let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+        <rect x="10" y="10" width="180" height="80" fill="#c0392b"/>
+        <circle cx="100" cy="50" r="30" fill="none" stroke="#ffffff" stroke-width="4"/>
+    </svg>"##;
+    let mut svg_logo = Logo::from_bytes(svg.to_vec());
+    svg_logo.max_width = 100.0;
+    svg_logo.max_height = 100.0;
+    assert_eq!(
+        svg_logo.fitted_size(&svg_logo.load().unwrap()),
+        (100.0, 50.0)
+    );
+
+    let options = PdfOptions {
+        logo: Some(svg_logo),
+        ..Default::default()
+    };
+    let pdf_bytes = generate_invoice_pdf_with_options(&invoice, font, &options).unwrap();
+    assert!(!pdf_bytes.is_empty());
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+#[test]
+fn test_hybrid_pdf_is_pdfa3_with_facturx_attachment() {
+    let invoice = make_test_invoice();
+    let hybrid = HybridInvoice::from_invoice(&invoice, EInvoiceProfile::En16931).unwrap();
+    assert_eq!(
+        hybrid.xml,
+        invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap()
+    );
+    let options = PdfOptions {
+        hybrid: Some(hybrid),
+        ..Default::default()
+    };
+    let pdf = generate_invoice_pdf_with_options(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        &options,
+    )
+    .unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+
+    assert!(text.contains("<pdfaid:part>3</pdfaid:part>"));
+    assert!(text.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+    assert!(text.contains("/AFRelationship /Alternative"));
+    assert!(text.contains("(factur-x.xml)"));
+    assert!(text.contains("<pdfaSchema:prefix>fx</pdfaSchema:prefix>"));
+    assert!(text.contains("<fx:DocumentFileName>factur-x.xml</fx:DocumentFileName>"));
+    assert!(text.contains("<fx:ConformanceLevel>EN 16931</fx:ConformanceLevel>"));
+
+    // The patched metadata stream and cross-reference table must still line up.
+    let metadata = find_bytes(&pdf, b"/Type /Metadata").unwrap();
+    let data_start = metadata + find_bytes(&pdf[metadata..], b"stream\n").unwrap() + 7;
+    let data_end = data_start + find_bytes(&pdf[data_start..], b"\nendstream").unwrap();
+    assert!(text.contains(&format!(
+        "/Length {}\n  /Type /Metadata",
+        data_end - data_start
+    )));
+    let tail = std::str::from_utf8(&pdf[pdf.len() - 32..]).unwrap();
+    let startxref: usize = tail
+        .rsplit("startxref\n")
+        .next()
+        .unwrap()
+        .trim_end_matches("%%EOF")
+        .trim()
+        .parse()
+        .unwrap();
+    let xref = std::str::from_utf8(&pdf[startxref..]).unwrap();
+    assert!(xref.starts_with("xref"));
+    for (number, entry) in xref
+        .lines()
+        .skip(2)
+        .take_while(|l| l.len() >= 18)
+        .enumerate()
+    {
+        if entry.trim_end().ends_with('n') {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj", number).as_bytes()));
+        }
+    }
+    assert_eq!(facturx::check_xref(&pdf), Ok(()));
+    let mut shifted = pdf.clone();
+    shifted.insert(20, b' ');
+    assert!(facturx::check_xref(&shifted).is_err());
+
+    // The output intent carries the bundled sRGB profile.
+    let intent = text.split("/DestOutputProfile ").nth(1).unwrap();
+    let profile_obj = format!("\n{} 0 obj", intent.split(' ').next().unwrap());
+    let profile = find_bytes(&pdf, profile_obj.as_bytes()).unwrap();
+    let stream_start = profile + find_bytes(&pdf[profile..], b"stream\n").unwrap() + 7;
+    let mut decoder = flate2::read::ZlibDecoder::new(&pdf[stream_start..]);
+    let mut icc = Vec::new();
+    io::Read::read_to_end(&mut decoder, &mut icc).unwrap();
+    assert_eq!(icc, SRGB_ICC);
+}
+
+#[test]
+fn test_extract_invoice_from_hybrid_pdf() {
+    let invoice = make_test_invoice();
+    let cii = invoice.to_cii_xml(EInvoiceProfile::En16931).unwrap();
+    let options = PdfOptions {
+        hybrid: Some(HybridInvoice::new(cii.clone(), "EN 16931")),
+        ..Default::default()
+    };
+    let pdf = generate_invoice_pdf_with_options(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        &options,
+    )
+    .unwrap();
+
+    let hybrid = extract_embedded_xml(&pdf).unwrap();
+    assert_eq!(hybrid.xml, cii);
+    assert_eq!(hybrid.file_name, "factur-x.xml");
+    assert_eq!(hybrid.conformance_level, "EN 16931");
+    assert_eq!(hybrid.version, "1.0");
+
+    let (parsed, report) = Invoice::from_pdf(&pdf).unwrap();
+    assert!(report.is_lossless(), "{:?}", report);
+    assert_eq!(parsed.number, invoice.number);
+    assert_eq!(
+        parsed.calculate_summary().total,
+        invoice.calculate_summary().total
+    );
+
+    let plain = generate_invoice_pdf(
+        &invoice,
+        std::path::Path::new("./fonts/OpenSans-Medium.ttf"),
+        None,
+    )
+    .unwrap();
+    assert!(Invoice::from_pdf(&plain).is_err());
+
+    // ZUGFeRD 2.x name, UTF-16 file name and the file spec inside a compressed object stream.
+    let ubl = invoice.to_xml().unwrap();
+    let deflate = |data: &[u8]| {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    };
+    let name = "zugferd-invoice.xml"
+        .bytes()
+        .map(|b| format!("00{:02X}", b))
+        .collect::<String>();
+    let spec = format!(
+        "<< /Type /Filespec /UF <FEFF{}> /EF << /F 3 0 R >> >>",
+        name
+    );
+    let objects = deflate(format!("2 0 {}", spec).as_bytes());
+    let file = deflate(ubl.as_bytes());
+    let mut handmade =
+        b"%PDF-1.7\n1 0 obj\n<< /Type /ObjStm /N 1 /First 4 /Filter /FlateDecode /Length ".to_vec();
+    handmade.extend_from_slice(format!("{} >>\nstream\n", objects.len()).as_bytes());
+    handmade.extend_from_slice(&objects);
+    handmade.extend_from_slice(
+        b"\nendstream\nendobj\n3 0 obj\n\
+          << /Type /EmbeddedFile /Filter [/FlateDecode] /Length 4 0 R >>\nstream\n",
+    );
+    handmade.extend_from_slice(&file);
+    handmade.extend_from_slice(
+        format!(
+            "\nendstream\nendobj\n4 0 obj\n{}\nendobj\n%%EOF\n",
+            file.len()
+        )
+        .as_bytes(),
+    );
+
+    let hybrid = extract_embedded_xml(&handmade).unwrap();
+    assert_eq!(hybrid.file_name, "zugferd-invoice.xml");
+    assert_eq!(hybrid.xml, ubl);
+    assert!(Invoice::from_pdf(&handmade).unwrap().1.is_lossless());
+}
+
+#[test]
+fn test_extract_rejects_hostile_pdfs() {
+    // Deeply nested arrays and dictionaries are skipped instead of overflowing the stack.
+    for open in ["[", "<<"] {
+        let mut nested = b"%PDF-1.7\n1 0 obj\n".to_vec();
+        nested.extend(open.repeat(1_000_000).bytes());
+        assert!(matches!(
+            extract_embedded_xml(&nested),
+            Err(InvoiceError::Parse(_))
+        ));
+    }
+
+    // A Flate stream that inflates beyond the limit is an error, not an allocation.
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(&vec![0; 65 << 20]).unwrap();
+    let bomb = encoder.finish().unwrap();
+    let mut pdf = b"%PDF-1.7\n1 0 obj\n\
+        << /Type /Filespec /F (factur-x.xml) /EF << /F 2 0 R >> >>\nendobj\n\
+        2 0 obj\n<< /Type /EmbeddedFile /Filter /FlateDecode /Length "
+        .to_vec();
+    pdf.extend_from_slice(format!("{} >>\nstream\n", bomb.len()).as_bytes());
+    pdf.extend_from_slice(&bomb);
+    pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+    match extract_embedded_xml(&pdf) {
+        Err(InvoiceError::Parse(message)) => assert!(message.contains("exceeds"), "{}", message),
+        _ => panic!("the oversized stream was accepted"),
+    }
+}